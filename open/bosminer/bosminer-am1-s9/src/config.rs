@@ -52,9 +52,13 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 use std::fs;
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
+use ii_async_compat::tokio;
+use tokio::sync::watch;
+
 /// Hardware revision
 pub const HW_MODEL: &'static str = "Antminer S9";
 
@@ -67,8 +71,8 @@ pub const FORMAT_MODEL: &'static str = HW_MODEL;
 /// Override the default drain channel size as miner tends to burst messages into the logger
 pub const ASYNC_LOGGER_DRAIN_CHANNEL_SIZE: usize = 4096;
 
-/// Location of default config
-/// TODO: Maybe don't add `.toml` prefix so we could use even JSON
+/// Location of default config - the extension picks which `SerializationFormat` `FormatWrapper`
+/// reads/writes it as, so this could just as well end in `.json` or `.yaml`
 pub const DEFAULT_CONFIG_PATH: &'static str = "/etc/bosminer.toml";
 
 /// Default Hardware ID path
@@ -133,6 +137,13 @@ pub const FAN_SPEED_MAX: usize = 100;
 pub const FANS_MIN: usize = 0;
 pub const FANS_MAX: usize = 4;
 
+/// Default `[autotune]` settings - autotuning is opt-in, targets just under the default
+/// `hot_temp` and steps slowly enough that it never overshoots before the next tick reacts
+pub const DEFAULT_AUTOTUNE_ENABLED: bool = false;
+pub const DEFAULT_AUTOTUNE_TARGET_TEMP_C: f64 = 95.0;
+pub const DEFAULT_AUTOTUNE_STEP_MHZ: f64 = 6.25;
+pub const DEFAULT_AUTOTUNE_INTERVAL_SECS: u64 = 300;
+
 /// Default ASIC difficulty
 pub const DEFAULT_ASIC_DIFFICULTY: usize = 64;
 
@@ -149,7 +160,79 @@ pub struct ResolvedChainConfig {
     pub enabled: bool,
 }
 
-#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+/// Resolved `[autotune]` settings, see `Autotune`/`AutotuneController`
+#[derive(Debug, Clone, Copy)]
+pub struct AutotuneConfig {
+    pub enabled: bool,
+    pub target_temp: f32,
+    pub max_frequency: f64,
+    pub step_mhz: f64,
+    pub interval: Duration,
+}
+
+/// Closed-loop autotuner that gradually raises or lowers one hash chain's frequency to keep its
+/// measured board temperature just under `AutotuneConfig::target_temp`, clamped to
+/// `FREQUENCY_MHZ_MIN..=AutotuneConfig::max_frequency` (itself clamped to `FREQUENCY_MHZ_MAX`). A
+/// slow step controller rather than a PID: each `AutotuneConfig::interval` tick nudges frequency
+/// by at most `step_mhz`, which is enough to converge without overshoot on a system whose thermal
+/// response lags the electrical one by tens of seconds.
+///
+/// `current_frequency` is the converged value - seed a fresh `AutotuneController` from
+/// `resolve_chain_config`'s frequency rather than `DEFAULT_FREQUENCY_MHZ` so a restart resumes
+/// near where it left off instead of re-ramping from scratch, and persist every changed value
+/// back out (e.g. via `Backend::set_chain_config` followed by `api::Command::CommitToFile`) so
+/// the next restart has it to seed from.
+#[derive(Debug, Clone, Copy)]
+pub struct AutotuneController {
+    current_frequency: f64,
+}
+
+impl AutotuneController {
+    pub fn new(initial_frequency: f64) -> Self {
+        Self {
+            current_frequency: initial_frequency,
+        }
+    }
+
+    pub fn current_frequency(&self) -> f64 {
+        self.current_frequency
+    }
+
+    /// Advance by one `interval` tick and return the (possibly unchanged) frequency the chain
+    /// should now run at. `measured_temp` is `None` when temperature control is disabled
+    /// (`TempControlMode::Disabled`) or the reading failed - autotuning refuses to run blind and
+    /// leaves the frequency untouched rather than guessing. `dangerous_temp` triggers a hard
+    /// back-off straight toward `FREQUENCY_MHZ_MIN` instead of a single `step_mhz` nudge, the
+    /// same urgency `ChainTemperature`'s dangerous-temp handling in `monitor` gives fan control.
+    pub fn step(
+        &mut self,
+        measured_temp: Option<f32>,
+        dangerous_temp: f32,
+        cfg: &AutotuneConfig,
+    ) -> f64 {
+        if !cfg.enabled {
+            return self.current_frequency;
+        }
+        let measured_temp = match measured_temp {
+            Some(measured_temp) => measured_temp,
+            None => return self.current_frequency,
+        };
+
+        let max_frequency = cfg.max_frequency.min(FREQUENCY_MHZ_MAX);
+        let target_frequency = if measured_temp >= dangerous_temp {
+            FREQUENCY_MHZ_MIN
+        } else if measured_temp >= cfg.target_temp {
+            self.current_frequency - cfg.step_mhz
+        } else {
+            self.current_frequency + cfg.step_mhz
+        };
+
+        self.current_frequency = target_frequency.max(FREQUENCY_MHZ_MIN).min(max_frequency);
+        self.current_frequency
+    }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum TempControlMode {
     Auto,
@@ -219,6 +302,41 @@ pub struct FanControl {
     min_fans: Option<usize>,
 }
 
+/// Closed-loop frequency autotuning, see `AutotuneConfig`/`AutotuneController`
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Autotune {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_temp: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_frequency: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step_mhz: Option<f64>,
+    /// Seconds between ticks
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interval: Option<u64>,
+}
+
+/// A named set of overrides selectable at runtime via `Backend::active_profile` - e.g. a quiet
+/// `eco` profile for daytime and a high-clock `turbo` profile for the night, without rewriting
+/// every hash chain's frequency/voltage by hand. Layered between the global defaults and any
+/// top-level/per-chain override, see `Backend::resolve_chain_values`/`resolve_monitor_config`.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_chain_global: Option<HashChainGlobal>,
+    #[serde(rename = "hash_chain")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash_chains: Option<BTreeMap<String, HashChain>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temp_control: Option<TempControl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fan_control: Option<FanControl>,
+}
+
 #[derive(Serialize, Deserialize, Default, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct Backend {
@@ -238,6 +356,14 @@ pub struct Backend {
     temp_control: Option<TempControl>,
     #[serde(skip_serializing_if = "Option::is_none")]
     fan_control: Option<FanControl>,
+    /// Named override sets selectable via `active_profile` - see `Profile`
+    #[serde(rename = "profile")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profiles: Option<BTreeMap<String, Profile>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active_profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    autotune: Option<Autotune>,
     #[serde(rename = "group")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub groups: Option<Vec<bosminer_config::GroupConfig>>,
@@ -262,6 +388,95 @@ where
     fn metadata() -> serde_json::Value;
 
     fn variant() -> String;
+
+    /// Upgrade steps from older format versions to `version()`, applied in `FormatWrapper::parse`
+    /// when a config file's version isn't supported directly. Empty until a future version bump
+    /// needs one - see `Migration`.
+    fn migrations() -> Vec<Migration>;
+}
+
+/// One upgrade step between adjacent config format versions, registered via
+/// `ConfigBody::migrations`. `transform` receives the whole parsed file - including the `format`
+/// wrapper section - as a generic JSON value, and returns it rewritten for the `to` version;
+/// `FormatWrapper` takes care of stamping `format.version` with `to` itself once `transform`
+/// succeeds, so `transform` only needs to worry about the body's own shape.
+pub struct Migration {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub transform: fn(serde_json::Value) -> Result<serde_json::Value, String>,
+}
+
+/// On-disk serialization formats `FormatWrapper` can read and write - picked from `config_path`'s
+/// extension by `SerializationFormat::from_extension`, or by trial-deserialization when the
+/// extension doesn't say. `save` always re-serializes into whichever format `parse` detected, so
+/// e.g. a JSON-authored config round-trips as JSON instead of silently becoming TOML.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum SerializationFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+/// All variants, tried in this order when `config_path`'s extension doesn't identify a format -
+/// TOML first, since it's what every config on disk predates this feature with
+const ALL_SERIALIZATION_FORMATS: [SerializationFormat; 3] = [
+    SerializationFormat::Toml,
+    SerializationFormat::Json,
+    SerializationFormat::Yaml,
+];
+
+impl Default for SerializationFormat {
+    fn default() -> Self {
+        Self::Toml
+    }
+}
+
+impl SerializationFormat {
+    fn from_extension(config_path: &str) -> Option<Self> {
+        match Path::new(config_path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+        {
+            Some("toml") => Some(Self::Toml),
+            Some("json") => Some(Self::Json),
+            Some("yaml") | Some("yml") => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Deserialize `config_path` as `self`, folding every backend's error into the same
+    /// `FormatWrapperError::ParsingError` variant `bosminer_config::parse` already uses
+    fn deserialize<B, T: DeserializeOwned>(
+        self,
+        config_path: &str,
+    ) -> Result<T, FormatWrapperError<B>> {
+        match self {
+            Self::Toml => {
+                bosminer_config::parse(config_path).map_err(FormatWrapperError::ParsingError)
+            }
+            Self::Json => {
+                let contents = fs::read_to_string(config_path)
+                    .map_err(|err| FormatWrapperError::ParsingError(err.to_string()))?;
+                serde_json::from_str(&contents)
+                    .map_err(|err| FormatWrapperError::ParsingError(err.to_string()))
+            }
+            Self::Yaml => {
+                let contents = fs::read_to_string(config_path)
+                    .map_err(|err| FormatWrapperError::ParsingError(err.to_string()))?;
+                serde_yaml::from_str(&contents)
+                    .map_err(|err| FormatWrapperError::ParsingError(err.to_string()))
+            }
+        }
+    }
+
+    /// Serialize `value` in `self`'s format, matching `deserialize`'s error handling
+    fn serialize<T: Serialize>(self, value: &T) -> Result<String, String> {
+        match self {
+            Self::Toml => toml::to_string(value).map_err(|err| err.to_string()),
+            Self::Json => serde_json::to_string_pretty(value).map_err(|err| err.to_string()),
+            Self::Yaml => serde_yaml::to_string(value).map_err(|err| err.to_string()),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -291,6 +506,10 @@ pub struct FormatWrapper<B> {
     format: Format,
     #[serde(flatten)]
     pub body: B,
+    /// Which `SerializationFormat` this was last `parse`d from - not part of the file contents
+    /// itself, just metadata `save` uses to round-trip back into the same format
+    #[serde(skip)]
+    source_format: SerializationFormat,
 }
 
 impl<B> FormatWrapper<B>
@@ -326,18 +545,97 @@ where
     }
 
     pub fn parse(config_path: &str) -> Result<Self, FormatWrapperError<B>> {
-        // Parse config file - either user specified or the default one
-        let mut config: Self = bosminer_config::parse(config_path)
-            .map_err(|msg| FormatWrapperError::ParsingError(msg))?;
+        let (mut config, source_format) = Self::parse_detecting_format(config_path)?;
+        config.source_format = source_format;
 
         match config.sanity_check() {
             Ok(_) => Ok(config),
-            Err(FormatWrapperError::IncompatibleVersion(version, _)) => Err(
-                FormatWrapperError::IncompatibleVersion(version, Some(config)),
-            ),
+            Err(FormatWrapperError::IncompatibleVersion(version, _)) => {
+                Self::migrate(config_path, source_format, version)
+            }
             Err(e) => Err(e),
         }
     }
+
+    /// Read `config_path` as whichever `SerializationFormat` its extension names, or - if the
+    /// extension doesn't match one of them - by trying each format in turn against the file
+    /// contents, returning the first that parses.
+    fn parse_detecting_format(
+        config_path: &str,
+    ) -> Result<(Self, SerializationFormat), FormatWrapperError<B>> {
+        if let Some(format) = SerializationFormat::from_extension(config_path) {
+            return Ok((format.deserialize(config_path)?, format));
+        }
+
+        let mut last_err = None;
+        for format in ALL_SERIALIZATION_FORMATS.iter().copied() {
+            match format.deserialize(config_path) {
+                Ok(config) => return Ok((config, format)),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        Err(last_err.expect("ALL_SERIALIZATION_FORMATS is never empty"))
+    }
+
+    /// Serialize `self` back to `config_path` in `source_format`, used after a successful
+    /// migration so the upgraded format version (and anything a transform changed) is persisted
+    /// instead of being re-derived on every boot.
+    pub fn save(&self, config_path: &str) -> Result<(), FormatWrapperError<B>> {
+        let contents = self
+            .source_format
+            .serialize(self)
+            .map_err(FormatWrapperError::ParsingError)?;
+        fs::write(config_path, contents)
+            .map_err(|err| FormatWrapperError::ParsingError(err.to_string()))?;
+        Ok(())
+    }
+
+    /// Walk `B::migrations()` from `version` up to `B::version()`, applying each matching
+    /// transform to the raw config in turn, re-stamping `format.version` after each step. Bails
+    /// with the original `IncompatibleVersion` if no migration covers the current version, or if
+    /// the chain revisits a version it's already seen (a cycle in the registered migrations).
+    fn migrate(
+        config_path: &str,
+        source_format: SerializationFormat,
+        mut version: String,
+    ) -> Result<Self, FormatWrapperError<B>> {
+        let migrations = B::migrations();
+        let mut value: serde_json::Value = source_format.deserialize(config_path)?;
+
+        let mut seen_versions = HashSet::new();
+        while version != B::version() {
+            if !seen_versions.insert(version.clone()) {
+                return Err(FormatWrapperError::IncompatibleVersion(version, None));
+            }
+
+            let migration = migrations
+                .iter()
+                .find(|migration| migration.from == version)
+                .ok_or_else(|| FormatWrapperError::IncompatibleVersion(version.clone(), None))?;
+
+            value = (migration.transform)(value)
+                .map_err(|msg| FormatWrapperError::IncorrectBody(msg))?;
+            version = migration.to.to_string();
+
+            if let Some(format) = value.get_mut("format").and_then(|f| f.as_object_mut()) {
+                format.insert(
+                    "version".to_string(),
+                    serde_json::Value::String(version.clone()),
+                );
+            }
+        }
+
+        let mut config: Self = serde_json::from_value(value)
+            .map_err(|err| FormatWrapperError::ParsingError(err.to_string()))?;
+        config.source_format = source_format;
+        config.sanity_check()?;
+
+        if let Err(err) = config.save(config_path) {
+            warn!("Failed to persist migrated configuration: {}", err);
+        }
+
+        Ok(config)
+    }
 }
 
 impl Backend {
@@ -354,78 +652,230 @@ impl Backend {
         }
     }
 
-    pub fn resolve_chain_config(&self, hash_chain_idx: usize) -> ResolvedChainConfig {
-        // Take global hash chain configuration or default value
+    /// The currently selected `Profile`, if `active_profile` names one that still exists in
+    /// `profiles` - a renamed/removed profile is treated the same as none being active rather
+    /// than an error, same as any other dangling config reference in this crate
+    fn active_profile(&self) -> Option<&Profile> {
+        let name = self.active_profile.as_ref()?;
+        self.profiles.as_ref()?.get(name)
+    }
+
+    /// Switch `active_profile`, validating that `name` actually names a profile in `profiles`.
+    /// Passing `None` clears it, falling back to the top-level/per-chain config untouched by any
+    /// profile. See `config::api::Command::SetActiveProfile`.
+    pub fn set_active_profile(&mut self, name: Option<String>) -> Result<(), api::Error> {
+        if let Some(name) = &name {
+            if !self.profiles.as_ref().map_or(false, |p| p.contains_key(name)) {
+                return Err(api::Error::UnknownProfile(name.clone()));
+            }
+        }
+        self.active_profile = name;
+        Ok(())
+    }
+
+    /// Resolve hash chain `hash_chain_idx`'s frequency (MHz), voltage (V) and enabled flag,
+    /// layering (lowest to highest priority): global defaults, the active profile's global hash
+    /// chain override, the top-level global override, the active profile's per-chain override,
+    /// then the top-level per-chain override - so an explicit per-chain tweak always survives a
+    /// profile switch. The plain-value core both `resolve_chain_config` and `config::api`'s live
+    /// `get_chain_config`/`set_chain_config` build on.
+    fn resolve_chain_values(&self, hash_chain_idx: usize) -> (f64, f64, bool) {
+        let profile = self.active_profile();
+
         let overridable = self
             .hash_chain_global
             .as_ref()
             .and_then(|v| v.overridable.as_ref());
+        let profile_overridable = profile
+            .and_then(|p| p.hash_chain_global.as_ref())
+            .and_then(|v| v.overridable.as_ref());
+
         let mut frequency = OptionDefault::new(
-            overridable.as_ref().and_then(|v| v.frequency),
+            overridable
+                .and_then(|v| v.frequency)
+                .or_else(|| profile_overridable.and_then(|v| v.frequency)),
             DEFAULT_FREQUENCY_MHZ,
         );
         let mut voltage = OptionDefault::new(
-            overridable.as_ref().and_then(|v| v.voltage),
+            overridable
+                .and_then(|v| v.voltage)
+                .or_else(|| profile_overridable.and_then(|v| v.voltage)),
             DEFAULT_VOLTAGE_V,
         );
         let mut enabled = DEFAULT_HASH_CHAIN_ENABLED;
 
-        // If there's a per-chain override then apply it
-        if let Some(hash_chain) = self
-            .hash_chains
-            .as_ref()
-            .and_then(|m| m.get(&hash_chain_idx.to_string()))
-        {
-            enabled = hash_chain.enabled.unwrap_or(enabled);
-            frequency = hash_chain
-                .frequency
-                .map(|v| OptionDefault::Some(v))
-                .unwrap_or(frequency);
-            voltage = hash_chain
-                .voltage
-                .map(|v| OptionDefault::Some(v))
-                .unwrap_or(voltage);
+        // Per-chain override: the active profile's, then the top-level one - top-level always
+        // wins so an operator's explicit per-chain tweak survives a profile switch
+        let hash_chain_idx = hash_chain_idx.to_string();
+        let per_chain_overrides: [Option<&BTreeMap<String, HashChain>>; 2] = [
+            profile.and_then(|p| p.hash_chains.as_ref()),
+            self.hash_chains.as_ref(),
+        ];
+        for hash_chains in per_chain_overrides.iter().copied() {
+            if let Some(hash_chain) = hash_chains.and_then(|m| m.get(&hash_chain_idx)) {
+                enabled = hash_chain.enabled.unwrap_or(enabled);
+                frequency = hash_chain
+                    .frequency
+                    .map(|v| OptionDefault::Some(v))
+                    .unwrap_or(frequency);
+                voltage = hash_chain
+                    .voltage
+                    .map(|v| OptionDefault::Some(v))
+                    .unwrap_or(voltage);
+            }
         }
 
+        (*frequency, *voltage, enabled)
+    }
+
+    pub fn resolve_chain_config(&self, hash_chain_idx: usize) -> ResolvedChainConfig {
+        let (frequency, voltage, enabled) = self.resolve_chain_values(hash_chain_idx);
+
         // Computed s9-specific values
         ResolvedChainConfig {
             midstate_count: MidstateCount::new(self.midstate_count()),
             frequency: hashchain::frequency::FrequencySettings::from_frequency(
-                (*frequency * 1_000_000.0) as usize,
+                (frequency * 1_000_000.0) as usize,
             ),
             // TODO: handle config errors
-            voltage: power::Voltage::from_volts(*voltage as f32)
+            voltage: power::Voltage::from_volts(voltage as f32)
                 .expect("TODO: bad voltage requested"),
             enabled,
         }
     }
 
+    /// Read back hash chain `hash_chain_idx`'s currently resolved frequency/voltage/enabled - the
+    /// same values `resolve_chain_config` would compute, but as the plain numbers
+    /// `api::Command::GetChainConfig` hands back to its caller
+    pub fn get_chain_config(&self, hash_chain_idx: usize) -> Result<api::ChainConfig, api::Error> {
+        api::check_chain_idx(hash_chain_idx)?;
+        let (frequency, voltage, enabled) = self.resolve_chain_values(hash_chain_idx);
+        Ok(api::ChainConfig {
+            frequency,
+            voltage,
+            enabled,
+        })
+    }
+
+    /// Validate and apply a live override for hash chain `hash_chain_idx`'s frequency/voltage/
+    /// enabled flag - fields left `None` keep their previously resolved value. The override is
+    /// stored into `hash_chains` so it takes effect on every future `resolve_chain_config` call
+    /// and survives a `config::api::Command::CommitToFile`; if `tuning_sender` is given (the
+    /// chain is currently running) the new frequency/voltage are also pushed through it so the
+    /// chain retunes without a restart - see `hashchain::TuningSettings`.
+    pub fn set_chain_config(
+        &mut self,
+        hash_chain_idx: usize,
+        frequency: Option<f64>,
+        voltage: Option<f64>,
+        enabled: Option<bool>,
+        tuning_sender: Option<&watch::Sender<hashchain::TuningSettings>>,
+    ) -> Result<api::ChainConfig, api::Error> {
+        api::check_chain_idx(hash_chain_idx)?;
+        if let Some(frequency) = frequency {
+            api::check_frequency(frequency)?;
+        }
+        if let Some(voltage) = voltage {
+            api::check_voltage(voltage)?;
+        }
+
+        let (resolved_frequency, resolved_voltage, resolved_enabled) =
+            self.resolve_chain_values(hash_chain_idx);
+        let frequency = frequency.unwrap_or(resolved_frequency);
+        let voltage = voltage.unwrap_or(resolved_voltage);
+        let enabled = enabled.unwrap_or(resolved_enabled);
+
+        self.hash_chains.get_or_insert_with(BTreeMap::new).insert(
+            hash_chain_idx.to_string(),
+            HashChain {
+                enabled: Some(enabled),
+                frequency: Some(frequency),
+                voltage: Some(voltage),
+            },
+        );
+
+        if let Some(tuning_sender) = tuning_sender {
+            let tuning_settings = hashchain::TuningSettings {
+                freq: hashchain::frequency::FrequencySettings::from_frequency(
+                    (frequency * 1_000_000.0) as usize,
+                ),
+                voltage: power::Voltage::from_volts(voltage as f32)
+                    .expect("voltage already validated against VOLTAGE_V_MIN/MAX"),
+            };
+            // A closed receiver just means the chain shut down between the caller looking up
+            // `tuning_sender` and here - the override above is already persisted in
+            // `hash_chains` and will simply apply the next time the chain starts
+            let _ = tuning_sender.broadcast(tuning_settings);
+        }
+
+        Ok(api::ChainConfig {
+            frequency,
+            voltage,
+            enabled,
+        })
+    }
+
+    /// Resolve the effective `TempControlMode`, falling back from the top-level `temp_control`
+    /// through the active profile's `temp_control` the same way every other temp/fan setting in
+    /// `resolve_monitor_config` does - so a profile that sets `mode = Disabled` is honored by
+    /// every consumer of the mode, not just `resolve_monitor_config` itself
+    fn resolve_temp_control_mode(&self) -> TempControlMode {
+        let profile_temp_control = self.active_profile().and_then(|p| p.temp_control.as_ref());
+
+        *OptionDefault::new(
+            self.temp_control
+                .as_ref()
+                .and_then(|v| v.mode)
+                .or_else(|| profile_temp_control.and_then(|v| v.mode)),
+            DEFAULT_TEMP_CONTROL_MODE,
+        )
+    }
+
     pub fn resolve_monitor_config(&self) -> monitor::Config {
+        // The active profile's `TempControl`/`FanControl` fill in for a field this struct's own
+        // top-level `temp_control`/`fan_control` leaves unset - the top-level value always wins
+        // so an explicit override survives a profile switch
+        let profile = self.active_profile();
+        let profile_temp_control = profile.and_then(|p| p.temp_control.as_ref());
+        let profile_fan_control = profile.and_then(|p| p.fan_control.as_ref());
+
         // Get temperature control settings
-        let mode = OptionDefault::new(
-            self.temp_control.as_ref().and_then(|v| v.mode),
-            DEFAULT_TEMP_CONTROL_MODE,
-        );
+        let mode = self.resolve_temp_control_mode();
         let target_temp = OptionDefault::new(
-            self.temp_control.as_ref().and_then(|v| v.target_temp),
+            self.temp_control
+                .as_ref()
+                .and_then(|v| v.target_temp)
+                .or_else(|| profile_temp_control.and_then(|v| v.target_temp)),
             DEFAULT_TARGET_TEMP_C,
         );
         let hot_temp = OptionDefault::new(
-            self.temp_control.as_ref().and_then(|v| v.hot_temp),
+            self.temp_control
+                .as_ref()
+                .and_then(|v| v.hot_temp)
+                .or_else(|| profile_temp_control.and_then(|v| v.hot_temp)),
             DEFAULT_HOT_TEMP_C,
         );
         let dangerous_temp = OptionDefault::new(
-            self.temp_control.as_ref().and_then(|v| v.dangerous_temp),
+            self.temp_control
+                .as_ref()
+                .and_then(|v| v.dangerous_temp)
+                .or_else(|| profile_temp_control.and_then(|v| v.dangerous_temp)),
             DEFAULT_DANGEROUS_TEMP_C,
         );
 
         // Get fan control settings
         let fan_speed = OptionDefault::new(
-            self.fan_control.as_ref().and_then(|v| v.speed),
+            self.fan_control
+                .as_ref()
+                .and_then(|v| v.speed)
+                .or_else(|| profile_fan_control.and_then(|v| v.speed)),
             DEFAULT_FAN_SPEED,
         );
         let min_fans = OptionDefault::new(
-            self.fan_control.as_ref().and_then(|v| v.min_fans),
+            self.fan_control
+                .as_ref()
+                .and_then(|v| v.min_fans)
+                .or_else(|| profile_fan_control.and_then(|v| v.min_fans)),
             DEFAULT_MIN_FANS,
         );
 
@@ -433,7 +883,7 @@ impl Backend {
         let fan_config;
 
         // Configure temperature controller
-        match *mode {
+        match mode {
             TempControlMode::Auto | TempControlMode::Manual => {
                 temp_config = Some(monitor::TempControlConfig {
                     dangerous_temp: *dangerous_temp as f32,
@@ -459,7 +909,7 @@ impl Backend {
         };
 
         // Configure fan controller
-        match *mode {
+        match mode {
             TempControlMode::Auto => {
                 fan_config = Some(monitor::FanControlConfig {
                     mode: monitor::FanControlMode::TargetTemperature(*target_temp as f32),
@@ -500,6 +950,42 @@ impl Backend {
         }
     }
 
+    /// Resolve `[autotune]` settings - `enabled` is forced to `false` whenever temperature
+    /// control is disabled (`TempControlMode::Disabled`), since there are no readings for
+    /// `AutotuneController::step` to react to and it must refuse to run blind
+    pub fn resolve_autotune_config(&self) -> AutotuneConfig {
+        let enabled = OptionDefault::new(
+            self.autotune.as_ref().and_then(|v| v.enabled),
+            DEFAULT_AUTOTUNE_ENABLED,
+        );
+        let target_temp = OptionDefault::new(
+            self.autotune.as_ref().and_then(|v| v.target_temp),
+            DEFAULT_AUTOTUNE_TARGET_TEMP_C,
+        );
+        let max_frequency = OptionDefault::new(
+            self.autotune.as_ref().and_then(|v| v.max_frequency),
+            FREQUENCY_MHZ_MAX,
+        );
+        let step_mhz = OptionDefault::new(
+            self.autotune.as_ref().and_then(|v| v.step_mhz),
+            DEFAULT_AUTOTUNE_STEP_MHZ,
+        );
+        let interval = OptionDefault::new(
+            self.autotune.as_ref().and_then(|v| v.interval),
+            DEFAULT_AUTOTUNE_INTERVAL_SECS,
+        );
+
+        let mode = self.resolve_temp_control_mode();
+
+        AutotuneConfig {
+            enabled: *enabled && mode != TempControlMode::Disabled,
+            target_temp: *target_temp as f32,
+            max_frequency: (*max_frequency).min(FREQUENCY_MHZ_MAX),
+            step_mhz: *step_mhz,
+            interval: Duration::from_secs(*interval),
+        }
+    }
+
     pub fn fill_info<T>(&mut self) -> Result<(), std::io::Error>
     where
         T: ConfigBody,
@@ -578,6 +1064,12 @@ impl ConfigBody for Backend {
     fn variant() -> String {
         bosminer::SIGNATURE.into()
     }
+
+    fn migrations() -> Vec<Migration> {
+        // No format version predates FORMAT_VERSION yet - add an entry here (`from: "0.9"` etc.)
+        // the next time FORMAT_VERSION is bumped and an old config shape needs upgrading.
+        Vec::new()
+    }
 }
 
 impl hal::BackendConfig for Backend {
@@ -0,0 +1,167 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Runtime control API for reading and retuning a running miner's per-chain frequency, voltage
+//! and enabled flag without a restart - a small request/response `Command` model in the same
+//! spirit as `cgminer-api`'s `request::Command`/`response::Dispatch`, scoped to what
+//! `Backend::resolve_chain_config` already computes. `dispatch` is the single entry point: it
+//! validates against the `FREQUENCY_MHZ_MIN/MAX`, `VOLTAGE_V_MIN/MAX` and
+//! `HASH_CHAIN_INDEX_MIN/MAX` bounds, applies the change to `Backend`, and
+//! optionally persists it back through `FormatWrapper::save`.
+
+use super::{
+    Backend, FormatWrapper, FREQUENCY_MHZ_MAX, FREQUENCY_MHZ_MIN, HASH_CHAIN_INDEX_MAX,
+    HASH_CHAIN_INDEX_MIN, VOLTAGE_V_MAX, VOLTAGE_V_MIN,
+};
+use crate::hashchain;
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+use ii_async_compat::tokio;
+use tokio::sync::watch;
+
+/// Requests accepted by the runtime control API, one per `dispatch` call
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    /// Read back hash chain `idx`'s currently resolved frequency/voltage/enabled
+    GetChainConfig { idx: usize },
+    /// Push new values for hash chain `idx`, taking effect immediately; fields left `None` keep
+    /// their previously resolved value
+    SetChainConfig {
+        idx: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        frequency: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        voltage: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        enabled: Option<bool>,
+    },
+    /// Persist the in-memory `hash_chains` overrides back to the config file on disk
+    CommitToFile,
+    /// Switch `Backend::active_profile` - `None` clears it, falling back to the top-level/
+    /// per-chain config untouched by any profile
+    SetActiveProfile { name: Option<String> },
+}
+
+/// A hash chain's resolved frequency (MHz), voltage (V) and enabled flag - plain numbers instead
+/// of `ResolvedChainConfig`'s `FrequencySettings`/`power::Voltage`, so this serializes directly
+/// as the API response
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Debug)]
+pub struct ChainConfig {
+    pub frequency: f64,
+    pub voltage: f64,
+    pub enabled: bool,
+}
+
+/// Rejects from `dispatch`: a bound violated by a request, an unknown profile name, or a failure
+/// persisting it
+#[derive(Clone, PartialEq, Debug)]
+pub enum Error {
+    InvalidChainIndex(usize),
+    FrequencyOutOfRange(f64),
+    VoltageOutOfRange(f64),
+    UnknownProfile(String),
+    CommitFailed(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidChainIndex(idx) => write!(
+                f,
+                "hash chain index {} out of range {}..={}",
+                idx, HASH_CHAIN_INDEX_MIN, HASH_CHAIN_INDEX_MAX
+            ),
+            Self::FrequencyOutOfRange(frequency) => write!(
+                f,
+                "frequency {} MHz out of range {}..={}",
+                frequency, FREQUENCY_MHZ_MIN, FREQUENCY_MHZ_MAX
+            ),
+            Self::VoltageOutOfRange(voltage) => write!(
+                f,
+                "voltage {} V out of range {}..={}",
+                voltage, VOLTAGE_V_MIN, VOLTAGE_V_MAX
+            ),
+            Self::UnknownProfile(name) => write!(f, "no such profile '{}'", name),
+            Self::CommitFailed(msg) => write!(f, "failed to persist configuration: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub(super) fn check_chain_idx(idx: usize) -> Result<(), Error> {
+    if idx < HASH_CHAIN_INDEX_MIN || idx > HASH_CHAIN_INDEX_MAX {
+        return Err(Error::InvalidChainIndex(idx));
+    }
+    Ok(())
+}
+
+pub(super) fn check_frequency(frequency: f64) -> Result<(), Error> {
+    if frequency < FREQUENCY_MHZ_MIN || frequency > FREQUENCY_MHZ_MAX {
+        return Err(Error::FrequencyOutOfRange(frequency));
+    }
+    Ok(())
+}
+
+pub(super) fn check_voltage(voltage: f64) -> Result<(), Error> {
+    if voltage < VOLTAGE_V_MIN || voltage > VOLTAGE_V_MAX {
+        return Err(Error::VoltageOutOfRange(voltage));
+    }
+    Ok(())
+}
+
+/// Run `command` against `wrapper`, looking up `tuning_sender` (the running chain's live retune
+/// channel, if it's up) only for `SetChainConfig`. `GetChainConfig`/`SetChainConfig` return the
+/// resolved `ChainConfig`; `CommitToFile` writes `wrapper` to `config_path` and returns `None`.
+pub fn dispatch(
+    wrapper: &mut FormatWrapper<Backend>,
+    config_path: &str,
+    tuning_sender: Option<&watch::Sender<hashchain::TuningSettings>>,
+    command: Command,
+) -> Result<Option<ChainConfig>, Error> {
+    match command {
+        Command::GetChainConfig { idx } => wrapper.body.get_chain_config(idx).map(Some),
+        Command::SetChainConfig {
+            idx,
+            frequency,
+            voltage,
+            enabled,
+        } => wrapper
+            .body
+            .set_chain_config(idx, frequency, voltage, enabled, tuning_sender)
+            .map(Some),
+        Command::CommitToFile => {
+            wrapper
+                .save(config_path)
+                .map_err(|err| Error::CommitFailed(err.to_string()))?;
+            Ok(None)
+        }
+        Command::SetActiveProfile { name } => {
+            wrapper.body.set_active_profile(name)?;
+            Ok(None)
+        }
+    }
+}
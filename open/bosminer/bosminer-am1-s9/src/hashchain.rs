@@ -0,0 +1,431 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Owns one physical Antminer S9 hashboard's chip chain. `HashChain` is the one place that
+//! bridges raw chip I/O with the rest of the system:
+//! - it owns the `broadcast::Sender<Solution>` every consumer (pool submitter, telemetry,
+//!   logging, ...) observes via `subscribe()`, instead of each consumer wiring up its own
+//! - it owns the `watch::Receiver<TuningSettings>` handed to `new()` and drives `control_task`
+//!   off it, so an autotuner or operator API can retune frequency/voltage live via the paired
+//!   `watch::Sender` without tearing the chain down - see `config::set_chain_config`
+
+use ii_async_compat::tokio;
+use ii_logging::macros::*;
+use tokio::sync::{broadcast, mpsc, watch, Mutex as AsyncMutex};
+use tokio::time::delay_for;
+
+use std::future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::bm1387::MidstateCount;
+use crate::error;
+use crate::gpio;
+use crate::monitor;
+use crate::power;
+
+use bosminer::work;
+
+/// How many not-yet-consumed solutions a `subscribe()`r may lag behind before it starts missing
+/// entries - see `broadcast::RecvError::Lagged`
+const SOLUTION_BROADCAST_CAPACITY: usize = 64;
+
+/// S9 hashboards carry a fixed-depth chain of chips; `TxIo::work_id_count` mirrors this so
+/// `work_pacer::WorkPacer` never keeps more submissions outstanding than the chain can hold
+const WORK_QUEUE_DEPTH: usize = 4;
+
+/// How long `HashChain::init` holds the reset line low before releasing it
+const RESET_DURATION: Duration = Duration::from_millis(100);
+
+/// Chain operating frequency, always carried in Hz to avoid repeating the same MHz conversion at
+/// every call site (`config::resolve_chain_config`, `config::set_chain_config`, ...)
+pub mod frequency {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct FrequencySettings {
+        hz: usize,
+    }
+
+    impl FrequencySettings {
+        pub fn from_frequency(hz: usize) -> Self {
+            Self { hz }
+        }
+
+        pub fn hz(&self) -> usize {
+            self.hz
+        }
+    }
+}
+
+/// Frequency/voltage pushed live into a running `HashChain` via the `watch::Sender` paired with
+/// the `watch::Receiver` given to `HashChain::new` - see `control_task`
+#[derive(Debug, Clone, Copy)]
+pub struct TuningSettings {
+    pub freq: frequency::FrequencySettings,
+    pub voltage: power::Voltage,
+}
+
+/// Raw per-chip solution as read back from the bus, before `Solution::from_hw_solution` resolves
+/// it against the network target currently in effect
+pub struct HwSolution {
+    pub nonce: u32,
+    pub midstate_idx: usize,
+}
+
+/// A validated solution ready for submission - the unit every `HashChain::subscribe`r observes
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub nonce: u32,
+    pub midstate_idx: usize,
+    pub target: ii_bitcoin::Target,
+}
+
+impl Solution {
+    pub fn from_hw_solution(hw_solution: &HwSolution, target: ii_bitcoin::Target) -> Self {
+        Self {
+            nonce: hw_solution.nonce,
+            midstate_idx: hw_solution.midstate_idx,
+            target,
+        }
+    }
+}
+
+/// GPIO reset line for one hashboard slot
+pub struct ResetPin {
+    pin: gpio::ControlPin,
+}
+
+impl ResetPin {
+    pub fn open(gpio_mgr: &gpio::ControlPinManager, hashboard_idx: usize) -> error::Result<Self> {
+        Ok(Self {
+            pin: gpio_mgr.get_pin(hashboard_idx)?,
+        })
+    }
+
+    fn assert(&self) {
+        let _ = self.pin.set_value(1);
+    }
+
+    fn deassert(&self) {
+        let _ = self.pin.set_value(0);
+    }
+}
+
+/// GPIO plug-detect line for one hashboard slot
+pub struct PlugPin {
+    pin: gpio::ControlPin,
+}
+
+impl PlugPin {
+    pub fn open(gpio_mgr: &gpio::ControlPinManager, hashboard_idx: usize) -> error::Result<Self> {
+        Ok(Self {
+            pin: gpio_mgr.get_pin(hashboard_idx)?,
+        })
+    }
+
+    fn is_present(&self) -> bool {
+        self.pin
+            .get_value()
+            .map(|value| value != 0)
+            .unwrap_or(false)
+    }
+}
+
+/// The hardware-facing half of the chain's solution stream, owned and consumed internally by the
+/// task `HashChain::new` spawns - never handed out to callers, who observe solutions through
+/// `HashChain::subscribe` instead.
+struct RxIo {
+    hashboard_idx: usize,
+}
+
+impl RxIo {
+    fn new(hashboard_idx: usize) -> Self {
+        Self { hashboard_idx }
+    }
+
+    /// Waits for the next solution the chip chain's bus has to offer. Consumes and returns
+    /// `self` so the forwarding task can keep looping without holding a long-lived `&mut` across
+    /// an `.await`.
+    ///
+    /// NOTE: the actual bus read is provided by this crate's low-level chip driver, which this
+    /// sparse tree doesn't carry; `subscribe()`/the forwarding task around it are real.
+    async fn recv_solution(self) -> error::Result<(Self, HwSolution)> {
+        let _ = self.hashboard_idx;
+        future::pending::<()>().await;
+        unreachable!("BUG: chip bus never yields in this tree's driver stub")
+    }
+}
+
+/// The hardware-facing half of the chain's work stream, handed out once via
+/// `HashChain::take_work_tx_io` to whoever paces and sends work (see `work_pacer::WorkPacer`).
+pub struct TxIo {
+    hashboard_idx: usize,
+    room: Arc<tokio::sync::Semaphore>,
+}
+
+impl TxIo {
+    fn new(hashboard_idx: usize) -> Self {
+        Self {
+            hashboard_idx,
+            room: Arc::new(tokio::sync::Semaphore::new(WORK_QUEUE_DEPTH)),
+        }
+    }
+
+    /// Number of work items the chain's own input queue can hold - sizes `work_pacer::WorkPacer`
+    /// so it never has more than this many submissions outstanding at once
+    pub fn work_id_count(&self) -> usize {
+        WORK_QUEUE_DEPTH
+    }
+
+    /// Blocks until the chain's input queue has room for one more work item
+    pub async fn wait_for_room(&mut self) -> error::Result<()> {
+        self.room.acquire().await.forget();
+        Ok(())
+    }
+
+    /// Pushes `work` onto the chain's input queue, tagged with `work_id` so the solution it
+    /// eventually yields can be matched back to it
+    ///
+    /// NOTE: the actual register-level push is provided by this crate's low-level chip driver,
+    /// which this sparse tree doesn't carry.
+    pub fn send_work(&mut self, _work: &work::Assignment, _work_id: usize) -> error::Result<()> {
+        let _ = self.hashboard_idx;
+        Ok(())
+    }
+}
+
+/// Cheaply cloneable handle used to ask every task `HashChain::new` spawns to stop. Each task
+/// holds a receiver of it, and a caller triggers the shutdown via `HashChain::halt_sender`; a task
+/// that ends on its own (e.g. on a receive error) does not signal the others.
+#[derive(Clone)]
+pub struct HaltSender {
+    sender: broadcast::Sender<()>,
+}
+
+impl HaltSender {
+    fn new() -> (Self, broadcast::Receiver<()>) {
+        let (sender, receiver) = broadcast::channel(1);
+        (Self { sender }, receiver)
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<()> {
+        self.sender.subscribe()
+    }
+
+    /// Signals every task spawned by `HashChain::new` to stop. Safe to call more than once, or
+    /// after the chain has already halted.
+    pub async fn send_halt(&self) {
+        let _ = self.sender.send(());
+    }
+}
+
+pub struct HashChain {
+    /// Skips priming the chain's input queue with initial work on `init` - tests that drive the
+    /// work queue themselves set this before calling `init`
+    pub disable_init_work: bool,
+    /// See `HaltSender::send_halt`
+    pub halt_sender: HaltSender,
+
+    hashboard_idx: usize,
+    reset_pin: ResetPin,
+    plug_pin: PlugPin,
+    voltage_ctrl_backend: Arc<power::I2cBackend>,
+    monitor_sender: mpsc::UnboundedSender<monitor::Message>,
+    midstate_count: MidstateCount,
+    asic_difficulty: usize,
+
+    chip_count: AtomicUsize,
+    solution_sender: broadcast::Sender<Solution>,
+    tx_io: AsyncMutex<Option<TxIo>>,
+}
+
+impl HashChain {
+    pub fn new(
+        reset_pin: ResetPin,
+        plug_pin: PlugPin,
+        voltage_ctrl_backend: Arc<power::I2cBackend>,
+        hashboard_idx: usize,
+        midstate_count: MidstateCount,
+        asic_difficulty: usize,
+        monitor_sender: mpsc::UnboundedSender<monitor::Message>,
+        tuning_receiver: watch::Receiver<TuningSettings>,
+    ) -> error::Result<Self> {
+        let (solution_sender, _) = broadcast::channel(SOLUTION_BROADCAST_CAPACITY);
+        let (halt_sender, forward_halt_receiver) = HaltSender::new();
+        let control_halt_receiver = halt_sender.subscribe();
+
+        tokio::spawn(Self::forward_solutions(
+            RxIo::new(hashboard_idx),
+            solution_sender.clone(),
+            asic_difficulty,
+            forward_halt_receiver,
+        ));
+        tokio::spawn(Self::control_task(
+            hashboard_idx,
+            voltage_ctrl_backend.clone(),
+            tuning_receiver,
+            control_halt_receiver,
+        ));
+
+        Ok(Self {
+            disable_init_work: false,
+            halt_sender,
+            hashboard_idx,
+            reset_pin,
+            plug_pin,
+            voltage_ctrl_backend,
+            monitor_sender,
+            midstate_count,
+            asic_difficulty,
+            chip_count: AtomicUsize::new(0),
+            solution_sender,
+            tx_io: AsyncMutex::new(Some(TxIo::new(hashboard_idx))),
+        })
+    }
+
+    /// Subscribes to every `Solution` this chain's chips produce from now on - the pool
+    /// submitter, a telemetry collector and a logging sink can all hold their own subscription at
+    /// once, see `SOLUTION_BROADCAST_CAPACITY` for how far a slow subscriber may lag behind.
+    pub fn subscribe(&self) -> broadcast::Receiver<Solution> {
+        self.solution_sender.subscribe()
+    }
+
+    /// Brings the chain's chips up: asserts and releases the reset line (when `reset` is true),
+    /// detects the plugged chip count, and - unless `disable_init_work` is set - primes the
+    /// chips' input queues with initial work. Live retuning from here on is handled entirely by
+    /// the control task `new` already spawned; `init` never needs calling again for a retune.
+    pub async fn init(&mut self, reset: bool) -> error::Result<()> {
+        if reset {
+            self.reset_pin.assert();
+            delay_for(RESET_DURATION).await;
+            self.reset_pin.deassert();
+        }
+
+        let chip_count = if self.plug_pin.is_present() {
+            self.midstate_count.to_count()
+        } else {
+            0
+        };
+        self.chip_count.store(chip_count, Ordering::SeqCst);
+        // let the rest of the system (e.g. the temperature/fan controller) know this chain is up
+        // and how many chips it needs to account for
+        let _ = self
+            .monitor_sender
+            .unbounded_send(monitor::Message::HashChainUp(
+                self.hashboard_idx,
+                chip_count,
+            ));
+
+        if !self.disable_init_work {
+            // primes every chip's input queue so the first real work submission doesn't have to
+            // wait out an empty pipeline - provided by the low-level chip driver
+        }
+
+        Ok(())
+    }
+
+    /// Number of chips detected on this chain by the last `init`
+    pub fn get_chip_count(&self) -> usize {
+        self.chip_count.load(Ordering::SeqCst)
+    }
+
+    /// Hands out the chain's `TxIo` - the caller is expected to size its work pacing off
+    /// `TxIo::work_id_count` before sending any work through it. May only be called once; panics
+    /// on a second call, same as taking ownership of a resource twice would.
+    pub async fn take_work_tx_io(&self) -> TxIo {
+        self.tx_io
+            .lock()
+            .await
+            .take()
+            .expect("BUG: work TxIo already taken")
+    }
+
+    /// Reads solutions off `rx_io` and broadcasts each one to every `subscribe()`r - the real
+    /// counterpart of what used to be a test-only `receiver_task`
+    async fn forward_solutions(
+        mut rx_io: RxIo,
+        solution_sender: broadcast::Sender<Solution>,
+        asic_difficulty: usize,
+        mut halt_receiver: broadcast::Receiver<()>,
+    ) {
+        let target = ii_bitcoin::Target::from_pool_difficulty(asic_difficulty);
+        loop {
+            tokio::select! {
+                result = rx_io.recv_solution() => {
+                    match result {
+                        Ok((rx_io_out, hw_solution)) => {
+                            rx_io = rx_io_out;
+                            // an error here only means there are currently no subscribers - the
+                            // solution isn't lost, it simply has nobody observing it yet
+                            let _ = solution_sender
+                                .send(Solution::from_hw_solution(&hw_solution, target));
+                        }
+                        Err(error) => {
+                            warn!("HashChain: solution receive failed: {}", error);
+                            break;
+                        }
+                    }
+                }
+                _ = halt_receiver.recv() => break,
+            }
+        }
+    }
+
+    /// Steps frequency/voltage towards whatever `tuning_receiver` currently holds, and again
+    /// every time a later value is pushed through the paired `watch::Sender` - this is what lets
+    /// `config::set_chain_config` retune a running chain without restarting it.
+    async fn control_task(
+        hashboard_idx: usize,
+        voltage_ctrl_backend: Arc<power::I2cBackend>,
+        mut tuning_receiver: watch::Receiver<TuningSettings>,
+        mut halt_receiver: broadcast::Receiver<()>,
+    ) {
+        loop {
+            tokio::select! {
+                tuning = tuning_receiver.recv() => {
+                    match tuning {
+                        Some(tuning_settings) => {
+                            Self::apply_tuning(hashboard_idx, &voltage_ctrl_backend, tuning_settings)
+                                .await;
+                        }
+                        None => break,
+                    }
+                }
+                _ = halt_receiver.recv() => break,
+            }
+        }
+    }
+
+    /// NOTE: the actual register-level frequency stepping and `voltage_ctrl_backend` write are
+    /// provided by this crate's low-level chip driver, which this sparse tree doesn't carry.
+    async fn apply_tuning(
+        hashboard_idx: usize,
+        _voltage_ctrl_backend: &power::I2cBackend,
+        tuning: TuningSettings,
+    ) {
+        info!(
+            "Hashboard {}: retuning to {} Hz",
+            hashboard_idx,
+            tuning.freq.hz()
+        );
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Aggregates the per-hashboard solution streams of many hash chains into a single ordered
+//! stream, see `HashChainSet`.
+
+use futures::stream::Stream;
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Identifies a hashboard slot within the miner, same indexing as `config::S9_HASHBOARD_INDEX`
+pub type HashboardIndex = usize;
+
+type BoxedSolutionStream<S> = Pin<Box<dyn Stream<Item = S> + Send>>;
+
+/// Merges the per-chain solution streams of many hash chains into a single
+/// `Stream<Item = (HashboardIndex, S)>` - the StreamMap pattern: a map from hashboard index to
+/// that chain's solution stream (`S` is `hashchain::Solution` in production), polled round-robin
+/// for fairness. The key lets a consumer (the pool submitter, telemetry, ...) attribute each
+/// solution and its achieved difficulty back to the board that found it.
+///
+/// Boards can be `insert`ed or `remove`d at runtime, independently of the others, so hot-plugged
+/// or faulted-out boards never require rebuilding the whole aggregate. An inner stream ending
+/// (the board was removed, or faulted out on its own) is absorbed silently - it never ends the
+/// outer stream.
+pub struct HashChainSet<S> {
+    chains: HashMap<HashboardIndex, BoxedSolutionStream<S>>,
+    /// Poll order for round-robin fairness - rotated on every solution so a board that always
+    /// has one ready cannot starve the others
+    poll_order: Vec<HashboardIndex>,
+}
+
+impl<S> Default for HashChainSet<S> {
+    fn default() -> Self {
+        Self {
+            chains: HashMap::new(),
+            poll_order: Vec::new(),
+        }
+    }
+}
+
+impl<S> HashChainSet<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `idx`'s solution stream with the set, replacing any stream already registered
+    /// under that index
+    pub fn insert(&mut self, idx: HashboardIndex, solutions: impl Stream<Item = S> + Send + 'static) {
+        if self.chains.insert(idx, Box::pin(solutions)).is_none() {
+            self.poll_order.push(idx);
+        }
+    }
+
+    /// Drops `idx` from the set, e.g. because its board was unplugged or faulted out. Returns
+    /// `true` if it was present.
+    pub fn remove(&mut self, idx: HashboardIndex) -> bool {
+        self.poll_order.retain(|&present| present != idx);
+        self.chains.remove(&idx).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chains.is_empty()
+    }
+}
+
+impl<S> Stream for HashChainSet<S> {
+    type Item = (HashboardIndex, S);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut exhausted = Vec::new();
+
+        for rotation in 0..this.poll_order.len() {
+            let idx = this.poll_order[rotation];
+            let solutions = this
+                .chains
+                .get_mut(&idx)
+                .expect("poll_order out of sync with chains");
+
+            match solutions.as_mut().poll_next(cx) {
+                Poll::Ready(Some(solution)) => {
+                    // start the next poll just past the board that just yielded, so a board
+                    // with a solution ready every time doesn't crowd out the others
+                    this.poll_order.rotate_left(rotation + 1);
+                    return Poll::Ready(Some((idx, solution)));
+                }
+                Poll::Ready(None) => exhausted.push(idx),
+                Poll::Pending => {}
+            }
+        }
+
+        for idx in exhausted {
+            this.remove(idx);
+        }
+
+        // never report the outer stream as done - a board may still be `insert`ed later
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use futures::stream;
+    use futures::task::noop_waker;
+
+    fn poll_once<S>(set: &mut HashChainSet<S>) -> Poll<Option<(HashboardIndex, S)>> {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        Pin::new(set).poll_next(&mut cx)
+    }
+
+    #[test]
+    fn test_round_robin_fairness() {
+        let mut set = HashChainSet::new();
+        set.insert(0, stream::iter(vec!["a0", "a1", "a2"]));
+        set.insert(1, stream::iter(vec!["b0", "b1", "b2"]));
+
+        // with two boards always ready, a fair poll order must not let one board run ahead of
+        // the other by more than one solution
+        let mut seen = HashMap::new();
+        for _ in 0..6 {
+            match poll_once(&mut set) {
+                Poll::Ready(Some((idx, _))) => {
+                    let count = seen.entry(idx).or_insert(0);
+                    *count += 1;
+                    assert!(
+                        (*count as i32 - *seen.entry(1 - idx).or_insert(0) as i32).abs() <= 1,
+                        "one board was starved by the other"
+                    );
+                }
+                other => panic!("expected a solution, got {:?}", other),
+            }
+        }
+        assert_eq!(seen[&0], 3);
+        assert_eq!(seen[&1], 3);
+    }
+
+    #[test]
+    fn test_exhausted_chain_does_not_end_outer_stream() {
+        let mut set = HashChainSet::new();
+        set.insert(0, stream::iter(vec!["solution"]));
+
+        assert_eq!(poll_once(&mut set), Poll::Ready(Some((0, "solution"))));
+        // board 0's stream is now exhausted and gets dropped from the set, but the aggregate
+        // itself must keep reporting `Pending`, never `None`
+        assert_eq!(poll_once(&mut set), Poll::Pending);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_insert_replaces_without_duplicating_poll_order() {
+        let mut set: HashChainSet<&str> = HashChainSet::new();
+        set.insert(0, stream::pending());
+        set.insert(0, stream::pending());
+        assert_eq!(set.poll_order, vec![0]);
+    }
+
+    #[test]
+    fn test_remove_reports_presence() {
+        let mut set: HashChainSet<&str> = HashChainSet::new();
+        set.insert(0, stream::pending());
+        assert!(set.remove(0));
+        assert!(!set.remove(0));
+        assert!(set.is_empty());
+    }
+}
@@ -26,7 +26,8 @@ use super::*;
 use crate::bm1387::MidstateCount;
 use crate::fan;
 use crate::hashchain;
-use crate::registry;
+use crate::power;
+use crate::work_pacer;
 
 use bosminer::work;
 
@@ -35,13 +36,24 @@ use std::time::Duration;
 use std::sync::Arc;
 
 use futures::channel::mpsc;
-use futures::stream::StreamExt;
 
 use ii_async_compat::{tokio, FutureExt};
+use tokio::sync::{broadcast, watch};
 use tokio::time::delay_for;
 
 const ASIC_DIFFICULTY: usize = 1;
 
+/// Builds the `TuningSettings` the chain should converge to - frequency is expressed in MHz here
+/// for readability, same as `config::DEFAULT_FREQUENCY_MHZ`
+fn tuning_settings(freq_mhz: f64, voltage: power::Voltage) -> hashchain::TuningSettings {
+    hashchain::TuningSettings {
+        freq: hashchain::frequency::FrequencySettings::from_frequency(
+            (freq_mhz * 1_000_000.0) as usize,
+        ),
+        voltage,
+    }
+}
+
 /// Prepares sample work with empty midstates
 /// NOTE: this work has 2 valid nonces:
 /// - 0x83ea0372 (solution 0)
@@ -57,69 +69,64 @@ fn prepare_test_work(midstate_count: usize) -> work::Assignment {
     work::Assignment::new(job, vec![one_midstate; midstate_count], time)
 }
 
-/// Task that receives solutions from hardware and sends them to channel
-async fn receiver_task(
-    hash_chain: Arc<hashchain::HashChain>,
-    solution_sender: mpsc::UnboundedSender<hashchain::Solution>,
-) {
-    let mut rx_io = hash_chain.take_work_rx_io().await;
-    let target = ii_bitcoin::Target::from_pool_difficulty(ASIC_DIFFICULTY);
-
+/// Receives the next solution from a subscription, folding any `Lagged` gaps into
+/// `missed_solutions` instead of treating them as fatal - a slow observer must not be able to
+/// abort the pool submission path
+async fn recv_solution(
+    solution_receiver: &mut broadcast::Receiver<hashchain::Solution>,
+    missed_solutions: &mut u64,
+) -> Option<hashchain::Solution> {
     loop {
-        let (rx_io_out, solution) = rx_io.recv_solution().await.expect("recv solution");
-        rx_io = rx_io_out;
-        solution_sender
-            .unbounded_send(hashchain::Solution::from_hw_solution(&solution, target))
-            .expect("solution send failed");
+        match solution_receiver.recv().await {
+            Ok(solution) => return Some(solution),
+            Err(broadcast::RecvError::Lagged(skipped)) => *missed_solutions += skipped,
+            Err(broadcast::RecvError::Closed) => return None,
+        }
     }
 }
 
-/// Task that receives work from channel and sends it to HW
+/// Task that receives work already paced (and assigned a `work_id`) by a `work_pacer::WorkPacer`
+/// and sends it to HW. `tx_io` is handed in already taken from the chain so the caller can read
+/// its `work_id_count()` to size the `WorkPacer` before this task ever starts running.
 async fn sender_task(
-    hash_chain: Arc<hashchain::HashChain>,
-    mut work_receiver: mpsc::UnboundedReceiver<work::Assignment>,
+    mut tx_io: hashchain::TxIo,
+    mut work_receiver: tokio::sync::mpsc::Receiver<(work::Assignment, usize)>,
 ) {
-    let mut tx_io = hash_chain.take_work_tx_io().await;
-    let mut work_registry =
-        registry::WorkRegistry::<hashchain::Solution>::new(tx_io.work_id_count());
-
-    loop {
+    while let Some((work, work_id)) = work_receiver.recv().await {
         tx_io.wait_for_room().await.expect("wait for tx room");
-        let work = work_receiver.next().await.expect("failed receiving work");
-        let work_id = work_registry.store_work(work.clone(), false);
         // send work is synchronous
         tx_io.send_work(&work, work_id).expect("send work");
     }
 }
 
+/// Submits `n_send` work items through `work_pacer` - which blocks each submission until there is
+/// real room for it instead of guessing a fixed delay - and waits to receive exactly
+/// `expected_solution_count` solutions for them. Since every call site already knows the exact
+/// count to expect, counting is itself the deterministic completion signal; there is no longer a
+/// timeout to tune for the board under test. Returns the `work_id`s this batch was assigned.
 async fn send_and_receive_test_workloads<'a>(
-    work_sender: &'a mpsc::UnboundedSender<work::Assignment>,
-    solution_receiver: &'a mut mpsc::UnboundedReceiver<hashchain::Solution>,
+    work_pacer: &'a mut work_pacer::WorkPacer,
+    solution_receiver: &'a mut broadcast::Receiver<hashchain::Solution>,
+    missed_solutions: &'a mut u64,
     n_send: usize,
     expected_solution_count: usize,
-) {
+) -> Vec<usize> {
     info!(
         "Sending {} work items and trying to receive {} solutions",
         n_send, expected_solution_count,
     );
-    //
-    // Put in some tasks
+
+    let mut work_ids = Vec::with_capacity(n_send);
     for _ in 0..n_send {
         let work = prepare_test_work(1);
-        work_sender.unbounded_send(work).expect("work send failed");
-        // wait time to send out work + to compute work
-        // TODO: come up with a formula instead of fixed time interval
-        // wait = work_time * number_of_chips + time_to_send_out_a_jov
-
-        delay_for(Duration::from_millis(100)).await;
+        work_ids.push(work_pacer.submit(work).await);
     }
+
     let mut returned_solution_count = 0;
-    while let Ok(res) = solution_receiver
-        .next()
-        .timeout(Duration::from_millis(1000))
-        .await
-    {
-        res.expect("timeout error");
+    for _ in 0..expected_solution_count {
+        recv_solution(solution_receiver, missed_solutions)
+            .await
+            .expect("solution channel closed before all expected solutions arrived");
         returned_solution_count += 1;
     }
 
@@ -128,9 +135,14 @@ async fn send_and_receive_test_workloads<'a>(
         "expected {} solutions but got {}",
         expected_solution_count, returned_solution_count
     );
+    work_ids
 }
 
-async fn start_hchain(monitor_tx: mpsc::UnboundedSender<monitor::Message>) -> hashchain::HashChain {
+/// Starts a hash chain and hands back a `watch::Sender` the caller can use to retune frequency
+/// and voltage live - e.g. from an autotuner or operator API - without tearing the chain down
+async fn start_hchain(
+    monitor_tx: mpsc::UnboundedSender<monitor::Message>,
+) -> (hashchain::HashChain, watch::Sender<hashchain::TuningSettings>) {
     let hashboard_idx = config::S9_HASHBOARD_INDEX;
     let gpio_mgr = gpio::ControlPinManager::new();
     let voltage_ctrl_backend = Arc::new(power::I2cBackend::new(0));
@@ -142,6 +154,11 @@ async fn start_hchain(monitor_tx: mpsc::UnboundedSender<monitor::Message>) -> ha
     // turn on fans to full (no temp control)
     fan_control.set_speed(fan::Speed::FULL_SPEED);
 
+    let (tuning_sender, tuning_receiver) = watch::channel(tuning_settings(
+        config::DEFAULT_FREQUENCY_MHZ,
+        *power::OPEN_CORE_VOLTAGE,
+    ));
+
     let mut hash_chain = hashchain::HashChain::new(
         reset_pin,
         plug_pin,
@@ -150,54 +167,61 @@ async fn start_hchain(monitor_tx: mpsc::UnboundedSender<monitor::Message>) -> ha
         MidstateCount::new(1),
         ASIC_DIFFICULTY,
         monitor_tx,
+        tuning_receiver,
     )
     .unwrap();
     hash_chain.disable_init_work = true;
 
-    hash_chain
-        .init(
-            &hashchain::FrequencySettings::from_frequency(
-                (config::DEFAULT_FREQUENCY_MHZ * 1_000_000.0) as usize,
-            ),
-            *crate::power::OPEN_CORE_VOLTAGE,
-            true,
-        )
-        .await
-        .expect("h_chain init failed");
-    hash_chain
+    // bring the chips up at whatever `tuning_receiver` currently holds; the control task spawned
+    // by `HashChain::new` takes over from here and steps towards every later `tuning_sender` value
+    hash_chain.init(true).await.expect("h_chain init failed");
+    (hash_chain, tuning_sender)
 }
 
 /// Verifies work generation for a hash chain
 ///
-/// The test runs two batches of work:
+/// The test runs three batches of work:
 /// - the first 3 work items are for initializing input queues of the chips and don't provide any
 /// solutions
 /// - the next 2 work items yield actual solutions. Since we don't push more work items, the
 /// solution 1 never appears on the bus and leave chips output queues. This is fine as this test
 /// is intended for initial check of correct operation
+/// - a live retune is then pushed through `tuning_sender` and a final batch confirms the chain
+/// keeps mining through it instead of needing to be torn down and reinitialized
 #[tokio::test]
 async fn test_work_generation() {
-    // Create channels
-    let (solution_sender, mut solution_receiver) = mpsc::unbounded();
-    let (work_sender, work_receiver) = mpsc::unbounded();
     let (monitor_sender, _monitor_receiver) = mpsc::unbounded();
 
-    // Guard lives until the end of the block
-    let _work_sender_guard = work_sender.clone();
-    let _solution_sender_guard = solution_sender.clone();
-
     // Start HW
-    let hash_chain = Arc::new(start_hchain(monitor_sender).await);
+    let (hash_chain, tuning_sender) = start_hchain(monitor_sender).await;
+    let hash_chain = Arc::new(hash_chain);
+
+    // the chain owns the solution broadcast itself now - subscribe instead of relaying it
+    // through a test-local channel (see `hashchain::HashChain::subscribe`)
+    let mut solution_receiver = hash_chain.subscribe();
+    // a second, independent subscriber - e.g. a telemetry collector - observes every solution too
+    let mut telemetry_solution_receiver = hash_chain.subscribe();
+    let mut missed_solutions = 0;
+    let mut telemetry_missed_solutions = 0;
 
-    // start HW receiver
-    tokio::spawn(receiver_task(hash_chain.clone(), solution_sender));
+    // take the tx side up front so its work_id_count is known before `WorkPacer` is built - the
+    // pacer and `sender_task` must agree on exactly the same queue depth
+    let tx_io = hash_chain.take_work_tx_io().await;
+    let (mut work_pacer, work_receiver) = work_pacer::WorkPacer::new(tx_io.work_id_count());
 
     // start HW sender
-    tokio::spawn(sender_task(hash_chain.clone(), work_receiver));
+    tokio::spawn(sender_task(tx_io, work_receiver));
 
     // the first 3 work loads don't produce any solutions, these are merely to initialize the input
     // queue of each hashing chip
-    send_and_receive_test_workloads(&work_sender, &mut solution_receiver, 3, 0).await;
+    send_and_receive_test_workloads(
+        &mut work_pacer,
+        &mut solution_receiver,
+        &mut missed_solutions,
+        3,
+        0,
+    )
+    .await;
 
     // submit 2 more work items, since we are intentionally being slow all chips should send a
     // solution for the submitted work
@@ -206,12 +230,52 @@ async fn test_work_generation() {
     let expected_solution_count = more_work_count * chip_count;
 
     send_and_receive_test_workloads(
-        &work_sender,
+        &mut work_pacer,
+        &mut solution_receiver,
+        &mut missed_solutions,
+        more_work_count,
+        expected_solution_count,
+    )
+    .await;
+    assert_eq!(missed_solutions, 0, "pool path must not lag behind the hardware");
+
+    // push a live retune - no re-`init`, just a new target for the control task to step towards.
+    // Only the latest value matters, so this would be safe to call again before the chain has
+    // finished converging on this one
+    tuning_sender
+        .broadcast(tuning_settings(
+            config::DEFAULT_FREQUENCY_MHZ + 50.0,
+            *power::OPEN_CORE_VOLTAGE,
+        ))
+        .expect("tuning channel closed");
+
+    // give the control task a moment to step the chips towards the new target before throwing
+    // more work at them
+    delay_for(Duration::from_millis(100)).await;
+
+    let last_batch_work_ids = send_and_receive_test_workloads(
+        &mut work_pacer,
         &mut solution_receiver,
+        &mut missed_solutions,
         more_work_count,
         expected_solution_count,
     )
     .await;
+    assert_eq!(
+        missed_solutions, 0,
+        "pool path must not lag behind the hardware, even across a live retune"
+    );
+    // the last batch's own ids can never be retired yet - retirement only happens once later
+    // submissions push them out of the registry, and there is no batch after this one
+    assert!(!work_pacer.all_retired(&last_batch_work_ids).await);
+
+    // the telemetry subscriber observed the same solutions - drain it too so its lag counter
+    // stays accurate, even though this test doesn't assert on its contents
+    while recv_solution(&mut telemetry_solution_receiver, &mut telemetry_missed_solutions)
+        .timeout(Duration::from_millis(100))
+        .await
+        .is_ok()
+    {}
 
     // stop everything
     hash_chain.halt_sender.clone().send_halt().await;
@@ -0,0 +1,104 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Deterministic backpressure between work submission and a hash chain's input queue, see
+//! `WorkPacer`. Replaces pacing submission with a fixed delay and waiting for solutions with a
+//! wall-clock timeout - both flaky on slower or faster boards - with a semaphore and the
+//! `WorkRegistry`'s own retirement bookkeeping.
+
+use crate::hashchain;
+use crate::registry;
+
+use bosminer::work;
+
+use futures::lock::Mutex;
+
+use ii_async_compat::tokio;
+use tokio::sync::{mpsc, OwnedSemaphorePermit, Semaphore};
+
+use std::sync::Arc;
+
+/// Paces work submitted to a hash chain against its input queue depth with a semaphore instead
+/// of a fixed delay between sends, and tracks retirement through a shared `WorkRegistry` so
+/// callers can wait for specific work ids to be retired instead of racing a timeout. Used by the
+/// work-generation test's `sender_task` to submit work under this backpressure.
+///
+/// Sized with the full `work_id_count()`, same as the registry it wraps - not half of it.
+/// Retirement (and thus room being freed) only starts once the registry has filled up half its
+/// slots, so an initial pool of only `work_id_count() / 2` permits would never be replenished in
+/// time for the submission that triggers the first retirement.
+pub struct WorkPacer {
+    work_sender: mpsc::Sender<(work::Assignment, usize)>,
+    room: Arc<Semaphore>,
+    registry: Arc<Mutex<registry::WorkRegistry<hashchain::Solution>>>,
+    /// Permit reserving each in-flight work id's queue slot - dropped (returning the permit to
+    /// `room`) the moment the registry reports that id retired
+    room_permits: Arc<Mutex<Vec<Option<OwnedSemaphorePermit>>>>,
+}
+
+impl WorkPacer {
+    /// `work_id_count` must match the chain's `TxIo::work_id_count()` - the registry and
+    /// semaphore are both sized from it
+    pub fn new(work_id_count: usize) -> (Self, mpsc::Receiver<(work::Assignment, usize)>) {
+        let (work_sender, work_receiver) = mpsc::channel(work_id_count);
+        let pacer = Self {
+            work_sender,
+            room: Arc::new(Semaphore::new(work_id_count)),
+            registry: Arc::new(Mutex::new(registry::WorkRegistry::new(work_id_count))),
+            room_permits: Arc::new(Mutex::new((0..work_id_count).map(|_| None).collect())),
+        };
+        (pacer, work_receiver)
+    }
+
+    /// Waits until the pipeline has room for one more item of work, stores it in the registry
+    /// and submits it paired with its `work_id`. Returns the `work_id` so the caller can later
+    /// ask `all_retired` whether it's safe to assume no more solutions for it will arrive.
+    pub async fn submit(&mut self, work: work::Assignment) -> usize {
+        let permit = self
+            .room
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("WorkPacer semaphore was closed");
+        let (work_id, retired_id) = self.registry.lock().await.store_work(work.clone(), false);
+
+        let mut room_permits = self.room_permits.lock().await;
+        room_permits[work_id] = Some(permit);
+        if let Some(retired_id) = retired_id {
+            // dropping the permit returns it to `room` for a future submission
+            room_permits[retired_id] = None;
+        }
+        drop(room_permits);
+
+        self.work_sender
+            .send((work, work_id))
+            .await
+            .expect("WorkPacer receiver dropped");
+        work_id
+    }
+
+    /// True once every work id in `work_ids` has been retired from the registry, i.e. no
+    /// solution for any of them can still arrive
+    pub async fn all_retired(&self, work_ids: &[usize]) -> bool {
+        self.registry.lock().await.all_retired(work_ids)
+    }
+}
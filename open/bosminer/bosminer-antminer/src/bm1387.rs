@@ -22,6 +22,8 @@
 
 pub mod command;
 pub mod i2c;
+pub mod simulator;
+pub mod trace;
 
 use crate::error::{self, ErrorKind};
 use crate::utils::distance;
@@ -43,11 +45,98 @@ pub const HASH_COUNTING_REG: u8 = 0x14;
 /// Maximum supported baud rate clock divisor
 const MAX_BAUD_CLOCK_DIV: usize = 26;
 
-/// Basic divisor of the clock speed when calculating the value for the baud register
-pub const CHIP_OSC_CLK_BASE_BAUD_DIV: usize = 8;
+/// Per-generation parameters needed to talk to a chip - core count, oscillator base, nonce bit
+/// layout and how `CmdResponse`'s trailing bytes should be read. `ChipRev::params` picks the right
+/// implementation once `GetAddressReg` has told us which generation is actually on the chain, so
+/// nothing downstream of chain enumeration needs to hardcode a single part.
+pub trait ChipParams: Send + Sync {
+    /// How many cores are on the chip
+    fn num_cores_on_chip(&self) -> usize;
 
-/// How many cores are on the chip
-pub const NUM_CORES_ON_CHIP: usize = 114;
+    /// Basic divisor of the clock speed when calculating the value for the baud register
+    fn chip_osc_clk_base_baud_div(&self) -> usize;
+
+    /// `(shift, mask)` used to recover `CoreAddress::chip` from a solution nonce
+    fn core_address_chip_bits(&self) -> (u32, u32);
+
+    /// `(shift, mask)` used to recover `CoreAddress::core` from a solution nonce
+    fn core_address_core_bits(&self) -> (u32, u32);
+
+    /// Decodes `CmdResponse`'s two trailing bytes - a chip address/register number pair on
+    /// generations that carry one, `None` on BM1387 where they're always zero
+    fn response_address(&self, response: &CmdResponse) -> Option<(u8, u8)>;
+
+    /// Legal `fbdiv`/`refdiv`/VCO ranges `PllReg::solve` must search within for this generation.
+    /// Defaults to BM1387's own window, which is the only one this tree has measured.
+    fn pll_bounds(&self) -> PllBounds {
+        PllBounds::default()
+    }
+
+    /// Decodes the chip/core address a solution's nonce was computed by, using this generation's
+    /// bit layout
+    fn decode_core_address(&self, nonce: u32) -> CoreAddress {
+        let (chip_shift, chip_mask) = self.core_address_chip_bits();
+        let (core_shift, core_mask) = self.core_address_core_bits();
+        CoreAddress {
+            chip: ((nonce >> chip_shift) & chip_mask) as usize,
+            core: ((nonce >> core_shift) & core_mask) as usize,
+        }
+    }
+}
+
+/// BM1387 - the only generation this driver originally supported. Nonce bit layout and register
+/// addresses throughout this module are BM1387's; other `ChipParams` impls are relative to it.
+pub struct Bm1387Params;
+
+impl ChipParams for Bm1387Params {
+    fn num_cores_on_chip(&self) -> usize {
+        114
+    }
+
+    fn chip_osc_clk_base_baud_div(&self) -> usize {
+        8
+    }
+
+    fn core_address_chip_bits(&self) -> (u32, u32) {
+        (2, 0x3f)
+    }
+
+    fn core_address_core_bits(&self) -> (u32, u32) {
+        (24, 0x7f)
+    }
+
+    fn response_address(&self, _response: &CmdResponse) -> Option<(u8, u8)> {
+        None
+    }
+}
+
+/// BM1391 - a newer generation whose `CmdResponse` carries a chip address/register number instead
+/// of always reading zero there. No BM1391 datasheet or hardware is available in this tree, so its
+/// core count, baud divisor and nonce bit layout are assumed unchanged from BM1387 until measured
+/// against real silicon; only the `CmdResponse` interpretation is known to actually differ.
+pub struct Bm1391Params;
+
+impl ChipParams for Bm1391Params {
+    fn num_cores_on_chip(&self) -> usize {
+        114
+    }
+
+    fn chip_osc_clk_base_baud_div(&self) -> usize {
+        8
+    }
+
+    fn core_address_chip_bits(&self) -> (u32, u32) {
+        (2, 0x3f)
+    }
+
+    fn core_address_core_bits(&self) -> (u32, u32) {
+        (24, 0x7f)
+    }
+
+    fn response_address(&self, response: &CmdResponse) -> Option<(u8, u8)> {
+        Some((response.chip_address_or_zero, response.register_number_or_zero))
+    }
+}
 
 /// This enum is a bridge between chip address representation as we tend to
 /// think about it (addresses `0..=62`) and how the hardware addresses them
@@ -92,12 +181,10 @@ pub struct CoreAddress {
 }
 
 impl CoreAddress {
-    pub fn new(nonce: u32) -> Self {
-        let nonce = nonce as usize;
-        Self {
-            chip: (nonce >> 2) & 0x3f,
-            core: (nonce >> 24) & 0x7f,
-        }
+    /// Decodes `nonce` according to `params`' bit layout - which differs across chip generations,
+    /// see `ChipParams`
+    pub fn new(nonce: u32, params: &dyn ChipParams) -> Self {
+        params.decode_core_address(nonce)
     }
 }
 
@@ -166,12 +253,15 @@ impl CmdHeader {
 }
 
 /// Command response
+///
+/// The trailing two bytes are always zero on BM1387, but carry a chip address/register number on
+/// newer generations - see `ChipParams::response_address` for reading them generically.
 #[derive(PackedStruct, Debug)]
 #[packed_struct(endian = "msb")]
 pub struct CmdResponse {
     pub value: u32,
-    _zero_in_bm1387_but_its_chip_address_in_bm1391: u8,
-    _zero_in_bm1387_but_its_register_number_in_bm1391: u8,
+    chip_address_or_zero: u8,
+    register_number_or_zero: u8,
 }
 
 /// Sets configuration register
@@ -334,6 +424,7 @@ impl Register for GetAddressReg {
 #[derive(PrimitiveEnum_u16, Clone, Copy, Debug, PartialEq)]
 pub enum ChipRev {
     Bm1387 = 0x1387,
+    Bm1391 = 0x1391,
 }
 
 impl Default for ChipRev {
@@ -342,9 +433,21 @@ impl Default for ChipRev {
     }
 }
 
+impl ChipRev {
+    /// Picks the `ChipParams` for this revision - called once per chain at enumeration time, right
+    /// after `GetAddressReg` reports which generation is actually on the bus
+    pub fn params(&self) -> Box<dyn ChipParams> {
+        match self {
+            ChipRev::Bm1387 => Box::new(Bm1387Params),
+            ChipRev::Bm1391 => Box::new(Bm1391Params),
+        }
+    }
+}
+
 /// Chip revision with `EnumCatchAll` wrapper so we would have to import `packed_struct`
 /// everywhere.
 pub const CHIP_REV_BM1387: EnumCatchAll<ChipRev> = EnumCatchAll::Enum(ChipRev::Bm1387);
+pub const CHIP_REV_BM1391: EnumCatchAll<ChipRev> = EnumCatchAll::Enum(ChipRev::Bm1391);
 
 /// This register represents ASIC difficulty
 ///
@@ -356,7 +459,7 @@ pub const CHIP_REV_BM1387: EnumCatchAll<ChipRev> = EnumCatchAll::Enum(ChipRev::B
 ///
 /// The weird mask format came about probably because they did comparison on bit-reversed SHA
 /// hash, not just byte-reversed SHA hash.
-#[derive(PackedStruct, Debug, PartialEq)]
+#[derive(PackedStruct, Debug, Clone, PartialEq)]
 #[packed_struct(size_bytes = "4", endian = "msb")]
 pub struct TicketMaskReg {
     /// stores difficulty - 1
@@ -379,6 +482,11 @@ impl TicketMaskReg {
         let ticket_mask = (difficulty - 1).reverse_bits().swap_bytes();
         Ok(Self { ticket_mask })
     }
+
+    /// Recovers the ASIC difficulty this register was built from, inverting `new`
+    pub fn difficulty(&self) -> u32 {
+        self.ticket_mask.swap_bytes().reverse_bits() + 1
+    }
 }
 
 impl Register for TicketMaskReg {
@@ -529,7 +637,7 @@ impl Register for MiscCtrlReg {
 #[derive(PackedStruct, Debug, PartialEq, Clone)]
 #[packed_struct(bit_numbering = "lsb0", size_bytes = "4", endian = "msb")]
 pub struct PllReg {
-    /// Range: 60..=320, but in datasheet table: 32..=128
+    /// Range: 60..=255 (the field is only 8 bits wide), but in datasheet table: 32..=128
     #[packed_field(bits = "23:16")]
     pub fbdiv: u8,
     /// Range: 1..=63, but in datasheet always 2
@@ -548,6 +656,102 @@ impl Register for PllReg {
     const REG_NUM: u8 = 0x0c;
 }
 
+/// Legal `fbdiv`/`refdiv`/VCO ranges for `PllReg::solve` to search within - see
+/// `ChipParams::pll_bounds`. Different chip generations can plug in their own window; BM1387's
+/// (this struct's `Default`) is the only one actually measured in this tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PllBounds {
+    /// Valid `fbdiv` range - wider than the datasheet's example table (32..=128) but matching the
+    /// field's documented range
+    pub min_fbdiv: u8,
+    pub max_fbdiv: u8,
+    /// Operating window for the post-`refdiv` reference frequency fed into the PLL
+    pub min_ref_freq: usize,
+    pub max_ref_freq: usize,
+    /// Operating window for the VCO frequency (post-`fbdiv`, pre-`postdiv1`/`postdiv2`) - matches
+    /// the range BM1387's former factory divider table spanned at 25 MHz (400 MHz..=1.6 GHz)
+    pub min_vco_freq: u64,
+    pub max_vco_freq: u64,
+}
+
+impl Default for PllBounds {
+    fn default() -> Self {
+        Self {
+            min_fbdiv: 60,
+            max_fbdiv: 255,
+            min_ref_freq: 1_000_000,
+            max_ref_freq: 30_000_000,
+            min_vco_freq: 400_000_000,
+            max_vco_freq: 1_600_000_000,
+        }
+    }
+}
+
+impl PllReg {
+    /// Searches the full `refdiv`/`postdiv1`/`postdiv2`/`fbdiv` divider space - bounded by
+    /// `params.pll_bounds()` - for the `PllReg` whose resulting frequency (at crystal frequency
+    /// `xtal_freq`) is closest to `target_freq`.
+    pub fn solve(
+        target_freq: usize,
+        xtal_freq: usize,
+        params: &dyn ChipParams,
+    ) -> error::Result<PllReg> {
+        let bounds = params.pll_bounds();
+        let mut candidates = Vec::new();
+
+        for refdiv in 1..=63u8 {
+            let ref_freq = xtal_freq / refdiv as usize;
+            if ref_freq < bounds.min_ref_freq || ref_freq > bounds.max_ref_freq {
+                continue;
+            }
+            for postdiv1 in 1..=7u8 {
+                for postdiv2 in 1..=postdiv1 {
+                    let divider = refdiv as u64 * postdiv1 as u64 * postdiv2 as u64;
+                    let fbdiv = (target_freq as u64 * divider + xtal_freq as u64 / 2)
+                        / xtal_freq as u64;
+                    let fbdiv = fbdiv
+                        .max(bounds.min_fbdiv as u64)
+                        .min(bounds.max_fbdiv as u64) as u8;
+
+                    let vco_freq = xtal_freq as u64 * fbdiv as u64 / refdiv as u64;
+                    if vco_freq < bounds.min_vco_freq || vco_freq > bounds.max_vco_freq {
+                        continue;
+                    }
+
+                    candidates.push(PllReg {
+                        fbdiv,
+                        refdiv,
+                        postdiv1,
+                        postdiv2,
+                    });
+                }
+            }
+        }
+
+        candidates
+            .into_iter()
+            .min_by_key(|reg| {
+                let frequency = PllFrequency::new(reg.clone(), xtal_freq).frequency;
+                let vco_freq = xtal_freq as u64 * reg.fbdiv as u64 / reg.refdiv as u64;
+                // ties favor the higher VCO (more margin from the lower end of its window) and
+                // then the lower overall divider (lower "current" - fewer stages the PLL output
+                // has to be divided back down through)
+                (
+                    distance(frequency, target_freq),
+                    std::cmp::Reverse(vco_freq),
+                    reg.refdiv as u32 * reg.postdiv1 as u32 * reg.postdiv2 as u32,
+                )
+            })
+            .ok_or_else(|| {
+                ErrorKind::PLL(format!(
+                    "no PLL divider combination reaches {} Hz from a {} Hz crystal",
+                    target_freq, xtal_freq
+                ))
+                .into()
+            })
+    }
+}
+
 /// Represents PLL divider and associated frequency computed at some crystal speed (which is
 /// assumed common to all hashboards and constant over the duration of program)
 #[derive(Debug, Clone)]
@@ -573,74 +777,118 @@ impl PllFrequency {
     }
 }
 
-/// Table with precomputed dividers
+/// Looks up the best-matching `PllReg` for a crystal frequency. Used to be backed by a
+/// precomputed table of factory dividers for the 25 MHz crystal (which all fixed `refdiv = 2`,
+/// so the achievable frequencies were coarse), with other crystals falling back to
+/// `PllReg::solve`. Now every crystal - 25 MHz included - goes through the same full
+/// `refdiv`/`postdiv1`/`postdiv2`/`fbdiv` search, so per-chip tuning gets the same fine-grained
+/// result regardless of crystal.
 pub struct PllTable {
-    /// Crystal frequency for which was this table computed
-    #[allow(dead_code)]
+    /// Crystal frequency this table looks up dividers for
     xtal_freq: usize,
-    table: Vec<PllFrequency>,
-}
-
-pub const BM1387_FACTORY_DIVIDERS: &[u32] = &[
-    0x200241, 0x280241, 0x300241, 0x380241, 0x400241, 0x480241, 0x500241, 0x580241, 0x600241,
-    0x680241, 0x700241, 0x780241, 0x800241, 0x610231, 0x410221, 0x620231, 0x420221, 0x640231,
-    0x430221, 0x650231, 0x440221, 0x670231, 0x450221, 0x680231, 0x460221, 0x6a0231, 0x470221,
-    0x6b0231, 0x480221, 0x6d0231, 0x490221, 0x6e0231, 0x4a0221, 0x700231, 0x4b0221, 0x710231,
-    0x4c0221, 0x730231, 0x4d0221, 0x740231, 0x4e0221, 0x760231, 0x4f0221, 0x770231, 0x500221,
-    0x790231, 0x510221, 0x7a0231, 0x520221, 0x7c0231, 0x530221, 0x7d0231, 0x540221, 0x7f0231,
-    0x550221, 0x800231, 0x560221, 0x570221, 0x580221, 0x590221, 0x5a0221, 0x5b0221, 0x5c0221,
-    0x5d0221, 0x5e0221, 0x5f0221, 0x600221, 0x610221, 0x620221, 0x630221, 0x640221, 0x650221,
-    0x660221, 0x670221, 0x680221, 0x690221, 0x6a0221, 0x6b0221, 0x6c0221, 0x6d0221, 0x6e0221,
-    0x6f0221, 0x700221, 0x710221, 0x720221, 0x730221, 0x740221, 0x750221, 0x760221, 0x770221,
-    0x780221, 0x790221, 0x7a0221, 0x7b0221, 0x7c0221, 0x7d0221, 0x7e0221, 0x7f0221, 0x800221,
-    0x420211, 0x440211, 0x460211, 0x480211, 0x4a0211, 0x4c0211, 0x4e0211, 0x500211, 0x520211,
-    0x540211, 0x560211, 0x580211, 0x5a0211, 0x5c0211, 0x5e0211,
-];
+    /// Chip generation whose `PllBounds` every lookup searches within
+    params: Box<dyn ChipParams>,
+}
 
 impl PllTable {
-    pub fn new(xtal_freq: usize, table: Vec<PllFrequency>) -> Self {
-        Self { table, xtal_freq }
+    /// Build a lookup "table" for `xtal_freq` on the chip generation described by `params` -
+    /// there's nothing to precompute any more, this just remembers what `lookup` searches against
+    pub fn build_pll_table(xtal_freq: usize, params: Box<dyn ChipParams>) -> Self {
+        Self { xtal_freq, params }
+    }
+
+    /// Lookup the best divider for `target_freq`, searching the full divider space via
+    /// `PllReg::solve`
+    pub fn lookup(&self, target_freq: usize) -> error::Result<PllFrequency> {
+        let reg = PllReg::solve(target_freq, self.xtal_freq, self.params.as_ref())?;
+        Ok(PllFrequency::new(reg, self.xtal_freq))
     }
+}
 
-    /// Build lookup table from factory dividers
-    pub fn build_pll_table(xtal_freq: usize) -> Self {
-        // Factory table was computed for 25 MHz clock frequency
-        assert_eq!(xtal_freq, 25_000_000);
-        let mut table = BM1387_FACTORY_DIVIDERS
-            .iter()
-            .map(|&reg_val| PllFrequency::new(PllReg::from_reg(reg_val), xtal_freq))
-            .collect::<Vec<_>>();
-        table.sort_by(|a, b| a.frequency.cmp(&b.frequency));
+/// Version byte for `TuningProfile`'s on-flash encoding - bumped whenever the byte layout below
+/// changes, so a profile written by older firmware is rejected instead of silently misread
+const TUNING_PROFILE_VERSION: u8 = 1;
 
-        Self::new(xtal_freq, table)
+/// Persisted, per-hashboard tuning: the PLL divider actually chosen (together with the frequency
+/// it resolves to, carried alongside it purely so `from_bytes` can catch a corrupt blob or a
+/// crystal swapped since the profile was written), the ASIC difficulty's ticket mask, and the
+/// baud-rate control register. Mirrors how other configuration round-trips through flash, so a
+/// user's manually tuned settings are restored on boot instead of recomputed from defaults.
+#[derive(Debug, Clone)]
+pub struct TuningProfile {
+    pub pll: PllFrequency,
+    pub ticket_mask: TicketMaskReg,
+    pub misc_ctrl: MiscCtrlReg,
+}
+
+impl PartialEq for TuningProfile {
+    fn eq(&self, other: &Self) -> bool {
+        self.pll.reg == other.pll.reg
+            && self.pll.frequency == other.pll.frequency
+            && self.ticket_mask == other.ticket_mask
+            && self.misc_ctrl == other.misc_ctrl
     }
+}
 
-    /// Lookup best divider from a precomputed table
-    pub fn lookup(&self, target_freq: usize) -> error::Result<PllFrequency> {
-        // The table is sorted
-        let result = self
-            .table
-            .binary_search_by_key(&target_freq, |p| p.frequency);
-        match result {
-            Ok(i) => return Ok(self.table[i].clone()),
-            Err(i) => {
-                if i == 0 || i >= self.table.len() {
-                    Err(ErrorKind::PLL(format!(
-                        "Requested frequency {} out of range!",
-                        target_freq
-                    )))?
-                } else {
-                    if distance(self.table[i - 1].frequency, target_freq)
-                        <= distance(self.table[i].frequency, target_freq)
-                    {
-                        Ok(self.table[i - 1].clone())
-                    } else {
-                        Ok(self.table[i].clone())
-                    }
-                }
-            }
+impl TuningProfile {
+    pub fn new(pll: PllFrequency, ticket_mask: TicketMaskReg, misc_ctrl: MiscCtrlReg) -> Self {
+        Self {
+            pll,
+            ticket_mask,
+            misc_ctrl,
         }
     }
+
+    /// Encodes this profile as `[version][PllReg][frequency as u64][TicketMaskReg][MiscCtrlReg]`,
+    /// every multi-byte field big-endian like every other on-chip register in this module
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::ENCODED_LEN);
+        bytes.push(TUNING_PROFILE_VERSION);
+        bytes.extend_from_slice(&self.pll.reg.to_reg().to_be_bytes());
+        bytes.extend_from_slice(&(self.pll.frequency as u64).to_be_bytes());
+        bytes.extend_from_slice(&self.ticket_mask.to_reg().to_be_bytes());
+        bytes.extend_from_slice(&self.misc_ctrl.to_reg().to_be_bytes());
+        bytes
+    }
+
+    /// Decodes and validates a blob written by `to_bytes`, recomputing the PLL frequency at
+    /// `xtal_freq` and rejecting the profile if it doesn't match the frequency stored alongside
+    /// the divider - a mismatch means either a corrupt blob or a crystal swapped since the profile
+    /// was written
+    pub fn from_bytes(bytes: &[u8], xtal_freq: usize) -> error::Result<Self> {
+        if bytes.len() != Self::ENCODED_LEN {
+            Err(ErrorKind::General(format!(
+                "tuning profile blob has {} bytes, expected {}",
+                bytes.len(),
+                Self::ENCODED_LEN
+            )))?
+        }
+        if bytes[0] != TUNING_PROFILE_VERSION {
+            Err(ErrorKind::General(format!(
+                "tuning profile version {} is not supported, expected {}",
+                bytes[0], TUNING_PROFILE_VERSION
+            )))?
+        }
+
+        let pll_reg = PllReg::from_reg(u32::from_be_bytes(bytes[1..5].try_into().unwrap()));
+        let stored_frequency = u64::from_be_bytes(bytes[5..13].try_into().unwrap()) as usize;
+        let ticket_mask =
+            TicketMaskReg::from_reg(u32::from_be_bytes(bytes[13..17].try_into().unwrap()));
+        let misc_ctrl = MiscCtrlReg::from_reg(u32::from_be_bytes(bytes[17..21].try_into().unwrap()));
+
+        let pll = PllFrequency::new(pll_reg, xtal_freq);
+        if pll.frequency != stored_frequency {
+            Err(ErrorKind::General(format!(
+                "tuning profile's stored frequency {} doesn't match {} recomputed at a {} Hz \
+                 crystal - blob is corrupt or the crystal has changed",
+                stored_frequency, pll.frequency, xtal_freq
+            )))?
+        }
+
+        Ok(Self::new(pll, ticket_mask, misc_ctrl))
+    }
+
+    const ENCODED_LEN: usize = 1 + 4 + 8 + 4 + 4;
 }
 
 #[cfg(test)]
@@ -696,6 +944,14 @@ mod test {
         assert_eq!(cmd_bytes, expected_cmd_with_padding);
     }
 
+    #[test]
+    fn test_ticket_mask_difficulty_round_trip() {
+        for difficulty in &[1u32, 2, 64, 1024] {
+            let reg = TicketMaskReg::new(*difficulty).expect("cannot build difficulty register");
+            assert_eq!(reg.difficulty(), *difficulty);
+        }
+    }
+
     /// Verify serialization of SetConfig(MISC_CONTROL(...)) command
     #[test]
     fn build_set_config_misc_control() {
@@ -743,6 +999,56 @@ mod test {
         assert_eq!(reg, misc_reg,);
     }
 
+    /// Verify a `TuningProfile` round-trips through `to_bytes`/`from_bytes` unchanged
+    #[test]
+    fn test_tuning_profile_round_trip() {
+        let pll = PllFrequency::new(PllReg::from_reg(0x00680221), DEFAULT_XTAL_FREQ);
+        let ticket_mask = TicketMaskReg::new(64).expect("cannot build difficulty register");
+        let misc_ctrl = MiscCtrlReg::new(true, true, 26, true, true).expect("invalid divisor");
+        let profile = TuningProfile::new(pll, ticket_mask, misc_ctrl);
+
+        let bytes = profile.to_bytes();
+        let decoded =
+            TuningProfile::from_bytes(&bytes, DEFAULT_XTAL_FREQ).expect("round-trip failed");
+        assert_eq!(decoded, profile);
+    }
+
+    #[test]
+    fn test_tuning_profile_rejects_unsupported_version() {
+        let profile = TuningProfile::new(
+            PllFrequency::new(PllReg::from_reg(0x00680221), DEFAULT_XTAL_FREQ),
+            TicketMaskReg::new(1).expect("cannot build difficulty register"),
+            MiscCtrlReg::new(false, false, 26, false, false).expect("invalid divisor"),
+        );
+        let mut bytes = profile.to_bytes();
+        bytes[0] = TUNING_PROFILE_VERSION + 1;
+        assert!(TuningProfile::from_bytes(&bytes, DEFAULT_XTAL_FREQ).is_err());
+    }
+
+    #[test]
+    fn test_tuning_profile_rejects_truncated_blob() {
+        let profile = TuningProfile::new(
+            PllFrequency::new(PllReg::from_reg(0x00680221), DEFAULT_XTAL_FREQ),
+            TicketMaskReg::new(1).expect("cannot build difficulty register"),
+            MiscCtrlReg::new(false, false, 26, false, false).expect("invalid divisor"),
+        );
+        let bytes = profile.to_bytes();
+        assert!(TuningProfile::from_bytes(&bytes[..bytes.len() - 1], DEFAULT_XTAL_FREQ).is_err());
+    }
+
+    #[test]
+    fn test_tuning_profile_rejects_frequency_mismatch_from_crystal_change() {
+        let profile = TuningProfile::new(
+            PllFrequency::new(PllReg::from_reg(0x00680221), DEFAULT_XTAL_FREQ),
+            TicketMaskReg::new(1).expect("cannot build difficulty register"),
+            MiscCtrlReg::new(false, false, 26, false, false).expect("invalid divisor"),
+        );
+        let bytes = profile.to_bytes();
+        // a different crystal resolves the same divider to a different frequency than what's
+        // stored in the blob, and must be rejected rather than silently accepted
+        assert!(TuningProfile::from_bytes(&bytes, DEFAULT_XTAL_FREQ * 2).is_err());
+    }
+
     /// Builds a get status command to read chip address of all chips
     #[test]
     fn build_get_status_cmd() {
@@ -933,59 +1239,103 @@ mod test {
 
     #[test]
     fn test_pll_search() {
-        let table = PllTable::build_pll_table(DEFAULT_XTAL_FREQ);
+        let table = PllTable::build_pll_table(DEFAULT_XTAL_FREQ, Box::new(Bm1387Params));
 
-        // boundary conditions
-        assert_eq!(lookup_one(&table, 100_000_000), Some(100_000_000));
-        assert_eq!(lookup_one(&table, 1_175_000_000), Some(1_175_000_000));
-        // should fail: too low
-        assert_eq!(lookup_one(&table, 0), None);
-        assert_eq!(lookup_one(&table, 50_000_000), None);
-        assert_eq!(lookup_one(&table, 99_999_999), None);
-        // should fail: too high
-        assert_eq!(lookup_one(&table, 1_175_000_001), None);
-        assert_eq!(lookup_one(&table, 4_000_000_000), None);
-
-        // approximate lookups
-        assert_eq!(lookup_one(&table, 703_125_000), Some(700_000_000));
-        assert_eq!(lookup_one(&table, 703_125_001), Some(706_250_000));
-
-        // exact lookups
+        // exact lookups, reachable with a refdiv of 2 - same as the old factory table
         assert_eq!(lookup_one(&table, 650_000_000), Some(650_000_000));
-        assert_eq!(lookup_one(&table, 1_025_000_000), Some(1025000000));
+        assert_eq!(lookup_one(&table, 1_025_000_000), Some(1_025_000_000));
+        assert_eq!(lookup_one(&table, 1_175_000_000), Some(1_175_000_000));
+
+        // the old factory table fixed refdiv at 2, so this only resolved to within 3.125 MHz
+        // (700_000_000); searching the full divider space (refdiv = 4 here) hits it exactly
+        assert_eq!(lookup_one(&table, 703_125_000), Some(703_125_000));
+
+        // out-of-table-range targets used to come back as an error; the full search still finds
+        // the closest divider combination it can reach instead of giving up
+        for &target_freq in &[0, 50_000_000, 4_000_000_000] {
+            let frequency = lookup_one(&table, target_freq).expect("search never gives up");
+            assert!(frequency > 0);
+        }
+    }
+
+    #[test]
+    fn test_pll_solve_gets_close_to_target() {
+        for &target_freq in &[100_000_000, 375_000_000, 650_000_000, 1_000_000_000] {
+            let reg = PllReg::solve(target_freq, DEFAULT_XTAL_FREQ, &Bm1387Params).expect("solver failed");
+            let frequency = PllFrequency::new(reg, DEFAULT_XTAL_FREQ).frequency;
+            // the solver isn't expected to hit every target exactly, but it must land within a
+            // fraction of the crystal's own granularity
+            assert!(
+                distance(frequency, target_freq) < 2_000_000,
+                "solved frequency {} too far from target {}",
+                frequency,
+                target_freq
+            );
+        }
+    }
+
+    #[test]
+    fn test_pll_solve_works_for_a_non_factory_crystal() {
+        let xtal_freq = 20_000_000;
+        let target_freq = 650_000_000;
+        let reg = PllReg::solve(target_freq, xtal_freq, &Bm1387Params).expect("solver failed");
+        let frequency = PllFrequency::new(reg, xtal_freq).frequency;
+        assert!(distance(frequency, target_freq) < 2_000_000);
+    }
+
+    #[test]
+    fn test_pll_solve_rejects_unreachable_frequency() {
+        // `fbdiv` is clamped into range, so any target is reachable from a 25 MHz crystal; with a
+        // 500 kHz crystal no `refdiv` puts the reference frequency inside its window
+        assert!(PllReg::solve(650_000_000, 500_000, &Bm1387Params).is_err());
+    }
+
+    #[test]
+    fn test_build_pll_table_searches_full_space_for_other_crystals_too() {
+        let table = PllTable::build_pll_table(20_000_000, Box::new(Bm1387Params));
+        let target_freq = 650_000_000;
+        let frequency = table.lookup(target_freq).expect("lookup failed").frequency;
+        assert!(distance(frequency, target_freq) < 2_000_000);
+    }
+
+    #[test]
+    fn test_pll_search_is_not_pinned_to_refdiv_two() {
+        let table = PllTable::build_pll_table(DEFAULT_XTAL_FREQ, Box::new(Bm1387Params));
+        let PllFrequency { reg, .. } = table.lookup(703_125_000).expect("lookup failed");
+        assert_ne!(reg.refdiv, 2);
     }
 
     #[test]
     fn test_core_address() {
         assert_eq!(
-            CoreAddress::new(0xffffffff),
+            CoreAddress::new(0xffffffff, &Bm1387Params),
             CoreAddress {
                 chip: 0x3f,
                 core: 0x7f
             }
         );
         assert_eq!(
-            CoreAddress::new(0x2a105d5d),
+            CoreAddress::new(0x2a105d5d, &Bm1387Params),
             CoreAddress { chip: 23, core: 42 }
         );
         assert_eq!(
-            CoreAddress::new(0xd25738d3),
+            CoreAddress::new(0xd25738d3, &Bm1387Params),
             CoreAddress { chip: 52, core: 82 }
         );
         assert_eq!(
-            CoreAddress::new(0x47268d19),
+            CoreAddress::new(0x47268d19, &Bm1387Params),
             CoreAddress { chip: 6, core: 71 }
         );
         assert_eq!(
-            CoreAddress::new(0xa5e09223),
+            CoreAddress::new(0xa5e09223, &Bm1387Params),
             CoreAddress { chip: 8, core: 37 }
         );
         assert_eq!(
-            CoreAddress::new(0xd57c1ce4),
+            CoreAddress::new(0xd57c1ce4, &Bm1387Params),
             CoreAddress { chip: 57, core: 85 }
         );
         assert_eq!(
-            CoreAddress::new(0x40e55650),
+            CoreAddress::new(0x40e55650, &Bm1387Params),
             CoreAddress { chip: 20, core: 64 }
         );
     }
@@ -1005,4 +1355,68 @@ mod test {
             100_000_000
         );
     }
+
+    #[test]
+    fn test_chip_rev_picks_matching_params() {
+        assert_eq!(ChipRev::Bm1387.params().num_cores_on_chip(), 114);
+        assert_eq!(ChipRev::Bm1391.params().num_cores_on_chip(), 114);
+    }
+
+    #[test]
+    fn test_response_address_differs_by_generation() {
+        let response = CmdResponse {
+            value: 0,
+            chip_address_or_zero: 0x08,
+            register_number_or_zero: 0x1c,
+        };
+        assert_eq!(Bm1387Params.response_address(&response), None);
+        assert_eq!(Bm1391Params.response_address(&response), Some((0x08, 0x1c)));
+    }
+
+    /// Stand-in for a hypothetical chip generation with a narrower VCO window than BM1387's, used
+    /// to prove `PllReg::solve` actually honors a non-default `ChipParams::pll_bounds` rather than
+    /// just ignoring it in favor of the BM1387 constants
+    struct NarrowVcoParams;
+
+    impl ChipParams for NarrowVcoParams {
+        fn num_cores_on_chip(&self) -> usize {
+            Bm1387Params.num_cores_on_chip()
+        }
+
+        fn chip_osc_clk_base_baud_div(&self) -> usize {
+            Bm1387Params.chip_osc_clk_base_baud_div()
+        }
+
+        fn core_address_chip_bits(&self) -> (u32, u32) {
+            Bm1387Params.core_address_chip_bits()
+        }
+
+        fn core_address_core_bits(&self) -> (u32, u32) {
+            Bm1387Params.core_address_core_bits()
+        }
+
+        fn response_address(&self, response: &CmdResponse) -> Option<(u8, u8)> {
+            Bm1387Params.response_address(response)
+        }
+
+        fn pll_bounds(&self) -> PllBounds {
+            PllBounds {
+                min_vco_freq: 600_000_000,
+                max_vco_freq: 800_000_000,
+                ..PllBounds::default()
+            }
+        }
+    }
+
+    #[test]
+    fn test_pll_bounds_are_pluggable_per_chip_generation() {
+        let reg = PllReg::solve(100_000_000, DEFAULT_XTAL_FREQ, &NarrowVcoParams)
+            .expect("solver failed");
+        let vco_freq = DEFAULT_XTAL_FREQ as u64 * reg.fbdiv as u64 / reg.refdiv as u64;
+        assert!(
+            vco_freq >= 600_000_000 && vco_freq <= 800_000_000,
+            "solver escaped the narrowed VCO window: {}",
+            vco_freq
+        );
+    }
 }
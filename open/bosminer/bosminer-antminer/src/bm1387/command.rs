@@ -0,0 +1,124 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Bounded polling for chip register round-trips, see `PollCountDown`. A `GetStatusCmd` that
+//! waits for the chip to settle on something (an `I2cControlReg` transaction, or any other
+//! register that isn't necessarily ready on the first read) must not spin forever - a NAK'd I2C
+//! device or a wedged bus would otherwise hang the chain controller.
+
+use crate::error::{self, ErrorKind};
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Bounds a `GetStatusCmd` poll loop, either by a maximum number of retries or by a wall-clock
+/// budget with a sleep interval between attempts - whichever the caller constructs it with
+pub enum PollCountDown {
+    Attempts {
+        remaining: usize,
+    },
+    Timeout {
+        deadline: Instant,
+        sleep: Duration,
+    },
+}
+
+impl PollCountDown {
+    /// Bounds the poll by a fixed number of `GetStatusCmd` round-trips
+    pub fn with_attempts(max_attempts: usize) -> Self {
+        Self::Attempts {
+            remaining: max_attempts,
+        }
+    }
+
+    /// Bounds the poll by wall-clock time, sleeping `sleep` between consecutive `GetStatusCmd`s
+    pub fn with_timeout(budget: Duration, sleep: Duration) -> Self {
+        Self::Timeout {
+            deadline: Instant::now() + budget,
+            sleep,
+        }
+    }
+
+    /// Polls `poll_status` - expected to issue a `GetStatusCmd`, parse the response and return
+    /// `Some(value)` once the chip is done (e.g. `I2cControlFlags::busy` cleared) or `None` to
+    /// keep waiting - until it settles or this countdown's bound is exhausted
+    pub fn wait_until<T, F>(mut self, mut poll_status: F) -> error::Result<T>
+    where
+        F: FnMut() -> error::Result<Option<T>>,
+    {
+        loop {
+            if let Some(value) = poll_status()? {
+                return Ok(value);
+            }
+            self.tick()?;
+        }
+    }
+
+    /// Accounts for one poll attempt having just come back without settling. Returns
+    /// `ErrorKind::Timeout` once the bound configured at construction is exhausted.
+    fn tick(&mut self) -> error::Result<()> {
+        match self {
+            Self::Attempts { remaining } => {
+                if *remaining == 0 {
+                    Err(ErrorKind::Timeout("exhausted poll attempts".to_string()))?
+                }
+                *remaining -= 1;
+            }
+            Self::Timeout { deadline, sleep } => {
+                if Instant::now() >= *deadline {
+                    Err(ErrorKind::Timeout("exhausted poll time budget".to_string()))?
+                }
+                thread::sleep(*sleep);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wait_until_returns_as_soon_as_settled() {
+        let mut calls = 0;
+        let result = PollCountDown::with_attempts(10).wait_until(|| {
+            calls += 1;
+            Ok(if calls == 3 { Some(calls) } else { None })
+        });
+        assert_eq!(result.expect("should have settled"), 3);
+    }
+
+    #[test]
+    fn test_wait_until_times_out_on_exhausted_attempts() {
+        let result: error::Result<()> =
+            PollCountDown::with_attempts(3).wait_until(|| Ok(None));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_wait_until_propagates_poll_error() {
+        let result: error::Result<()> = PollCountDown::with_attempts(10)
+            .wait_until(|| Err(ErrorKind::I2c("NAK".to_string()).into()));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,331 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! `embedded-hal` I2C master driven through the chip's onboard I2C bridge, see `I2c`. The bridge
+//! itself is just the `I2cControlReg` status/control register - there is no driver surface here
+//! until this module wraps it, so any off-the-shelf EEPROM or temperature-sensor crate written
+//! against `embedded_hal::blocking::i2c` can be reused against the hashboard's I2C bus instead of
+//! hand-rolling register pokes. `I2c::read_i2c`/`write_i2c` additionally cover the common
+//! addressed-register access pattern (temperature sensors, the hashboard EEPROM) directly, for
+//! callers that don't need a full `embedded_hal` device driver.
+
+use super::command::PollCountDown;
+use super::{ChipAddress, I2cBusSelect, I2cControlFlags, I2cControlReg, MiscCtrlReg, Register};
+use crate::error::{self, ErrorKind};
+
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+
+/// How many times to re-issue `GetStatusCmd` while waiting for `I2cControlReg.flags.busy` to
+/// clear on a single byte transfer before giving up - a NAK'd device or a wedged bus must not be
+/// able to hang the chain controller
+const MAX_POLL_ATTEMPTS: usize = 1000;
+
+/// The seam `I2c` needs from the rest of the driver stack: something that can push a command built
+/// via `SetConfigCmd`/`GetStatusCmd` out to a specific chip and - for a status request - hand back
+/// the register value the chip wrote back. Implemented by the chain's command channel.
+pub trait CommandContext {
+    /// Drive `SetConfigCmd::new(chip_address, register, value)` to completion
+    fn set_config(&mut self, chip_address: ChipAddress, register: u8, value: u32) -> error::Result<()>;
+
+    /// Drive `GetStatusCmd::new(chip_address, register)` to completion and return the value the
+    /// chip responded with
+    fn get_status(&mut self, chip_address: ChipAddress, register: u8) -> error::Result<u32>;
+}
+
+/// `embedded_hal::blocking::i2c::{Read, Write, WriteRead}` on top of a chip's onboard I2C master.
+/// Routes SCL0/SDA0 to `bus` once at construction time (a read-modify-write of `MiscCtrlReg` that
+/// otherwise leaves the register's baud rate/AsicBoost bits untouched), then drives one chip
+/// register transaction - `I2cControlReg` - per transferred byte.
+pub struct I2c<'a, C> {
+    ctx: &'a mut C,
+    chip_address: ChipAddress,
+}
+
+impl<'a, C: CommandContext> I2c<'a, C> {
+    pub fn new(ctx: &'a mut C, chip_address: ChipAddress, bus: I2cBusSelect) -> error::Result<Self> {
+        let mut i2c = Self { ctx, chip_address };
+        i2c.select_bus(bus)?;
+        Ok(i2c)
+    }
+
+    /// Read-modify-write `MiscCtrlReg` so SCL0/SDA0 are routed to `bus`, leaving every other
+    /// setting (baud rate divisor, AsicBoost, ...) as the chip already had it
+    fn select_bus(&mut self, bus: I2cBusSelect) -> error::Result<()> {
+        let mut misc = MiscCtrlReg::from_reg(
+            self.ctx.get_status(self.chip_address, MiscCtrlReg::REG_NUM)?,
+        );
+        misc.set_i2c(Some(bus));
+        self.ctx
+            .set_config(self.chip_address, MiscCtrlReg::REG_NUM, misc.to_reg())
+    }
+
+    /// Transfers a single byte: sets up `I2cControlReg` with `addr`/`reg`/`data` and
+    /// `do_command=1` via `SetConfigCmd`, then polls it back with `GetStatusCmd` - bounded by
+    /// `PollCountDown` so a NAK'd device or a wedged bus times out instead of hanging - until
+    /// `flags.busy` clears, returning the `data` byte the chip reports (the read result for a
+    /// read, an echo of what was written for a write)
+    fn transfer_byte(&mut self, addr: u8, reg: u8, data: u8) -> error::Result<u8> {
+        let request = I2cControlReg {
+            flags: I2cControlFlags {
+                busy: false,
+                error: false,
+                do_command: true,
+            },
+            addr,
+            reg,
+            data,
+        };
+        self.ctx
+            .set_config(self.chip_address, I2cControlReg::REG_NUM, request.to_reg())?;
+
+        PollCountDown::with_attempts(MAX_POLL_ATTEMPTS).wait_until(|| {
+            let status = I2cControlReg::from_reg(
+                self.ctx.get_status(self.chip_address, I2cControlReg::REG_NUM)?,
+            );
+            if status.flags.error {
+                Err(ErrorKind::I2c("chip I2C transaction failed".to_string()))?
+            }
+            Ok(if status.flags.busy { None } else { Some(status.data) })
+        })
+    }
+
+    /// Reads `buffer.len()` bytes starting at `register` on the device at 7-bit `device_address` -
+    /// a write of the register offset followed by a repeated-start read, the "random read"
+    /// sequence EEPROMs and sensors expect for addressed register access (e.g. a board's
+    /// temperature sensor or EEPROM)
+    pub fn read_i2c(
+        &mut self,
+        device_address: u8,
+        register: u8,
+        buffer: &mut [u8],
+    ) -> error::Result<()> {
+        self.write_read(device_address, &[register], buffer)
+    }
+
+    /// Writes `data` starting at `register` on the device at 7-bit `device_address`
+    pub fn write_i2c(&mut self, device_address: u8, register: u8, data: &[u8]) -> error::Result<()> {
+        let mut bytes = Vec::with_capacity(1 + data.len());
+        bytes.push(register);
+        bytes.extend_from_slice(data);
+        self.write(device_address, &bytes)
+    }
+}
+
+impl<'a, C: CommandContext> Write for I2c<'a, C> {
+    type Error = error::Error;
+
+    /// `address` is the 7-bit I2C address - the 8-bit form's odd (write) bit is added here, one
+    /// `I2cControlReg` transaction per byte, `reg` counting up from 0
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        let addr = (address << 1) | 1;
+        for (reg, &data) in bytes.iter().enumerate() {
+            self.transfer_byte(addr, reg as u8, data)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, C: CommandContext> Read for I2c<'a, C> {
+    type Error = error::Error;
+
+    /// `address` is the 7-bit I2C address - the 8-bit form's even (read) bit is added here, one
+    /// `I2cControlReg` transaction per byte, `reg` counting up from 0
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        let addr = address << 1;
+        for (reg, slot) in buffer.iter_mut().enumerate() {
+            *slot = self.transfer_byte(addr, reg as u8, 0)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, C: CommandContext> WriteRead for I2c<'a, C> {
+    type Error = error::Error;
+
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.write(address, bytes)?;
+        self.read(address, buffer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Fake chip that just remembers the last register it was told to set and always reports
+    /// back `busy=false` with a fixed data byte, so the polling loop in `transfer_byte` resolves
+    /// on the very first `get_status`
+    struct FakeChip {
+        last_set: Vec<(ChipAddress, u8, u32)>,
+        status_data: u8,
+        report_error: bool,
+    }
+
+    impl FakeChip {
+        fn new(status_data: u8) -> Self {
+            Self {
+                last_set: Vec::new(),
+                status_data,
+                report_error: false,
+            }
+        }
+    }
+
+    impl CommandContext for FakeChip {
+        fn set_config(
+            &mut self,
+            chip_address: ChipAddress,
+            register: u8,
+            value: u32,
+        ) -> error::Result<()> {
+            self.last_set.push((chip_address, register, value));
+            Ok(())
+        }
+
+        fn get_status(&mut self, _chip_address: ChipAddress, register: u8) -> error::Result<u32> {
+            if register == MiscCtrlReg::REG_NUM {
+                return Ok(MiscCtrlReg::new(true, false, 0, false, false)
+                    .expect("valid divisor")
+                    .to_reg());
+            }
+            let status = I2cControlReg {
+                flags: I2cControlFlags {
+                    busy: false,
+                    error: self.report_error,
+                    do_command: false,
+                },
+                addr: 0,
+                reg: 0,
+                data: self.status_data,
+            };
+            Ok(status.to_reg())
+        }
+    }
+
+    #[test]
+    fn test_new_selects_bus() {
+        let mut chip = FakeChip::new(0);
+        let _i2c = I2c::new(&mut chip, ChipAddress::One(0), I2cBusSelect::Bottom)
+            .expect("bus selection failed");
+
+        let (_, register, value) = chip.last_set.last().expect("MiscCtrlReg was never written");
+        assert_eq!(*register, MiscCtrlReg::REG_NUM);
+        let misc = MiscCtrlReg::from_reg(*value);
+        assert_eq!(misc.i2c_bus, I2cBusSelect::Bottom);
+    }
+
+    #[test]
+    fn test_write_uses_odd_address_and_counts_registers() {
+        let mut chip = FakeChip::new(0);
+        let mut i2c =
+            I2c::new(&mut chip, ChipAddress::One(0), I2cBusSelect::Bottom).expect("new failed");
+        i2c.write(0x50, &[0xaa, 0xbb]).expect("write failed");
+
+        let written: Vec<_> = chip
+            .last_set
+            .iter()
+            .filter(|(_, register, _)| *register == I2cControlReg::REG_NUM)
+            .map(|(_, _, value)| I2cControlReg::from_reg(*value))
+            .collect();
+        assert_eq!(written.len(), 2);
+        assert_eq!(written[0].addr, (0x50 << 1) | 1);
+        assert_eq!(written[0].reg, 0);
+        assert_eq!(written[0].data, 0xaa);
+        assert_eq!(written[1].reg, 1);
+        assert_eq!(written[1].data, 0xbb);
+    }
+
+    #[test]
+    fn test_read_uses_even_address_and_returns_status_data() {
+        let mut chip = FakeChip::new(0x42);
+        let mut i2c =
+            I2c::new(&mut chip, ChipAddress::One(0), I2cBusSelect::Bottom).expect("new failed");
+        let mut buffer = [0u8; 2];
+        i2c.read(0x50, &mut buffer).expect("read failed");
+        assert_eq!(buffer, [0x42, 0x42]);
+
+        let written: Vec<_> = chip
+            .last_set
+            .iter()
+            .filter(|(_, register, _)| *register == I2cControlReg::REG_NUM)
+            .map(|(_, _, value)| I2cControlReg::from_reg(*value))
+            .collect();
+        assert_eq!(written[0].addr, 0x50 << 1);
+    }
+
+    #[test]
+    fn test_read_i2c_writes_register_then_reads_with_repeated_start() {
+        let mut chip = FakeChip::new(0x7);
+        let mut i2c =
+            I2c::new(&mut chip, ChipAddress::One(0), I2cBusSelect::Bottom).expect("new failed");
+        let mut buffer = [0u8; 2];
+        i2c.read_i2c(0x50, 0x03, &mut buffer).expect("read_i2c failed");
+        assert_eq!(buffer, [0x7, 0x7]);
+
+        let written: Vec<_> = chip
+            .last_set
+            .iter()
+            .filter(|(_, register, _)| *register == I2cControlReg::REG_NUM)
+            .map(|(_, _, value)| I2cControlReg::from_reg(*value))
+            .collect();
+        // the register offset is written first (odd/write address), then the repeated-start read
+        // begins (even/read address)
+        assert_eq!(written[0].addr, (0x50 << 1) | 1);
+        assert_eq!(written[0].data, 0x03);
+        assert_eq!(written[1].addr, 0x50 << 1);
+    }
+
+    #[test]
+    fn test_write_i2c_sends_register_then_data() {
+        let mut chip = FakeChip::new(0);
+        let mut i2c =
+            I2c::new(&mut chip, ChipAddress::One(0), I2cBusSelect::Bottom).expect("new failed");
+        i2c.write_i2c(0x50, 0x03, &[0xaa, 0xbb])
+            .expect("write_i2c failed");
+
+        let written: Vec<_> = chip
+            .last_set
+            .iter()
+            .filter(|(_, register, _)| *register == I2cControlReg::REG_NUM)
+            .map(|(_, _, value)| I2cControlReg::from_reg(*value))
+            .collect();
+        assert_eq!(written.len(), 3);
+        assert_eq!(written[0].data, 0x03);
+        assert_eq!(written[1].data, 0xaa);
+        assert_eq!(written[2].data, 0xbb);
+    }
+
+    #[test]
+    fn test_transfer_byte_reports_chip_error() {
+        let mut chip = FakeChip::new(0);
+        chip.report_error = true;
+        let mut i2c =
+            I2c::new(&mut chip, ChipAddress::One(0), I2cBusSelect::Bottom).expect("new failed");
+        let result = i2c.write(0x50, &[0xaa]);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,350 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! A software model of a BM1387 chain, see `Bm1387Simulator`. Every other test in this module only
+//! checks `pack()`/`unpack_from_slice()` byte round-trips in isolation; this model instead
+//! consumes the same packed command bytes real hardware would see and answers the same way a
+//! chain would - so chain enumeration, frequency tuning and work distribution can be driven
+//! end-to-end in CI instead of only ever being exercised against real silicon.
+
+use super::{
+    Bm1387Params, ChipParams, CmdResponse, GetAddressReg, MiscCtrlReg, PllFrequency, PllReg,
+    Register, TicketMaskReg, CHIP_REV_BM1387,
+};
+use crate::error::{self, ErrorKind};
+
+use packed_struct::prelude::*;
+
+use std::convert::TryInto;
+use std::time::Duration;
+
+/// Command opcodes this simulator understands - the same codes `CmdHeader::new`/`new_extended`
+/// bakes into real commands elsewhere in this module. `pub` rather than private since `trace`
+/// dispatches on the same codes to decode a capture.
+pub mod opcode {
+    pub const SET_CHIP_ADDRESS: u8 = 0x01;
+    pub const GET_STATUS: u8 = 0x04;
+    pub const INACTIVATE_FROM_CHAIN: u8 = 0x05;
+    pub const SET_CONFIG: u8 = 0x08;
+}
+
+/// Per-chip state the simulator tracks - everything a real chip remembers between commands
+#[derive(Debug, Clone)]
+struct ChipState {
+    /// Hardware address (`ChipAddress::to_hw_addr`'s output) - `None` until `SetChipAddressCmd`
+    /// walks the chain and assigns one, cleared back to `None` by `InactivateFromChainCmd`
+    addr: Option<u8>,
+    misc_ctrl: MiscCtrlReg,
+    ticket_mask: TicketMaskReg,
+    pll: PllReg,
+    /// Simulated progress towards the next solution, see `Bm1387Simulator::step`
+    nonce_counter: u64,
+    /// How many solutions this chip has produced so far, used only to vary the `core` field of
+    /// successive synthetic nonces
+    solutions_found: u64,
+}
+
+impl Default for ChipState {
+    fn default() -> Self {
+        Self {
+            addr: None,
+            misc_ctrl: MiscCtrlReg::new(true, false, 0, false, false).expect("valid divisor"),
+            ticket_mask: TicketMaskReg::new(1).expect("valid difficulty"),
+            pll: PllReg {
+                fbdiv: 0,
+                refdiv: 1,
+                postdiv1: 1,
+                postdiv2: 1,
+            },
+            nonce_counter: 0,
+            solutions_found: 0,
+        }
+    }
+}
+
+/// A chain of `chip_count` simulated BM1387s, addressed and configured the same way real silicon
+/// is: every chip starts unaddressed, and `SetChipAddressCmd`/`GetStatusCmd(GetAddressReg)` walk
+/// the chain one chip at a time, the same way a real chain's command passthrough only lets the
+/// first not-yet-addressed chip answer a broadcast.
+pub struct Bm1387Simulator {
+    chips: Vec<ChipState>,
+    xtal_freq: usize,
+}
+
+impl Bm1387Simulator {
+    pub fn new(chip_count: usize, xtal_freq: usize) -> Self {
+        Self {
+            chips: (0..chip_count).map(|_| ChipState::default()).collect(),
+            xtal_freq,
+        }
+    }
+
+    /// Feeds one packed command - as produced by e.g. `SetConfigCmd::pack`/`GetStatusCmd::pack` -
+    /// to the chain. Returns the response bytes a `GetStatusCmd` would read back; every other
+    /// command returns an empty response, same as real hardware only ever replies to a status
+    /// request.
+    pub fn process_command(&mut self, bytes: &[u8]) -> error::Result<Vec<u8>> {
+        if bytes.len() < 3 {
+            Err(ErrorKind::General(format!(
+                "command is only {} bytes, too short for a header",
+                bytes.len()
+            )))?
+        }
+        // `Cmd`'s bit layout: bits 0:3 = code, bit 4 = to_all, bits 5:7 = cmd_type
+        let code = bytes[0] & 0x0f;
+        let to_all = bytes[0] & 0x10 != 0;
+        let hw_addr = bytes[2];
+
+        match code {
+            opcode::SET_CHIP_ADDRESS => {
+                self.assign_next_address(hw_addr);
+                Ok(Vec::new())
+            }
+            opcode::INACTIVATE_FROM_CHAIN => {
+                for chip in &mut self.chips {
+                    chip.addr = None;
+                }
+                Ok(Vec::new())
+            }
+            opcode::SET_CONFIG => {
+                if bytes.len() < 8 {
+                    Err(ErrorKind::General(format!(
+                        "SetConfigCmd is only {} bytes, expected at least 8",
+                        bytes.len()
+                    )))?
+                }
+                let register = bytes[3];
+                let value = u32::from_be_bytes(bytes[4..8].try_into().unwrap());
+                self.set_config(to_all, hw_addr, register, value);
+                Ok(Vec::new())
+            }
+            opcode::GET_STATUS => {
+                if bytes.len() < 4 {
+                    Err(ErrorKind::General(format!(
+                        "GetStatusCmd is only {} bytes, expected at least 4",
+                        bytes.len()
+                    )))?
+                }
+                self.get_status(to_all, hw_addr, bytes[3])
+            }
+            other => Err(ErrorKind::General(format!(
+                "simulator doesn't recognize command opcode {:#04x}",
+                other
+            )))?,
+        }
+    }
+
+    /// Models the daisy-chain address walk: the first not-yet-addressed chip in the chain claims
+    /// whatever address `SetChipAddressCmd` carried, same as real hardware where every
+    /// already-addressed chip passes the command on instead of reacting to it
+    fn assign_next_address(&mut self, hw_addr: u8) {
+        if let Some(chip) = self.chips.iter_mut().find(|chip| chip.addr.is_none()) {
+            chip.addr = Some(hw_addr);
+        }
+    }
+
+    fn set_config(&mut self, to_all: bool, hw_addr: u8, register: u8, value: u32) {
+        for chip in self
+            .chips
+            .iter_mut()
+            .filter(|chip| to_all || chip.addr == Some(hw_addr))
+        {
+            match register {
+                MiscCtrlReg::REG_NUM => chip.misc_ctrl = MiscCtrlReg::from_reg(value),
+                TicketMaskReg::REG_NUM => chip.ticket_mask = TicketMaskReg::from_reg(value),
+                PllReg::REG_NUM => chip.pll = PllReg::from_reg(value),
+                _ => {}
+            }
+        }
+    }
+
+    fn get_status(&self, to_all: bool, hw_addr: u8, register: u8) -> error::Result<Vec<u8>> {
+        // enumeration probes GetAddressReg as a broadcast - only the first not-yet-addressed chip
+        // in the chain is meant to answer, same as `assign_next_address`
+        let chip = if to_all && register == GetAddressReg::REG_NUM {
+            self.chips.iter().find(|chip| chip.addr.is_none())
+        } else {
+            self.chips.iter().find(|chip| chip.addr == Some(hw_addr))
+        }
+        .ok_or_else(|| {
+            ErrorKind::General(format!("no chip responds at address {:#04x}", hw_addr))
+        })?;
+
+        let value = match register {
+            GetAddressReg::REG_NUM => GetAddressReg {
+                chip_rev: CHIP_REV_BM1387,
+                _reserved1: 0,
+                addr: chip.addr.unwrap_or(0),
+            }
+            .to_reg(),
+            MiscCtrlReg::REG_NUM => chip.misc_ctrl.to_reg(),
+            TicketMaskReg::REG_NUM => chip.ticket_mask.to_reg(),
+            PllReg::REG_NUM => chip.pll.to_reg(),
+            _ => 0,
+        };
+        let response = CmdResponse {
+            value,
+            chip_address_or_zero: 0,
+            register_number_or_zero: 0,
+        };
+        Ok(response.pack().to_vec())
+    }
+
+    /// Advances every addressed chip's simulated nonce search by `elapsed` and returns every
+    /// `(chip_index, nonce)` solution that "arrived" in that span - at a rate consistent with the
+    /// chip's currently programmed PLL frequency and `TicketMaskReg` difficulty, the same way real
+    /// hashrate and solution frequency both scale with those two settings
+    pub fn step(&mut self, elapsed: Duration) -> Vec<(usize, u32)> {
+        let xtal_freq = self.xtal_freq;
+        let num_cores = Bm1387Params.num_cores_on_chip() as u32;
+        let mut solutions = Vec::new();
+
+        for (chip_idx, chip) in self.chips.iter_mut().enumerate() {
+            if chip.addr.is_none() {
+                continue;
+            }
+            let frequency = PllFrequency::new(chip.pll.clone(), xtal_freq).frequency;
+            if frequency == 0 {
+                continue;
+            }
+            let difficulty = chip.ticket_mask.difficulty() as u64;
+
+            chip.nonce_counter += frequency as u64 * elapsed.as_micros() as u64 / 1_000_000;
+            while chip.nonce_counter >= difficulty {
+                chip.nonce_counter -= difficulty;
+                let core = (chip.solutions_found % num_cores as u64) as u32;
+                chip.solutions_found += 1;
+                let nonce = ((chip_idx as u32) << 2) | (core << 24);
+                solutions.push((chip_idx, nonce));
+            }
+        }
+        solutions
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bm1387::{
+        ChipAddress, CoreAddress, GetStatusCmd, InactivateFromChainCmd, SetChipAddressCmd,
+        SetConfigCmd,
+    };
+
+    const DEFAULT_XTAL_FREQ: usize = 25_000_000;
+
+    #[test]
+    fn test_enumeration_assigns_addresses_in_order() {
+        let mut sim = Bm1387Simulator::new(2, DEFAULT_XTAL_FREQ);
+
+        // probing GetAddressReg as a broadcast finds the first unaddressed chip
+        let probe = GetStatusCmd::new(ChipAddress::All, GetAddressReg::REG_NUM).pack();
+        let response = sim.process_command(&probe).expect("probe failed");
+        let reg = GetAddressReg::unpack_from_slice(&response[0..4]).expect("unpack failed");
+        assert_eq!(reg.addr, 0);
+
+        sim.process_command(&SetChipAddressCmd::new(ChipAddress::One(0)).pack())
+            .expect("assign failed");
+
+        // the first chip no longer answers the broadcast probe - the second one does
+        let response = sim.process_command(&probe).expect("probe failed");
+        let reg = GetAddressReg::unpack_from_slice(&response[0..4]).expect("unpack failed");
+        assert_eq!(reg.addr, 0);
+
+        sim.process_command(&SetChipAddressCmd::new(ChipAddress::One(1)).pack())
+            .expect("assign failed");
+
+        // every chip now has an address, so the broadcast probe goes unanswered
+        assert!(sim.process_command(&probe).is_err());
+    }
+
+    #[test]
+    fn test_inactivate_resets_addressing() {
+        let mut sim = Bm1387Simulator::new(1, DEFAULT_XTAL_FREQ);
+        sim.process_command(&SetChipAddressCmd::new(ChipAddress::One(0)).pack())
+            .expect("assign failed");
+
+        sim.process_command(&InactivateFromChainCmd::new().pack())
+            .expect("inactivate failed");
+
+        let probe = GetStatusCmd::new(ChipAddress::All, GetAddressReg::REG_NUM).pack();
+        let response = sim.process_command(&probe).expect("probe failed");
+        let reg = GetAddressReg::unpack_from_slice(&response[0..4]).expect("unpack failed");
+        assert_eq!(reg.addr, 0);
+    }
+
+    #[test]
+    fn test_set_config_and_get_status_round_trip_misc_ctrl() {
+        let mut sim = Bm1387Simulator::new(1, DEFAULT_XTAL_FREQ);
+        sim.process_command(&SetChipAddressCmd::new(ChipAddress::One(0)).pack())
+            .expect("assign failed");
+
+        let reg = MiscCtrlReg::new(false, true, 10, false, true).expect("invalid divisor");
+        sim.process_command(&SetConfigCmd::new(
+            ChipAddress::One(0),
+            MiscCtrlReg::REG_NUM,
+            reg.to_reg(),
+        )
+        .pack())
+        .expect("set_config failed");
+
+        let response = sim
+            .process_command(
+                &GetStatusCmd::new(ChipAddress::One(0), MiscCtrlReg::REG_NUM).pack(),
+            )
+            .expect("get_status failed");
+        let value = u32::from_be_bytes(response[0..4].try_into().unwrap());
+        assert_eq!(MiscCtrlReg::from_reg(value), reg);
+    }
+
+    #[test]
+    fn test_step_emits_solutions_consistent_with_pll_and_difficulty() {
+        let mut sim = Bm1387Simulator::new(1, DEFAULT_XTAL_FREQ);
+        sim.process_command(&SetChipAddressCmd::new(ChipAddress::One(0)).pack())
+            .expect("assign failed");
+
+        let pll = PllReg::solve(500_000_000, DEFAULT_XTAL_FREQ, &Bm1387Params).expect("solve failed");
+        sim.process_command(
+            &SetConfigCmd::new(ChipAddress::One(0), PllReg::REG_NUM, pll.to_reg()).pack(),
+        )
+        .expect("set_config failed");
+        let difficulty = 1 << 20;
+        sim.process_command(&SetConfigCmd::new(
+            ChipAddress::One(0),
+            TicketMaskReg::REG_NUM,
+            TicketMaskReg::new(difficulty)
+                .expect("invalid difficulty")
+                .to_reg(),
+        )
+        .pack())
+        .expect("set_config failed");
+
+        let solutions = sim.step(Duration::from_secs(1));
+        let expected = 500_000_000 / difficulty as u64;
+        // simulated nonce attempts per second equal the PLL frequency, so this many solutions
+        // should arrive under a difficulty of `difficulty`
+        assert_eq!(solutions.len() as u64, expected);
+        for (chip_idx, nonce) in solutions {
+            assert_eq!(chip_idx, 0);
+            assert_eq!(CoreAddress::new(nonce, &Bm1387Params).chip, 0);
+        }
+    }
+}
@@ -0,0 +1,402 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Command/response capture and replay, see `TracingContext`. Wrapping a `CommandContext` in a
+//! `TracingContext` records every `set_config`/`get_status` call - packed command bytes, packed
+//! response bytes and when it happened - into a `TraceLog` that can be written to flash, dumped
+//! as an annotated human-readable listing (`TraceLog::dump`) or fed straight into
+//! `simulator::Bm1387Simulator` for a regression test (`replay`). This is a pcap-style capture of
+//! the chip bus, meant to be switched on only when a field engineer is diagnosing an
+//! initialization failure - it costs an allocation per command, so it's not left on by default.
+
+use super::i2c::CommandContext;
+use super::simulator::{opcode, Bm1387Simulator};
+use super::{
+    ChipAddress, CmdResponse, GetAddressReg, GetStatusCmd, MiscCtrlReg, PllReg, Register,
+    SetConfigCmd, TicketMaskReg,
+};
+use crate::error::{self, ErrorKind};
+
+use packed_struct::prelude::*;
+
+use std::convert::TryInto;
+use std::time::{Duration, Instant};
+
+/// Version byte for `TraceLog`'s on-disk encoding - bumped whenever the record layout below
+/// changes, so a capture written by older firmware is rejected instead of silently misread
+const TRACE_LOG_VERSION: u8 = 1;
+
+/// One recorded `CommandContext` call: the packed command bytes exactly as they'd go out over the
+/// wire, the packed response bytes (empty for `set_config`, which has no response payload
+/// observable through `CommandContext`), and when it happened relative to when the `TracingContext`
+/// was created
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEvent {
+    pub elapsed: Duration,
+    pub command: Vec<u8>,
+    pub response: Vec<u8>,
+}
+
+impl TraceEvent {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + 1 + self.command.len() + 1 + self.response.len());
+        bytes.extend_from_slice(&(self.elapsed.as_nanos() as u64).to_be_bytes());
+        bytes.push(self.command.len() as u8);
+        bytes.extend_from_slice(&self.command);
+        bytes.push(self.response.len() as u8);
+        bytes.extend_from_slice(&self.response);
+        bytes
+    }
+
+    /// Parses one event off the front of `bytes`, returning it together with whatever's left
+    fn read_from(bytes: &[u8]) -> error::Result<(Self, &[u8])> {
+        if bytes.len() < 9 {
+            Err(ErrorKind::General(format!(
+                "trace is truncated: only {} bytes left, expected at least 9",
+                bytes.len()
+            )))?
+        }
+        let elapsed = Duration::from_nanos(u64::from_be_bytes(bytes[0..8].try_into().unwrap()));
+        let command_len = bytes[8] as usize;
+        let rest = &bytes[9..];
+        if rest.len() < command_len + 1 {
+            Err(ErrorKind::General(format!(
+                "trace is truncated: command cut short",
+            )))?
+        }
+        let command = rest[..command_len].to_vec();
+        let rest = &rest[command_len..];
+
+        let response_len = rest[0] as usize;
+        let rest = &rest[1..];
+        if rest.len() < response_len {
+            Err(ErrorKind::General(format!(
+                "trace is truncated: response cut short",
+            )))?
+        }
+        let response = rest[..response_len].to_vec();
+        let rest = &rest[response_len..];
+
+        Ok((
+            Self {
+                elapsed,
+                command,
+                response,
+            },
+            rest,
+        ))
+    }
+}
+
+/// A captured command/response session, in the order the commands were issued
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TraceLog {
+    pub events: Vec<TraceEvent>,
+}
+
+impl TraceLog {
+    /// Encodes this trace as `[version][event]...[event]`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![TRACE_LOG_VERSION];
+        for event in &self.events {
+            bytes.extend_from_slice(&event.to_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a blob written by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> error::Result<Self> {
+        let (&version, mut rest) = bytes
+            .split_first()
+            .ok_or_else(|| ErrorKind::General(format!("trace blob is empty")))?;
+        if version != TRACE_LOG_VERSION {
+            Err(ErrorKind::General(format!(
+                "trace version {} is not supported, expected {}",
+                version, TRACE_LOG_VERSION
+            )))?
+        }
+
+        let mut events = Vec::new();
+        while !rest.is_empty() {
+            let (event, remainder) = TraceEvent::read_from(rest)?;
+            events.push(event);
+            rest = remainder;
+        }
+        Ok(Self { events })
+    }
+
+    /// Renders every event as one annotated line - opcode, target `ChipAddress`, decoded register
+    /// and (for a known register) its decoded fields - by reparsing the captured bytes back
+    /// through the same `unpack_from_slice`/`from_reg` paths real commands are built with
+    pub fn dump(&self) -> String {
+        self.events
+            .iter()
+            .map(|event| {
+                let millis = event.elapsed.as_secs_f64() * 1000.0;
+                format!("{:>12.3}ms  {}", millis, describe_event(event))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Recovers the `ChipAddress` a command targeted from its raw header bytes - the inverse of
+/// `ChipAddress::to_hw_addr`/`Cmd::new`'s `to_all` bit
+fn decode_chip_address(to_all: bool, hw_addr: u8) -> ChipAddress {
+    if to_all {
+        ChipAddress::All
+    } else {
+        ChipAddress::One(hw_addr as usize / 4)
+    }
+}
+
+/// Looks a register number up against the ones this module knows about and formats its value
+/// accordingly, falling back to plain hex for anything else (e.g. `I2cControlReg`, or a register
+/// from a generation this tree hasn't added yet)
+fn describe_register_value(register: u8, value: u32) -> String {
+    match register {
+        GetAddressReg::REG_NUM => format!("{:?}", GetAddressReg::from_reg(value)),
+        TicketMaskReg::REG_NUM => format!("{:?}", TicketMaskReg::from_reg(value)),
+        MiscCtrlReg::REG_NUM => format!("{:?}", MiscCtrlReg::from_reg(value)),
+        PllReg::REG_NUM => format!("{:?}", PllReg::from_reg(value)),
+        _ => format!("0x{:08x}", value),
+    }
+}
+
+/// One annotated line for `TraceLog::dump`
+fn describe_event(event: &TraceEvent) -> String {
+    if event.command.len() < 3 {
+        return format!("<malformed command, {} bytes>", event.command.len());
+    }
+    // `Cmd`'s bit layout: bits 0:3 = code, bit 4 = to_all, bits 5:7 = cmd_type
+    let code = event.command[0] & 0x0f;
+    let to_all = event.command[0] & 0x10 != 0;
+    let chip_address = decode_chip_address(to_all, event.command[2]);
+
+    match code {
+        opcode::SET_CHIP_ADDRESS => "SetChipAddressCmd".to_string(),
+        opcode::INACTIVATE_FROM_CHAIN => "InactivateFromChainCmd".to_string(),
+        opcode::SET_CONFIG => match SetConfigCmd::unpack_from_slice(&event.command) {
+            Ok(cmd) => format!(
+                "SetConfigCmd {{ chip_address: {:?}, register: 0x{:02x}, value: {} }}",
+                chip_address,
+                cmd.register,
+                describe_register_value(cmd.register, cmd.value)
+            ),
+            Err(e) => format!("<malformed SetConfigCmd: {}>", e),
+        },
+        opcode::GET_STATUS => match GetStatusCmd::unpack_from_slice(&event.command) {
+            Ok(cmd) => {
+                let response = if event.response.len() == CmdResponse::packed_bytes() {
+                    match CmdResponse::unpack_from_slice(&event.response) {
+                        Ok(response) => describe_register_value(cmd.register, response.value),
+                        Err(e) => format!("<malformed response: {}>", e),
+                    }
+                } else {
+                    "<no response captured>".to_string()
+                };
+                format!(
+                    "GetStatusCmd {{ chip_address: {:?}, register: 0x{:02x} }} -> {}",
+                    chip_address, cmd.register, response
+                )
+            }
+            Err(e) => format!("<malformed GetStatusCmd: {}>", e),
+        },
+        _ => format!("<unrecognized opcode 0x{:02x}>", code),
+    }
+}
+
+/// Feeds every captured command back through `sim` in order, returning each response - lets a
+/// trace captured against real hardware double as a regression test input for `Bm1387Simulator`
+pub fn replay(log: &TraceLog, sim: &mut Bm1387Simulator) -> error::Result<Vec<Vec<u8>>> {
+    log.events
+        .iter()
+        .map(|event| sim.process_command(&event.command))
+        .collect()
+}
+
+/// Wraps any `CommandContext` and records every call into a `TraceLog`, while still delegating to
+/// `inner` so tracing can be layered onto a live chain with no behavior change - only enable it
+/// when a field engineer needs a capture to diagnose an initialization failure
+pub struct TracingContext<C> {
+    inner: C,
+    start: Instant,
+    log: TraceLog,
+}
+
+impl<C> TracingContext<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            start: Instant::now(),
+            log: TraceLog::default(),
+        }
+    }
+
+    /// Hands back the capture recorded so far, e.g. to persist it via `TraceLog::to_bytes`
+    pub fn log(&self) -> &TraceLog {
+        &self.log
+    }
+
+    /// Unwraps back to the inner `CommandContext`, discarding whatever was captured
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: CommandContext> CommandContext for TracingContext<C> {
+    fn set_config(
+        &mut self,
+        chip_address: ChipAddress,
+        register: u8,
+        value: u32,
+    ) -> error::Result<()> {
+        let command = SetConfigCmd::new(chip_address, register, value)
+            .pack()
+            .to_vec();
+        let elapsed = self.start.elapsed();
+        let result = self.inner.set_config(chip_address, register, value);
+        self.log.events.push(TraceEvent {
+            elapsed,
+            command,
+            response: Vec::new(),
+        });
+        result
+    }
+
+    fn get_status(&mut self, chip_address: ChipAddress, register: u8) -> error::Result<u32> {
+        let command = GetStatusCmd::new(chip_address, register).pack().to_vec();
+        let elapsed = self.start.elapsed();
+        let result = self.inner.get_status(chip_address, register);
+        // the trailing chip-address/register-number bytes newer generations carry aren't
+        // observable through `CommandContext::get_status`'s `u32` return, so they're recorded as
+        // zero - same as `ChipParams::response_address` does for BM1387
+        let response = match &result {
+            Ok(value) => CmdResponse {
+                value: *value,
+                chip_address_or_zero: 0,
+                register_number_or_zero: 0,
+            }
+            .pack()
+            .to_vec(),
+            Err(_) => Vec::new(),
+        };
+        self.log.events.push(TraceEvent {
+            elapsed,
+            command,
+            response,
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A `CommandContext` that just echoes back whatever was last written to a register, so tests
+    /// can drive `TracingContext` without a real chain
+    #[derive(Default)]
+    struct FakeContext {
+        last_value: u32,
+    }
+
+    impl CommandContext for FakeContext {
+        fn set_config(
+            &mut self,
+            _chip_address: ChipAddress,
+            _register: u8,
+            value: u32,
+        ) -> error::Result<()> {
+            self.last_value = value;
+            Ok(())
+        }
+
+        fn get_status(&mut self, _chip_address: ChipAddress, _register: u8) -> error::Result<u32> {
+            Ok(self.last_value)
+        }
+    }
+
+    #[test]
+    fn test_tracing_context_records_both_calls_and_still_delegates() {
+        let mut ctx = TracingContext::new(FakeContext::default());
+        ctx.set_config(ChipAddress::One(3), PllReg::REG_NUM, 0x00680221)
+            .expect("set_config failed");
+        let value = ctx
+            .get_status(ChipAddress::One(3), PllReg::REG_NUM)
+            .expect("get_status failed");
+        assert_eq!(value, 0x00680221);
+        assert_eq!(ctx.log().events.len(), 2);
+        assert!(ctx.log().events[0].response.is_empty());
+        assert_eq!(ctx.log().events[1].response.len(), CmdResponse::packed_bytes());
+    }
+
+    #[test]
+    fn test_trace_log_round_trips_through_bytes() {
+        let mut ctx = TracingContext::new(FakeContext::default());
+        ctx.set_config(ChipAddress::All, MiscCtrlReg::REG_NUM, 0x12345678)
+            .expect("set_config failed");
+        ctx.get_status(ChipAddress::One(5), MiscCtrlReg::REG_NUM)
+            .expect("get_status failed");
+
+        let bytes = ctx.log().to_bytes();
+        let decoded = TraceLog::from_bytes(&bytes).expect("decode failed");
+        assert_eq!(&decoded, ctx.log());
+    }
+
+    #[test]
+    fn test_trace_log_rejects_unsupported_version() {
+        let mut bytes = TraceLog::default().to_bytes();
+        bytes[0] = TRACE_LOG_VERSION + 1;
+        assert!(TraceLog::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_dump_annotates_opcode_chip_address_and_register() {
+        let mut ctx = TracingContext::new(FakeContext::default());
+        ctx.set_config(ChipAddress::One(2), PllReg::REG_NUM, 0x00680221)
+            .expect("set_config failed");
+
+        let dump = ctx.log().dump();
+        assert!(dump.contains("SetConfigCmd"));
+        assert!(dump.contains("One(2)"));
+        assert!(dump.contains("PllReg"));
+    }
+
+    #[test]
+    fn test_replay_feeds_captured_commands_into_simulator() {
+        let mut ctx = TracingContext::new(FakeContext::default());
+        ctx.set_config(ChipAddress::One(0), PllReg::REG_NUM, 0x00680221)
+            .expect("set_config failed");
+        ctx.get_status(ChipAddress::One(0), PllReg::REG_NUM)
+            .expect("get_status failed");
+
+        // the simulator errors on a GetStatusCmd no chip answers, so give it one chip addressed as
+        // chip 0 first - this only exercises that a captured trace replays without an error, not
+        // the simulator's own chain-enumeration behavior (see `simulator::test` for that)
+        let mut sim = Bm1387Simulator::new(1, 25_000_000);
+        sim.process_command(&crate::bm1387::SetChipAddressCmd::new(ChipAddress::One(0)).pack())
+            .expect("assign failed");
+        let responses = replay(ctx.log(), &mut sim).expect("replay failed");
+        assert_eq!(responses.len(), 2);
+    }
+}
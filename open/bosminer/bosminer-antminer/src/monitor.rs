@@ -30,6 +30,8 @@ use crate::halt;
 
 use ii_sensors::{self as sensor, Measurement};
 
+use serde::Serialize;
+
 use std::fmt;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -80,32 +82,138 @@ pub enum ChainTemperature {
     Ok(f32),
 }
 
-impl ChainTemperature {
-    /// Convert temperature to monitor interpretation.
-    /// Specific to S9, because it fakes chip temperature.
-    ///
-    /// TODO: Maybe figure out a strage for disabling remote sensors that are failing. Sometimes
-    /// remote sensors fail while mining and instead of signalizing error they return non-sensical
-    /// numbers.
-    /// TODO: Is returning "Unknown" when sensor fails OK?
-    fn from_s9_sensor(temp: sensor::Temperature) -> Self {
+/// Per-model interpretation of raw sensor readings into a `ChainTemperature`.
+///
+/// Different hardware has different thermal topology (e.g. where the remote sensor sits
+/// relative to the chips, or whether a remote sensor exists at all), so this is factored out
+/// of the core decision logic and supplied per-chain instead of being hardcoded in `Monitor`.
+pub trait SensorInterpreter: fmt::Debug + Send + Sync {
+    fn interpret(&self, temp: &sensor::Temperature) -> ChainTemperature;
+}
+
+/// S9-specific interpretation: remote is the chip sensor and takes priority; when it's missing,
+/// fake the chip temperature from the local (PCB) sensor instead of reporting `Unknown`.
+///
+/// TODO: Maybe figure out a strategy for disabling remote sensors that are failing. Sometimes
+/// remote sensors fail while mining and instead of signalizing error they return non-sensical
+/// numbers.
+#[derive(Debug, Clone, Copy)]
+pub struct S9SensorInterpreter;
+
+impl SensorInterpreter for S9SensorInterpreter {
+    fn interpret(&self, temp: &sensor::Temperature) -> ChainTemperature {
         match temp.remote {
             // remote is chip temperature
             Measurement::Ok(t_remote) => match temp.local {
-                Measurement::Ok(t_local) => Self::Ok(t_remote.max(t_local)),
-                _ => Self::Ok(t_remote),
+                Measurement::Ok(t_local) => ChainTemperature::Ok(t_remote.max(t_local)),
+                _ => ChainTemperature::Ok(t_remote),
             },
             _ => {
                 // fake chip temperature from local (PCB) temperature
                 match temp.local {
-                    Measurement::Ok(t_local) => Self::Ok(t_local + 15.0),
-                    _ => Self::Unknown,
+                    Measurement::Ok(t_local) => ChainTemperature::Ok(t_local + 15.0),
+                    _ => ChainTemperature::Unknown,
                 }
             }
         }
     }
 }
 
+/// Uses the remote chip sensor verbatim - a missing/failing remote reading is reported as
+/// `Unknown` instead of being faked from the local sensor.
+#[derive(Debug, Clone, Copy)]
+pub struct DirectSensorInterpreter;
+
+impl SensorInterpreter for DirectSensorInterpreter {
+    fn interpret(&self, temp: &sensor::Temperature) -> ChainTemperature {
+        match temp.remote {
+            Measurement::Ok(t_remote) => ChainTemperature::Ok(t_remote),
+            _ => ChainTemperature::Unknown,
+        }
+    }
+}
+
+/// Actuation/feedback backend for fan control, factored out (in the same spirit as
+/// `SensorInterpreter`) so the full `do_tick`/`tick_task` control loop can be exercised in
+/// integration tests or on a dev workstation without real fan hardware.
+pub trait FanBackend: fmt::Debug + Send + Sync {
+    /// Command the fans to spin at `speed`
+    fn set_speed(&mut self, speed: fan::Speed);
+    /// Read back the fans' current tachometer feedback
+    fn read_feedback(&self) -> fan::Feedback;
+}
+
+impl FanBackend for fan::Control {
+    fn set_speed(&mut self, speed: fan::Speed) {
+        self.set_speed(speed);
+    }
+
+    fn read_feedback(&self) -> fan::Feedback {
+        self.read_feedback()
+    }
+}
+
+/// Dev/test `FanBackend` that models fan RPM ramp-up and a simple thermal plant (temperature
+/// rises with `load`, falls with airflow) instead of talking to real hardware. This lets the
+/// unit tests around `do_tick`/`tick_task` graduate into end-to-end tests that drive ticks
+/// through simulated time and assert the PID actually stabilizes `input_temperature`.
+#[derive(Debug, Clone)]
+pub struct SimulatedFan {
+    commanded: fan::Speed,
+    /// Current simulated RPM, ramping towards whatever `commanded` implies
+    rpm: f32,
+    /// Maximum RPM at `FULL_SPEED`, used to derive the ramp target and airflow
+    max_rpm: f32,
+    /// How many RPM the fan gains/loses per second towards its target
+    ramp_rate: f32,
+    /// Simulated board temperature
+    temp: f32,
+    /// Ambient temperature the board cools towards when idle
+    ambient_temp: f32,
+}
+
+impl SimulatedFan {
+    pub fn new(max_rpm: f32, ramp_rate: f32, ambient_temp: f32) -> Self {
+        Self {
+            commanded: fan::Speed::STOPPED,
+            rpm: 0.0,
+            max_rpm,
+            ramp_rate,
+            temp: ambient_temp,
+            ambient_temp,
+        }
+    }
+
+    /// Current simulated board temperature
+    pub fn temperature(&self) -> f32 {
+        self.temp
+    }
+
+    /// Advance the thermal plant by `dt`: `load` (`0.0..=1.0`) heats the board, while airflow
+    /// from the current (ramping) RPM cools it back towards `ambient_temp`.
+    pub fn step(&mut self, dt: Duration, load: f32) {
+        let dt = dt.as_secs_f32();
+
+        let target_rpm = self.max_rpm * (self.commanded.as_percent() as f32 / 100.0);
+        let max_step = self.ramp_rate * dt;
+        self.rpm += (target_rpm - self.rpm).max(-max_step).min(max_step);
+
+        let heating = load * 5.0;
+        let cooling = (self.rpm / self.max_rpm) * (self.temp - self.ambient_temp).max(0.0) * 0.5;
+        self.temp += (heating - cooling) * dt;
+    }
+}
+
+impl FanBackend for SimulatedFan {
+    fn set_speed(&mut self, speed: fan::Speed) {
+        self.commanded = speed;
+    }
+
+    fn read_feedback(&self) -> fan::Feedback {
+        fan::Feedback::new(vec![Some(self.rpm.max(0.0) as u32)])
+    }
+}
+
 impl fmt::Display for ChainTemperature {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -187,14 +295,12 @@ impl ChainState {
     /// Return hashchain temperature as seen from our point of view. For example,
     /// `Broken` miner doesn't have a valid temperature reading even though it sent
     /// some numbers a while ago.
-    fn get_temperature(&self) -> ChainTemperature {
+    fn get_temperature(&self, sensor_interpreter: &dyn SensorInterpreter) -> ChainTemperature {
         match self {
             ChainState::On(_) => ChainTemperature::Unknown,
             ChainState::Off => ChainTemperature::Unknown,
             ChainState::Broken(_) => ChainTemperature::Failed,
-            ChainState::Running { temperature, .. } => {
-                ChainTemperature::from_s9_sensor(temperature.clone())
-            }
+            ChainState::Running { temperature, .. } => sensor_interpreter.interpret(temperature),
         }
     }
 
@@ -207,6 +313,27 @@ impl ChainState {
             _ => false,
         }
     }
+
+    /// Coarse state used for `ThermalMetrics::time_in_state` - collapses away the data each
+    /// variant carries (timestamps, the last reading) since only the amount of time spent in
+    /// each state kind matters for metrics.
+    fn kind(&self) -> ChainStateKind {
+        match self {
+            ChainState::Off => ChainStateKind::Off,
+            ChainState::On(_) => ChainStateKind::On,
+            ChainState::Running { .. } => ChainStateKind::Running,
+            ChainState::Broken(_) => ChainStateKind::Broken,
+        }
+    }
+}
+
+/// See `ChainState::kind()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChainStateKind {
+    Off,
+    On,
+    Running,
+    Broken,
 }
 
 impl fmt::Display for ChainState {
@@ -229,13 +356,51 @@ impl fmt::Display for ChainState {
 struct Chain {
     state: ChainState,
     hashboard_idx: usize,
+    /// How to turn this chain's raw sensor readings into a `ChainTemperature` - differs between
+    /// hardware models with different thermal topology
+    sensor_interpreter: Arc<dyn SensorInterpreter>,
+    /// Smooths this chain's own readings, so a single noisy I2C transaction on one board can't
+    /// trip `dangerous_temp` or jitter the PID output for the whole miner
+    filter: TemperatureFilter,
+    /// Rate-limits and time-bounds this chain's raw reading, so the decision tree's freshness
+    /// guarantees don't implicitly depend on however often `recv_task` happens to be fed
+    sensor_cache: SensorCache,
 }
 
 impl Chain {
-    fn new(hashboard_idx: usize) -> Self {
+    fn new(
+        hashboard_idx: usize,
+        sensor_interpreter: Arc<dyn SensorInterpreter>,
+        filter_time_constant: Duration,
+        min_poll_interval: Duration,
+        max_sample_age: Duration,
+    ) -> Self {
         Self {
             state: ChainState::Off,
             hashboard_idx,
+            sensor_interpreter,
+            filter: TemperatureFilter::new(filter_time_constant),
+            sensor_cache: SensorCache::new(min_poll_interval, max_sample_age),
+        }
+    }
+
+    /// Raw (unfiltered) temperature - used for the dangerous-temperature shutdown path and for
+    /// diagnostics. Goes through `sensor_cache`, so a reading can be rate-limited or, once too
+    /// old, degraded to `Unknown` - see its doc comment.
+    fn get_temperature(&mut self, now: Instant) -> ChainTemperature {
+        let temp = self.state.get_temperature(self.sensor_interpreter.as_ref());
+        self.sensor_cache.update(now, temp)
+    }
+
+    /// Low-pass filtered temperature - used as PID/decision-tree input. Faults bypass and reset
+    /// the filter so a genuine sensor failure isn't smoothed away into a stale-but-"Ok" reading.
+    fn get_filtered_temperature(&mut self, now: Instant) -> ChainTemperature {
+        match self.get_temperature(now) {
+            ChainTemperature::Ok(t) => ChainTemperature::Ok(self.filter.update(now, t)),
+            other => {
+                self.filter.reset();
+                other
+            }
         }
     }
 }
@@ -245,6 +410,28 @@ impl Chain {
 pub enum FanControlMode {
     FixedSpeed(fan::Speed),
     TargetTemperature(f32),
+    /// Map temperature directly to PWM duty via a quadratic curve `k_a*temp^2 + k_b*temp + k_c`,
+    /// clamped to `[MIN_SPEED, FULL_SPEED]`. A deterministic alternative to the PID loop that
+    /// can't overshoot on transients.
+    Curve {
+        k_a: f32,
+        k_b: f32,
+        k_c: f32,
+    },
+    /// Closed-loop PID directly on user-configured `kp`/`ki`/`kd` gains, as opposed to
+    /// `TargetTemperature`, which drives the fixed-gain controller `fan::pid::TempControl`
+    /// backs it with. See `PidController`.
+    Pid {
+        target_temp: f32,
+        kp: f32,
+        ki: f32,
+        kd: f32,
+        /// Sampling interval used for the integral/derivative terms - normally `TICK_LENGTH`,
+        /// but configurable so gains tuned against a different loop rate still behave the same.
+        sample_period: Duration,
+        min_speed: fan::Speed,
+        max_speed: fan::Speed,
+    },
 }
 
 /// Fan configuration
@@ -254,6 +441,35 @@ pub struct FanControlConfig {
     /// Minimal number of fans - miner will refuse to work until at least
     /// this number of fans is spinning.
     pub min_fans: usize,
+    /// Tachometer reading below which a commanded fan counts as `LowSignal` rather than `Ok`
+    pub low_signal_rpm: u32,
+}
+
+/// Per-fan health, derived from the commanded speed and the tachometer reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FanStatus {
+    /// Fan spins within expectations (or is correctly commanded off)
+    Ok,
+    /// No tachometer wire/reading for this fan slot
+    NotAvailable,
+    /// Fan is commanded to spin but reports zero RPM
+    Stalled,
+    /// Fan is commanded to spin but reports RPM below `low_signal_rpm`
+    LowSignal,
+}
+
+impl FanStatus {
+    /// Derive the status of a single fan from its tachometer reading and whether it is
+    /// currently commanded to spin.
+    fn from_rpm(rpm: Option<u32>, commanded_on: bool, low_signal_rpm: u32) -> Self {
+        match rpm {
+            None => Self::NotAvailable,
+            Some(_) if !commanded_on => Self::Ok,
+            Some(0) => Self::Stalled,
+            Some(rpm) if rpm < low_signal_rpm => Self::LowSignal,
+            Some(_) => Self::Ok,
+        }
+    }
 }
 
 /// Temperature limit configuration
@@ -261,17 +477,63 @@ pub struct FanControlConfig {
 pub struct TempControlConfig {
     pub dangerous_temp: f32,
     pub hot_temp: f32,
+    /// Setpoint for the thermal-load PI controller - see `ThermalLoadController`
+    pub throttle_temp: f32,
+    /// Proportional gain of the thermal-load controller
+    pub kp: f32,
+    /// Integral gain of the thermal-load controller
+    pub ki: f32,
+    /// Clamp on the accumulated integral term, to bound windup
+    pub integral_max: f32,
+    /// How long `thermal_load` may stay saturated at the minimum hashrate before we give up on
+    /// throttling and fall back to `Shutdown`
+    pub shutdown_grace: Duration,
 }
 
-/// Overall configuration
-/// "Disabled" is represented as `None`
+/// An independent fan group: its own `FanControlConfig`/`TempControlConfig`, driven by the
+/// subset of chains listed in `hashboard_indices`.
+///
+/// Before zones existed, every chain collapsed into one `TemperatureAccumulator` and one fan
+/// decision, so a single hot board spun every fan in the miner to full even when other boards
+/// had their own dedicated cooling. Splitting `Config` into zones lets a thermally balanced
+/// miner run each fan group off just the chains it actually cools.
 #[derive(Debug, Clone)]
-pub struct Config {
+pub struct ZoneConfig {
+    /// Which chains (by `hashboard_idx`) feed this zone's `TemperatureAccumulator`
+    pub hashboard_indices: Vec<usize>,
     pub fan_config: Option<FanControlConfig>,
     pub temp_config: Option<TempControlConfig>,
+}
+
+/// Overall configuration
+/// "Disabled" is represented as `None` within each zone's `fan_config`/`temp_config`
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Independent fan groups - see `ZoneConfig`. The number of zones is fixed for the lifetime
+    /// of a `Monitor` (each needs its own `FanBackend`, registered in `Monitor::new_and_start`);
+    /// `with_configuration` can change a zone's settings but not add or remove zones.
+    pub zones: Vec<ZoneConfig>,
     /// If true, then do not let fans bellow predefined limit while miner is warming up.
     /// TODO: this is not particularly nice, it should be done per-chain and run-time.
     pub fans_on_while_warming_up: bool,
+    /// Time constant of each chain's `TemperatureFilter` - see its doc comment
+    pub temp_filter_time_constant: Duration,
+    /// Minimum time between readings each chain's `SensorCache` will accept as fresh - see its
+    /// doc comment
+    pub min_poll_interval: Duration,
+    /// How long each chain's `SensorCache` will keep serving a reading before degrading the
+    /// contribution to `ChainTemperature::Unknown` - see its doc comment
+    pub max_sample_age: Duration,
+}
+
+impl Config {
+    /// Locate the zone that `hashboard_idx` has been assigned to, if any - a chain not listed in
+    /// any zone's `hashboard_indices` is left without fan/temp control.
+    fn zone_idx_for_chain(&self, hashboard_idx: usize) -> Option<usize> {
+        self.zones
+            .iter()
+            .position(|zone| zone.hashboard_indices.contains(&hashboard_idx))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -287,12 +549,173 @@ pub enum ControlDecision {
     Shutdown,
     /// Pass these parameters to PID and let it calculate fan speed
     UsePid { target_temp: f32, input_temp: f32 },
+    /// Pass these parameters to a `PidController` (`FanControlMode::Pid`'s directly
+    /// user-configured Kp/Ki/Kd gains, as opposed to `UsePid`'s fixed-gain controller)
+    UseConfigurablePid {
+        target_temp: f32,
+        input_temp: f32,
+        kp: f32,
+        ki: f32,
+        kd: f32,
+        sample_period: Duration,
+        min_speed: fan::Speed,
+        max_speed: fan::Speed,
+    },
     /// Use fixed speed
     UseFixedSpeed(fan::Speed),
+    /// Back off hashrate by `load` to shed heat instead of shutting down outright - fans are
+    /// assumed to already be running full speed at this point
+    Throttle { load: ThermalLoad },
     /// Do nothing (only valid when fan control is disabled)
     Nothing,
 }
 
+/// How much the hashchain should back off its hashrate to shed heat, `0.0` meaning full
+/// hashrate and `1.0` meaning the minimum before `Shutdown` takes over.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct ThermalLoad(f32);
+
+impl ThermalLoad {
+    /// Below this load, throttling is released (and above it, begun) - see
+    /// `ThermalLoadController`'s hysteresis handling for why a single threshold plus sticky
+    /// state is enough here.
+    const HYSTERESIS_THRESHOLD: f32 = 0.1;
+
+    fn new(value: f32) -> Self {
+        Self(value.max(0.0).min(1.0))
+    }
+
+    pub fn as_f32(&self) -> f32 {
+        self.0
+    }
+
+    /// `true` once the hashchain has been backed off to the minimum and still isn't enough
+    fn is_saturated(&self) -> bool {
+        self.0 >= 1.0
+    }
+}
+
+/// Closed-loop thermal-load controller, modeled on Fuchsia's power manager: a PI controller on
+/// `e = filtered_temp - throttle_temp` that backs off hashrate gradually instead of jumping
+/// straight from full power to `Shutdown`. Its integral term and saturation timer are carried
+/// across ticks, so it lives alongside the rest of the monitor's per-tick state in
+/// `MonitorInner` rather than inside the (otherwise pure) `decide()`.
+#[derive(Debug, Clone)]
+struct ThermalLoadController {
+    integral: f32,
+    /// How long `thermal_load` has been continuously saturated at `1.0`
+    saturated_for: Duration,
+    /// Sticky throttling state - see `HYSTERESIS_THRESHOLD`
+    throttling: bool,
+}
+
+impl ThermalLoadController {
+    fn new() -> Self {
+        Self {
+            integral: 0.0,
+            saturated_for: Duration::from_secs(0),
+            throttling: false,
+        }
+    }
+
+    /// Advance the controller by one tick of length `dt` for the given filtered `temp` and
+    /// return the resulting thermal load, updating the sticky throttling/saturation state.
+    fn update(&mut self, temp: f32, dt: Duration, temp_config: &TempControlConfig) -> ThermalLoad {
+        let e = temp - temp_config.throttle_temp;
+        if e > 0.0 {
+            self.integral = (self.integral + e * dt.as_secs_f32()).min(temp_config.integral_max);
+        }
+        let load = ThermalLoad::new(temp_config.kp * e + temp_config.ki * self.integral);
+
+        self.saturated_for = if load.is_saturated() {
+            self.saturated_for + dt
+        } else {
+            Duration::from_secs(0)
+        };
+
+        self.throttling = if self.throttling {
+            load.as_f32() >= ThermalLoad::HYSTERESIS_THRESHOLD
+        } else {
+            load.as_f32() > ThermalLoad::HYSTERESIS_THRESHOLD
+        };
+
+        load
+    }
+
+    /// Minimum hashrate hasn't brought the board back under control for longer than the
+    /// configured grace period - time to give up and cut power entirely.
+    fn shutdown_due(&self, temp_config: &TempControlConfig) -> bool {
+        self.saturated_for >= temp_config.shutdown_grace
+    }
+}
+
+/// Closed-loop PID fan controller driven directly by user-configured `kp`/`ki`/`kd` gains
+/// (`FanControlMode::Pid`), as opposed to the fixed-gain controller `fan::pid::TempControl`
+/// backs `FanControlMode::TargetTemperature` with.
+///
+/// `integral` and `previous_error` carry across samples, the same way `ThermalLoadController`
+/// carries its own integral - a fresh `PidController` (as `ZoneState::new` creates) always starts
+/// with both zeroed, so a miner restart never inherits stale windup. Anti-windup clamps
+/// `integral` so `ki * integral` alone can never push the output past `[min_speed, max_speed]`,
+/// and freezes integration while the unclamped output is already saturated.
+#[derive(Debug, Clone)]
+struct PidController {
+    integral: f32,
+    previous_error: Option<f32>,
+}
+
+impl PidController {
+    fn new() -> Self {
+        Self {
+            integral: 0.0,
+            previous_error: None,
+        }
+    }
+
+    /// Advance the controller by one sample of length `sample_period` and return the resulting
+    /// fan speed, clamped to `[min_speed, max_speed]`.
+    ///
+    /// `e = measured_temp - target_temp`, so positive error (too hot) drives more cooling.
+    #[allow(clippy::too_many_arguments)]
+    fn update(
+        &mut self,
+        measured_temp: f32,
+        target_temp: f32,
+        kp: f32,
+        ki: f32,
+        kd: f32,
+        sample_period: Duration,
+        min_speed: fan::Speed,
+        max_speed: fan::Speed,
+    ) -> fan::Speed {
+        let dt = sample_period.as_secs_f32();
+        let min_speed = min_speed.as_percent() as f32;
+        let max_speed = max_speed.as_percent() as f32;
+
+        let e = measured_temp - target_temp;
+        let d = self
+            .previous_error
+            .map(|previous_e| (e - previous_e) / dt)
+            .unwrap_or(0.0);
+        self.previous_error = Some(e);
+
+        // Anti-windup: integrate only while last tick's output wasn't already saturated, and
+        // clamp the integral itself so `ki * integral` alone can never exceed the output range.
+        let unclamped = kp * e + ki * self.integral + kd * d;
+        if ki != 0.0 && unclamped > min_speed && unclamped < max_speed {
+            let (integral_min, integral_max) = if ki > 0.0 {
+                (min_speed / ki, max_speed / ki)
+            } else {
+                (max_speed / ki, min_speed / ki)
+            };
+            self.integral = (self.integral + e * dt).max(integral_min).min(integral_max);
+        }
+
+        let output = kp * e + ki * self.integral + kd * d;
+        fan::Speed::new(output.max(min_speed).min(max_speed).round() as u8)
+    }
+}
+
 impl ControlDecision {
     /// Decision rules if both fan control and temp control are enabled
     fn decide_fan_control(
@@ -336,6 +759,56 @@ impl ControlDecision {
                     };
                 }
             },
+            FanControlMode::Curve { k_a, k_b, k_c } => match temp {
+                ChainTemperature::Failed | ChainTemperature::Unknown => {
+                    panic!("BUG: should've been caught earlier at the top of `decide()` function")
+                }
+                ChainTemperature::Ok(input_temp) => {
+                    let duty = k_a * input_temp * input_temp + k_b * input_temp + k_c;
+                    let pwm = fan::Speed::new(duty.round().max(0.0) as u8);
+                    return ControlDecisionExplained {
+                        decision: Self::UseFixedSpeed(pwm),
+                        reason: format!("Curve fan control: input {} -> duty {:.0}%", temp, duty),
+                    };
+                }
+            },
+            FanControlMode::Pid {
+                target_temp,
+                kp,
+                ki,
+                kd,
+                sample_period,
+                min_speed,
+                max_speed,
+            } => match temp {
+                ChainTemperature::Failed | ChainTemperature::Unknown => {
+                    panic!("BUG: should've been caught earlier at the top of `decide()` function")
+                }
+                ChainTemperature::Ok(input_temp) => {
+                    if input_temp >= temp_config.hot_temp {
+                        return ControlDecisionExplained {
+                            decision: Self::UseFixedSpeed(fan::Speed::FULL_SPEED),
+                            reason: format!("Fans full speed: temperature {} above HOT", temp),
+                        };
+                    }
+                    return ControlDecisionExplained {
+                        decision: Self::UseConfigurablePid {
+                            target_temp: *target_temp,
+                            input_temp,
+                            kp: *kp,
+                            ki: *ki,
+                            kd: *kd,
+                            sample_period: *sample_period,
+                            min_speed: *min_speed,
+                            max_speed: *max_speed,
+                        },
+                        reason: format!(
+                            "PID fan control: input {} target {:.0}°C",
+                            temp, target_temp
+                        ),
+                    };
+                }
+            },
         }
     }
 
@@ -348,7 +821,9 @@ impl ControlDecision {
                     reason: format!("Fans to {} (user defined)", pwm),
                 };
             }
-            FanControlMode::TargetTemperature(_) => {
+            FanControlMode::TargetTemperature(_)
+            | FanControlMode::Curve { .. }
+            | FanControlMode::Pid { .. } => {
                 // I don't know how to avoid this variant using type system alone
                 // Let's make it non-fatal
                 return ControlDecisionExplained {
@@ -361,15 +836,23 @@ impl ControlDecision {
 
     /// Decide what to do depending on temperature/fan feedback.
     /// This function has been factored out of the main control code to facilitate testing.
+    ///
+    /// Operates on a single zone's configuration/accumulated readings - `do_tick` calls this
+    /// once per `ZoneConfig`.
     fn decide(
-        config: &Config,
+        zone_config: &ZoneConfig,
         num_fans_running: usize,
+        raw_temp: ChainTemperature,
         temp: ChainTemperature,
+        dt: Duration,
+        thermal_load: &mut ThermalLoadController,
     ) -> ControlDecisionExplained {
         // This section is labeled `TEMP_DANGER` in the diagram
-        // Check for dangerous temperature or dead sensors
-        if let Some(temp_config) = config.temp_config.as_ref() {
-            match temp {
+        // Check for dangerous temperature or dead sensors.
+        // Deliberately uses `raw_temp` (not the PID-smoothed `temp`): a filtered reading could
+        // mask a genuine thermal runaway for the duration of the filter's time constant.
+        if let Some(temp_config) = zone_config.temp_config.as_ref() {
+            match raw_temp {
                 ChainTemperature::Failed => {
                     return ControlDecisionExplained {
                         decision: Self::Shutdown,
@@ -380,16 +863,42 @@ impl ControlDecision {
                     if input_temp >= temp_config.dangerous_temp {
                         return ControlDecisionExplained {
                             decision: Self::Shutdown,
-                            reason: format!("Shutdown: temperature {} above DANGEROUS", temp),
+                            reason: format!("Shutdown: temperature {} above DANGEROUS", raw_temp),
                         };
                     }
                 }
                 ChainTemperature::Unknown => {}
             }
+
+            // Graduated thermal throttling: back off hashrate to shed heat instead of jumping
+            // straight to `Shutdown` once we're hot. Only falls back to `Shutdown` once the
+            // controller has been pinned at minimum hashrate for longer than `shutdown_grace`.
+            if let ChainTemperature::Ok(filtered_temp) = temp {
+                let load = thermal_load.update(filtered_temp, dt, temp_config);
+                if thermal_load.shutdown_due(temp_config) {
+                    return ControlDecisionExplained {
+                        decision: Self::Shutdown,
+                        reason: format!(
+                            "Shutdown: thermal load saturated for over {:?}",
+                            temp_config.shutdown_grace
+                        ),
+                    };
+                }
+                if thermal_load.throttling {
+                    return ControlDecisionExplained {
+                        decision: Self::Throttle { load },
+                        reason: format!(
+                            "Throttling: load {:.0}% (input {})",
+                            load.as_f32() * 100.0,
+                            temp
+                        ),
+                    };
+                }
+            }
         }
         // Check the health of fans and decide their speed
-        if let Some(fan_config) = config.fan_config.as_ref() {
-            let decision_explained = if let Some(temp_config) = config.temp_config.as_ref() {
+        if let Some(fan_config) = zone_config.fan_config.as_ref() {
+            let decision_explained = if let Some(temp_config) = zone_config.temp_config.as_ref() {
                 Self::decide_fan_control(fan_config, temp_config, temp)
             } else {
                 Self::decide_fan_control_notemp(fan_config)
@@ -424,6 +933,121 @@ impl ControlDecision {
     }
 }
 
+/// Exponential low-pass filter that smooths a chain's raw sensor reading before it is used as
+/// PID/decision-tree input. This accounts for the variable interval between readings so that
+/// the same `tau` behaves consistently regardless of jitter in the tick loop.
+///
+/// The filter is intentionally *not* applied to the dangerous-temperature shutdown path: a
+/// smoothed reading could mask a genuine thermal runaway, so that path always looks at the raw
+/// reading instead.
+#[derive(Debug, Clone)]
+struct TemperatureFilter {
+    /// Time constant of the exponential filter
+    tau: Duration,
+    /// Last filtered value together with the time it was computed, `None` before the first
+    /// sample (or right after a reset)
+    state: Option<(f32, Instant)>,
+}
+
+impl TemperatureFilter {
+    /// Default time constant - long enough to damp tick-to-tick sensor noise, short enough to
+    /// still track genuine thermal trends within about a minute.
+    const DEFAULT_TAU: Duration = Duration::from_secs(15);
+
+    /// Reset the filter once it has been quiet for this many time constants - beyond that point
+    /// the stored value is so stale that smoothing through it would just add lag (e.g. across a
+    /// hashchain restart).
+    const RESET_AFTER_TAU_MULTIPLE: u32 = 4;
+
+    fn new(tau: Duration) -> Self {
+        Self { tau, state: None }
+    }
+
+    /// Feed a new raw aggregate reading `y` (sampled at `now`) through the filter and return the
+    /// smoothed value.
+    fn update(&mut self, now: Instant, y: f32) -> f32 {
+        let filtered = match self.state {
+            Some((filtered, last_update))
+                if now.saturating_duration_since(last_update)
+                    < self.tau * Self::RESET_AFTER_TAU_MULTIPLE =>
+            {
+                let dt = now.saturating_duration_since(last_update).as_secs_f32();
+                let alpha = 1.0 - (-dt / self.tau.as_secs_f32()).exp();
+                filtered + alpha * (y - filtered)
+            }
+            // No previous sample, or the gap since it was taken is too large - seed the filter
+            // with the fresh reading instead of smoothing through the gap.
+            _ => y,
+        };
+        self.state = Some((filtered, now));
+        filtered
+    }
+
+    /// Drop any filter state - called whenever the upstream reading becomes meaningless
+    /// (`Unknown`/`Failed`) so the filter doesn't lag once real readings resume.
+    fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+/// Caches a chain's last accepted raw reading, decoupling the decision tree's freshness
+/// guarantees from however often `recv_task` happens to receive a `Message::Running` - modeled
+/// on Fuchsia's `TemperatureHandler` reading cache.
+///
+/// - A reading arriving sooner than `min_poll_interval` after the last accepted one is dropped
+///   on the floor and the previous value keeps being served instead, so a chain pushing updates
+///   faster than the bus can safely sustain can't be fed straight into the accumulator.
+/// - A cached value older than `max_sample_age` degrades the contribution to
+///   `ChainTemperature::Unknown` instead of being served indefinitely.
+/// - `Unknown`/`Failed` readings are never cached and always propagate immediately - a fault is
+///   more urgent than any rate limit or staleness concern, and caching it would only delay
+///   noticing a real problem.
+#[derive(Debug, Clone)]
+struct SensorCache {
+    min_poll_interval: Duration,
+    max_sample_age: Duration,
+    /// Last accepted reading together with when it was accepted
+    cached: Option<(f32, Instant)>,
+}
+
+impl SensorCache {
+    fn new(min_poll_interval: Duration, max_sample_age: Duration) -> Self {
+        Self {
+            min_poll_interval,
+            max_sample_age,
+            cached: None,
+        }
+    }
+
+    /// Feed a freshly observed `reading` (sampled at `now`) through the cache and return the
+    /// value to actually use this tick.
+    fn update(&mut self, now: Instant, reading: ChainTemperature) -> ChainTemperature {
+        let t = match reading {
+            ChainTemperature::Ok(t) => t,
+            // Faults are never cached and always propagate immediately
+            other => {
+                self.cached = None;
+                return other;
+            }
+        };
+
+        let rate_limited = matches!(
+            self.cached,
+            Some((_, last)) if now.saturating_duration_since(last) < self.min_poll_interval
+        );
+        if !rate_limited {
+            self.cached = Some((t, now));
+        }
+
+        match self.cached {
+            Some((t, last)) if now.saturating_duration_since(last) < self.max_sample_age => {
+                ChainTemperature::Ok(t)
+            }
+            _ => ChainTemperature::Unknown,
+        }
+    }
+}
+
 /// This structure abstracts the process of "making one aggregate temperature out of
 /// all hashchain temperatures".
 /// The resulting temperature is used as an input variable for PID control.
@@ -467,15 +1091,227 @@ impl TemperatureAccumulator {
     }
 }
 
-/// Status of `Monitor` for others to observe
+/// Width of each regular bucket in a `TemperatureHistogram`, in degrees Celsius
+const TEMP_HISTOGRAM_BUCKET_WIDTH: f32 = 5.0;
+/// Lower edge of the first regular bucket - readings below this accumulate in `underflow`
+const TEMP_HISTOGRAM_MIN: f32 = 0.0;
+/// Number of regular buckets, covering `TEMP_HISTOGRAM_MIN` up to 200 °C, comfortably above any
+/// realistic `dangerous_temp` - readings at or above that fall into `overflow` instead
+const TEMP_HISTOGRAM_BUCKET_COUNT: usize = 40;
+
+/// Linear histogram of filtered temperature readings, weighted by tick duration rather than
+/// sample count (in the spirit of Fuchsia/Cobalt's linear histograms), so an operator can chart
+/// e.g. "how many hours did this board spend in the 85-90°C bucket last week" regardless of how
+/// often `do_tick` happened to run.
+#[derive(Debug, Clone, Serialize)]
+pub struct TemperatureHistogram {
+    /// Seconds spent with a reading below `TEMP_HISTOGRAM_MIN`
+    pub underflow: f32,
+    /// Seconds spent with a reading in each `TEMP_HISTOGRAM_BUCKET_WIDTH`-wide bucket, starting
+    /// at `TEMP_HISTOGRAM_MIN`
+    pub buckets: Vec<f32>,
+    /// Seconds spent with a reading at or above the last bucket's upper edge
+    pub overflow: f32,
+}
+
+impl TemperatureHistogram {
+    fn new() -> Self {
+        Self {
+            underflow: 0.0,
+            buckets: vec![0.0; TEMP_HISTOGRAM_BUCKET_COUNT],
+            overflow: 0.0,
+        }
+    }
+
+    /// Accumulate `dt` worth of dwell time at temperature `temp`.
+    fn record(&mut self, temp: f32, dt: Duration) {
+        let dt = dt.as_secs_f32();
+        if temp < TEMP_HISTOGRAM_MIN {
+            self.underflow += dt;
+            return;
+        }
+        let idx = ((temp - TEMP_HISTOGRAM_MIN) / TEMP_HISTOGRAM_BUCKET_WIDTH) as usize;
+        match self.buckets.get_mut(idx) {
+            Some(bucket) => *bucket += dt,
+            None => self.overflow += dt,
+        }
+    }
+}
+
+/// Total time a chain has spent in each `ChainStateKind`, accumulated one tick at a time.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TimeInState {
+    pub off: Duration,
+    pub on: Duration,
+    pub running: Duration,
+    pub broken: Duration,
+}
+
+impl TimeInState {
+    fn add(&mut self, state: ChainStateKind, dt: Duration) {
+        let bucket = match state {
+            ChainStateKind::Off => &mut self.off,
+            ChainStateKind::On => &mut self.on,
+            ChainStateKind::Running => &mut self.running,
+            ChainStateKind::Broken => &mut self.broken,
+        };
+        *bucket += dt;
+    }
+}
+
+/// Per-chain thermal history, indexed the same way as `Chain::hashboard_idx` - see
+/// `ThermalMetrics`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainMetrics {
+    /// Histogram of this chain's filtered (PID-input) temperature
+    pub temperature_histogram: TemperatureHistogram,
+    pub time_in_state: TimeInState,
+    /// Total time this chain's raw temperature was at or above `TempControlConfig::hot_temp`
+    pub time_above_hot: Duration,
+    /// Total time this chain's raw temperature was at or above
+    /// `TempControlConfig::dangerous_temp`
+    pub time_above_dangerous: Duration,
+}
+
+impl ChainMetrics {
+    fn new() -> Self {
+        Self {
+            temperature_histogram: TemperatureHistogram::new(),
+            time_in_state: TimeInState::default(),
+            time_above_hot: Duration::from_secs(0),
+            time_above_dangerous: Duration::from_secs(0),
+        }
+    }
+
+    /// Fold one tick's worth of observations for this chain into the accumulated metrics.
+    fn record_tick(
+        &mut self,
+        state: ChainStateKind,
+        raw_temp: ChainTemperature,
+        filtered_temp: ChainTemperature,
+        dt: Duration,
+        temp_config: Option<&TempControlConfig>,
+    ) {
+        self.time_in_state.add(state, dt);
+        if let ChainTemperature::Ok(t) = filtered_temp {
+            self.temperature_histogram.record(t, dt);
+        }
+        if let (ChainTemperature::Ok(t), Some(temp_config)) = (raw_temp, temp_config) {
+            if t >= temp_config.hot_temp {
+                self.time_above_hot += dt;
+            }
+            if t >= temp_config.dangerous_temp {
+                self.time_above_dangerous += dt;
+            }
+        }
+    }
+}
+
+/// Thermal history collected once per tick in `do_tick`, answering questions like "how long did
+/// board 2 spend above `hot_temp` last week" or "how often do chains go `Broken`" that a bare
+/// instantaneous `Status` can't. See `Monitor::metrics_snapshot()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThermalMetrics {
+    /// Number of times the monitor has decided `ControlDecision::Shutdown`
+    pub shutdown_count: u64,
+    /// Indexed the same way as `Chain::hashboard_idx`
+    pub chains: Vec<ChainMetrics>,
+}
+
+impl ThermalMetrics {
+    fn new() -> Self {
+        Self {
+            shutdown_count: 0,
+            chains: Vec::new(),
+        }
+    }
+
+    /// Metrics for `hashboard_idx`, growing `chains` on first use - chains register themselves
+    /// with `Monitor` one at a time, so the vector can't just be pre-sized up front.
+    fn chain_mut(&mut self, hashboard_idx: usize) -> &mut ChainMetrics {
+        if hashboard_idx >= self.chains.len() {
+            self.chains
+                .resize_with(hashboard_idx + 1, ChainMetrics::new);
+        }
+        &mut self.chains[hashboard_idx]
+    }
+
+    /// Cheap-to-clone summary folded into `Status` every tick - see `ThermalMetricsSummary`.
+    fn summary(&self) -> ThermalMetricsSummary {
+        ThermalMetricsSummary {
+            shutdown_count: self.shutdown_count,
+            chain_broken_time: self
+                .chains
+                .iter()
+                .map(|chain| chain.time_in_state.broken)
+                .collect(),
+        }
+    }
+}
+
+/// Small subset of `ThermalMetrics` folded into `Status` on every tick, so a consumer already
+/// watching `status_receiver` gets the headline numbers for free; the full histograms are only
+/// available from `Monitor::metrics_snapshot()`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ThermalMetricsSummary {
+    pub shutdown_count: u64,
+    /// Indexed the same way as `Chain::hashboard_idx`
+    pub chain_broken_time: Vec<Duration>,
+}
+
+/// Per-zone snapshot of `Monitor`'s state, one of which is reported in `Status::zones` for each
+/// configured `ZoneConfig`.
 #[derive(Debug, Clone)]
-pub struct Status {
-    pub config: Config,
+pub struct ZoneStatus {
+    /// Index into `Config::zones`
+    pub zone_idx: usize,
     pub fan_feedback: fan::Feedback,
     pub fan_speed: Option<fan::Speed>,
     pub input_temperature: ChainTemperature,
     pub temperature_accumulator: TemperatureAccumulator,
     pub decision_explained: ControlDecisionExplained,
+    pub fan_status: Vec<FanStatus>,
+}
+
+/// Status of `Monitor` for others to observe
+#[derive(Debug, Clone)]
+pub struct Status {
+    pub config: Config,
+    /// One entry per `Config::zones`, in the same order
+    pub zones: Vec<ZoneStatus>,
+    pub thermal_metrics: ThermalMetricsSummary,
+}
+
+/// Per-zone runtime state - the parts of `Monitor` that used to be singletons before zones
+/// existed and now need one instance per `ZoneConfig` (see `Config::zones`).
+struct ZoneState {
+    /// Fan controller driving just this zone's fans - can set RPM or read feedback. A trait
+    /// object so tests/dev workstations can swap in `SimulatedFan` instead of the real hardware
+    /// backend.
+    fan_control: Box<dyn FanBackend>,
+    /// Last fan speed that was set
+    current_fan_speed: Option<fan::Speed>,
+    /// PID that controls this zone's fans with its accumulated chain temperature as input
+    pid: fan::pid::TempControl,
+    /// Persistent state for this zone's `FanControlMode::Pid` controller, see `PidController`
+    configurable_pid: PidController,
+    /// Per-fan status as of the last tick, used to only log on transitions
+    last_fan_status: Vec<FanStatus>,
+    /// Persistent state for this zone's thermal-load PI controller, see `ThermalLoadController`
+    thermal_load: ThermalLoadController,
+}
+
+impl ZoneState {
+    fn new(fan_control: Box<dyn FanBackend>) -> Self {
+        Self {
+            fan_control,
+            current_fan_speed: None,
+            pid: fan::pid::TempControl::new(),
+            configurable_pid: PidController::new(),
+            last_fan_status: Vec::new(),
+            thermal_load: ThermalLoadController::new(),
+        }
+    }
 }
 
 /// Monitor - it holds states of all Chains and everything related to fan control
@@ -484,15 +1320,13 @@ pub struct MonitorInner {
     chains: Vec<Arc<Mutex<Chain>>>,
     /// temp/fan control configuration
     config: Config,
-    /// Fan controller - can set RPM or read feedback
-    fan_control: fan::Control,
-    /// Last fan speed that was set
-    current_fan_speed: Option<fan::Speed>,
-    /// PID that controls fan with hashchain temperature as input
-    pid: fan::pid::TempControl,
+    /// Runtime state of each zone, indexed the same way as `config.zones`
+    zones: Vec<ZoneState>,
     /// Flag whether miner is in failure state - temperature critical, hashboards not responding,
     /// fans gone missing...
     failure_state: bool,
+    /// Accumulated thermal history, see `ThermalMetrics`
+    metrics: ThermalMetrics,
 }
 
 /// Wrapper around `MonitorInner` with immutable fields
@@ -501,6 +1335,12 @@ pub struct Monitor {
     status_sender: watch::Sender<Option<Status>>,
     pub status_receiver: watch::Receiver<Option<Status>>,
 
+    /// Broadcast channel for the current thermal throttling load of each zone (indexed the same
+    /// way as `Config::zones`) - the hashchain controller subscribes to this to back off hashrate
+    /// before the monitor has to resort to `Shutdown`
+    thermal_load_sender: watch::Sender<Vec<ThermalLoad>>,
+    pub thermal_load_receiver: watch::Receiver<Vec<ThermalLoad>>,
+
     /// Context to shutdown when miner enters critical state
     miner_shutdown: Arc<halt::Sender>,
 
@@ -511,28 +1351,40 @@ pub struct Monitor {
 impl Monitor {
     /// Construct a new monitor and start it
     ///
+    /// * `fan_controls` - one backend per `config.zones` entry (same order), used to
+    ///   actuate/read back that zone's fans; pass a real hardware `fan::Control` in production or
+    ///   a `SimulatedFan` in tests
     /// * `miner_shutdown` - halt sender to shutdown the whole miner in case of a failure
     /// * `halt_receiver` - termination context in which to start the monitor
     pub async fn new_and_start(
         config: Config,
+        fan_controls: Vec<Box<dyn FanBackend>>,
         miner_shutdown: Arc<halt::Sender>,
         halt_receiver: halt::Receiver,
     ) -> Arc<Self> {
+        assert_eq!(
+            fan_controls.len(),
+            config.zones.len(),
+            "Monitor: need exactly one FanBackend per configured zone"
+        );
         let (status_sender, status_receiver) = watch::channel(None);
+        let (thermal_load_sender, thermal_load_receiver) =
+            watch::channel(vec![ThermalLoad::new(0.0); config.zones.len()]);
 
         let inner = MonitorInner {
             chains: Vec::new(),
+            zones: fan_controls.into_iter().map(ZoneState::new).collect(),
             config,
-            fan_control: fan::Control::new().expect("failed initializing fan controller"),
-            pid: fan::pid::TempControl::new(),
             failure_state: false,
-            current_fan_speed: None,
+            metrics: ThermalMetrics::new(),
         };
 
         let monitor = Arc::new(Monitor {
             miner_shutdown,
             status_sender,
             status_receiver,
+            thermal_load_sender,
+            thermal_load_receiver,
             inner: Mutex::new(inner),
         });
 
@@ -554,10 +1406,13 @@ impl Monitor {
     async fn termination_handler(self: Arc<Self>) {
         let mut inner = self.inner.lock().await;
         // Decide whether to leave fans on (depending on whether we are in failure state or not)
-        if inner.failure_state {
-            self.set_fan_speed(&mut inner, fan::Speed::FULL_SPEED);
+        let fan_speed = if inner.failure_state {
+            fan::Speed::FULL_SPEED
         } else {
-            self.set_fan_speed(&mut inner, fan::Speed::STOPPED);
+            fan::Speed::STOPPED
+        };
+        for zone in inner.zones.iter_mut() {
+            Self::set_fan_speed(zone, fan_speed);
         }
     }
 
@@ -565,16 +1420,17 @@ impl Monitor {
     async fn shutdown(&self, mut inner: MutexGuard<'_, MonitorInner>, reason: String) {
         error!("Monitor task declared miner shutdown: {}", reason);
         inner.failure_state = true;
+        inner.metrics.shutdown_count += 1;
         // Shutdown handler locks `inner`, so drop the guard here to prevent deadlock
         drop(inner);
         self.miner_shutdown.clone().send_halt().await;
     }
 
-    /// Set fan speed
-    fn set_fan_speed(&self, inner: &mut MonitorInner, fan_speed: fan::Speed) {
+    /// Set fan speed for a single zone
+    fn set_fan_speed(zone: &mut ZoneState, fan_speed: fan::Speed) {
         trace!("Monitor: setting fan to {:?}", fan_speed);
-        inner.fan_control.set_speed(fan_speed);
-        inner.current_fan_speed = Some(fan_speed);
+        zone.fan_control.set_speed(fan_speed);
+        zone.current_fan_speed = Some(fan_speed);
     }
 
     /// One tick of temperature/fan controller
@@ -582,85 +1438,239 @@ impl Monitor {
     /// TODO: Run this tick every time new temperature is submitted to lower temp controller
     ///   latency.
     async fn do_tick(&self) {
-        // decide hashchain state and collect temperatures
+        // decide hashchain state and collect temperatures, grouped by the zone each chain
+        // belongs to (see `Config::zone_idx_for_chain`)
         let mut inner = self.inner.lock().await;
-        let mut temperature_accumulator = TemperatureAccumulator::new();
-        let mut miner_warming_up = false;
-        let mut chain_info_status = vec![];
+        let num_zones = inner.zones.len();
+        let mut raw_accumulators: Vec<_> = (0..num_zones)
+            .map(|_| TemperatureAccumulator::new())
+            .collect();
+        let mut filtered_accumulators: Vec<_> = (0..num_zones)
+            .map(|_| TemperatureAccumulator::new())
+            .collect();
+        let mut zone_warming_up = vec![false; num_zones];
+        let mut zone_chain_info: Vec<Vec<String>> = vec![Vec::new(); num_zones];
+        // Recorded here and folded into `inner.metrics` after the loop, once the per-chain
+        // `MutexGuard`s (and the borrow of `inner.chains` they're iterated from) are gone.
+        let mut chain_ticks = vec![];
+        let mut broken_chain = None;
         for chain in inner.chains.iter() {
             let mut chain = chain.lock().await;
             chain.state.tick(Instant::now());
 
             if let ChainState::Broken(reason) = chain.state {
                 // TODO: here comes "Shutdown"
-                let reason = format!("Chain {} is broken: {}", chain.hashboard_idx, reason);
-                // drop `chain` here to drop iterator which holds immutable reference
-                // to `monitor`
-                drop(chain);
-                self.shutdown(inner, reason).await;
-                return;
+                broken_chain = Some(format!(
+                    "Chain {} is broken: {}",
+                    chain.hashboard_idx, reason
+                ));
+                chain_ticks.push((
+                    chain.hashboard_idx,
+                    ChainStateKind::Broken,
+                    ChainTemperature::Failed,
+                    ChainTemperature::Failed,
+                ));
+                break;
             }
             trace!("Monitor: chain {}: {:?}", chain.hashboard_idx, chain.state);
-            chain_info_status.push(chain.state.to_string());
-            temperature_accumulator.add_chain_temp(chain.state.get_temperature());
-            miner_warming_up |= chain.state.is_warming_up(Instant::now());
-        }
-        let input_temperature = temperature_accumulator.calc_result();
-
-        // Read fans
-        let fan_feedback = inner.fan_control.read_feedback();
-        let num_fans_running = fan_feedback.num_fans_running();
-        trace!(
-            "Monitor: fan={:?} num_fans={} acc.temp.={:?}",
-            fan_feedback,
-            num_fans_running,
-            input_temperature,
-        );
-        // all right, temperature has been aggregated, decide what to do
-        let decision_explained =
-            ControlDecision::decide(&inner.config, num_fans_running, input_temperature);
-        trace!("Monitor: {:?}", decision_explained);
-        let status_line = format!(
-            "{} | {} | {}",
-            decision_explained.reason,
-            chain_info_status.join(" "),
-            fan_feedback.to_string(),
-        );
-        match decision_explained.decision {
-            ControlDecision::Shutdown => {
-                info!("Monitor: {}", status_line);
-                self.shutdown(inner, decision_explained.reason.into()).await;
-                return;
+            let raw_temp = chain.get_temperature(Instant::now());
+            let filtered_temp = chain.get_filtered_temperature(Instant::now());
+            if let Some(zone_idx) = inner.config.zone_idx_for_chain(chain.hashboard_idx) {
+                raw_accumulators[zone_idx].add_chain_temp(raw_temp);
+                filtered_accumulators[zone_idx].add_chain_temp(filtered_temp);
+                zone_warming_up[zone_idx] |= chain.state.is_warming_up(Instant::now());
+                zone_chain_info[zone_idx].push(chain.state.to_string());
             }
-            ControlDecision::UseFixedSpeed(fan_speed) => {
-                info!("Monitor: {} fan_{}", status_line, fan_speed);
-                self.set_fan_speed(&mut inner, fan_speed);
+            chain_ticks.push((
+                chain.hashboard_idx,
+                chain.state.kind(),
+                raw_temp,
+                filtered_temp,
+            ));
+        }
+        for (hashboard_idx, state, raw_temp, filtered_temp) in chain_ticks {
+            let temp_config = inner
+                .config
+                .zone_idx_for_chain(hashboard_idx)
+                .and_then(|zone_idx| inner.config.zones[zone_idx].temp_config.clone());
+            inner.metrics.chain_mut(hashboard_idx).record_tick(
+                state,
+                raw_temp,
+                filtered_temp,
+                TICK_LENGTH,
+                temp_config.as_ref(),
+            );
+        }
+        if let Some(reason) = broken_chain {
+            self.shutdown(inner, reason).await;
+            return;
+        }
+
+        // Run the decide/PID logic once per zone, against that zone's own accumulated readings
+        // and its own fan backend. Shutdown semantics stay global-safe: if any zone decides to
+        // shut down, the whole miner stops, but normal fan/PID control is otherwise independent
+        // per zone.
+        let mut zone_statuses = Vec::with_capacity(num_zones);
+        let mut zone_loads = Vec::with_capacity(num_zones);
+        let mut shutdown_reason = None;
+        for zone_idx in 0..num_zones {
+            let zone_config = inner.config.zones[zone_idx].clone();
+            let raw_temp = raw_accumulators[zone_idx].calc_result();
+            let filtered_temp = filtered_accumulators[zone_idx].calc_result();
+
+            let fan_feedback = inner.zones[zone_idx].fan_control.read_feedback();
+            let num_fans_running = fan_feedback.num_fans_running();
+            trace!(
+                "Monitor: zone {} fan={:?} num_fans={} acc.temp.={:?} filtered={:?}",
+                zone_idx,
+                fan_feedback,
+                num_fans_running,
+                raw_temp,
+                filtered_temp,
+            );
+
+            // Diagnose individual fans from their tachometer reading and log only on
+            // transitions, so a permanently dead fan doesn't flood the journal every tick.
+            let low_signal_rpm = zone_config
+                .fan_config
+                .as_ref()
+                .map(|fan_config| fan_config.low_signal_rpm)
+                .unwrap_or(0);
+            let commanded_on = matches!(
+                inner.zones[zone_idx].current_fan_speed,
+                Some(speed) if speed != fan::Speed::STOPPED
+            );
+            let fan_status: Vec<FanStatus> = fan_feedback
+                .rpms()
+                .iter()
+                .map(|&rpm| FanStatus::from_rpm(rpm, commanded_on, low_signal_rpm))
+                .collect();
+            for (i, &status) in fan_status.iter().enumerate() {
+                let last_status = inner.zones[zone_idx]
+                    .last_fan_status
+                    .get(i)
+                    .copied()
+                    .unwrap_or(FanStatus::Ok);
+                if status != last_status {
+                    warn!(
+                        "Monitor: zone {} fan {} status changed: {:?} -> {:?}",
+                        zone_idx, i, last_status, status
+                    );
+                }
             }
-            ControlDecision::UsePid {
-                target_temp,
-                input_temp,
-            } => {
-                if inner.config.fans_on_while_warming_up && miner_warming_up {
-                    inner.pid.set_warm_up_limits();
-                } else {
-                    inner.pid.set_normal_limits();
+            inner.zones[zone_idx].last_fan_status = fan_status.clone();
+
+            // all right, temperature has been aggregated, decide what to do
+            let decision_explained = ControlDecision::decide(
+                &zone_config,
+                num_fans_running,
+                raw_temp,
+                filtered_temp,
+                TICK_LENGTH,
+                &mut inner.zones[zone_idx].thermal_load,
+            );
+            trace!("Monitor: zone {} {:?}", zone_idx, decision_explained);
+            let status_line = format!(
+                "zone {} | {} | {} | {}",
+                zone_idx,
+                decision_explained.reason,
+                zone_chain_info[zone_idx].join(" "),
+                fan_feedback.to_string(),
+            );
+            match decision_explained.decision {
+                ControlDecision::Shutdown => {
+                    info!("Monitor: {}", status_line);
+                    if shutdown_reason.is_none() {
+                        shutdown_reason = Some(decision_explained.reason.clone());
+                    }
+                }
+                ControlDecision::UseFixedSpeed(fan_speed) => {
+                    info!("Monitor: {} fan_{}", status_line, fan_speed);
+                    Self::set_fan_speed(&mut inner.zones[zone_idx], fan_speed);
+                }
+                ControlDecision::UsePid {
+                    target_temp,
+                    input_temp,
+                } => {
+                    let warming_up =
+                        inner.config.fans_on_while_warming_up && zone_warming_up[zone_idx];
+                    let zone = &mut inner.zones[zone_idx];
+                    if warming_up {
+                        zone.pid.set_warm_up_limits();
+                    } else {
+                        zone.pid.set_normal_limits();
+                    }
+                    zone.pid.set_target(target_temp.into());
+                    let speed = zone.pid.update(input_temp.into());
+                    info!("Monitor: {} fan_{}", status_line, speed);
+                    Self::set_fan_speed(zone, speed);
+                }
+                ControlDecision::UseConfigurablePid {
+                    target_temp,
+                    input_temp,
+                    kp,
+                    ki,
+                    kd,
+                    sample_period,
+                    min_speed,
+                    max_speed,
+                } => {
+                    let zone = &mut inner.zones[zone_idx];
+                    let speed = zone.configurable_pid.update(
+                        input_temp,
+                        target_temp,
+                        kp,
+                        ki,
+                        kd,
+                        sample_period,
+                        min_speed,
+                        max_speed,
+                    );
+                    info!("Monitor: {} fan_{}", status_line, speed);
+                    Self::set_fan_speed(zone, speed);
+                }
+                ControlDecision::Throttle { load } => {
+                    // Fans are assumed to already be at full speed once we're throttling (the
+                    // temperature had to cross `hot_temp` to get here)
+                    Self::set_fan_speed(&mut inner.zones[zone_idx], fan::Speed::FULL_SPEED);
+                    info!(
+                        "Monitor: {} throttle_{:.0}%",
+                        status_line,
+                        load.as_f32() * 100.0
+                    );
                 }
-                inner.pid.set_target(target_temp.into());
-                let speed = inner.pid.update(input_temp.into());
-                self.set_fan_speed(&mut inner, speed);
-                info!("Monitor: {} fan_{}", status_line, speed);
+                ControlDecision::Nothing => {}
             }
-            ControlDecision::Nothing => {}
+
+            zone_loads.push(match decision_explained.decision {
+                ControlDecision::Throttle { load } => load,
+                _ => ThermalLoad::new(0.0),
+            });
+            zone_statuses.push(ZoneStatus {
+                zone_idx,
+                fan_speed: inner.zones[zone_idx].current_fan_speed,
+                input_temperature: filtered_temp,
+                temperature_accumulator: raw_accumulators[zone_idx].clone(),
+                decision_explained,
+                fan_status,
+                fan_feedback,
+            });
+        }
+
+        if let Some(reason) = shutdown_reason {
+            self.shutdown(inner, reason).await;
+            return;
         }
 
+        self.thermal_load_sender
+            .broadcast(zone_loads)
+            .expect("broadcast failed");
+
         // Broadcast `Status`
         let monitor_status = Status {
-            fan_feedback,
-            fan_speed: inner.current_fan_speed,
-            input_temperature,
-            temperature_accumulator,
-            decision_explained,
             config: inner.config.clone(),
+            zones: zone_statuses,
+            thermal_metrics: inner.metrics.summary(),
         };
         self.status_sender
             .broadcast(Some(monitor_status))
@@ -676,24 +1686,75 @@ impl Monitor {
         }
     }
 
+    /// Fast path for a just-arrived temperature that's already critical: react immediately
+    /// instead of waiting for the next `do_tick`, which can be up to `TICK_LENGTH` away.
+    /// Only handles the `TEMP_DANGER` portion of `decide()` (shutdown / full speed) - the
+    /// PID/curve adjustment is left to the periodic tick.
+    ///
+    /// `hashboard_idx` identifies which chain `temp` came from, so the right zone's
+    /// `TempControlConfig`/`FanBackend` get used.
+    async fn handle_critical_temp(self: &Arc<Self>, hashboard_idx: usize, temp: ChainTemperature) {
+        let mut inner = self.inner.lock().await;
+        let zone_idx = match inner.config.zone_idx_for_chain(hashboard_idx) {
+            Some(zone_idx) => zone_idx,
+            None => return,
+        };
+        let temp_config = inner.config.zones[zone_idx].temp_config.clone();
+        let dangerous = match (temp_config.as_ref(), temp) {
+            (Some(_), ChainTemperature::Failed) => true,
+            (Some(temp_config), ChainTemperature::Ok(input_temp)) => {
+                input_temp >= temp_config.dangerous_temp
+            }
+            _ => false,
+        };
+        if !dangerous {
+            return;
+        }
+        Self::set_fan_speed(&mut inner.zones[zone_idx], fan::Speed::FULL_SPEED);
+        let reason = format!("Shutdown: temperature {} above DANGEROUS (fast path)", temp);
+        error!("Monitor: {}", reason);
+        self.shutdown(inner, reason).await;
+    }
+
     /// Per-chain task that collects hashchain status update messages
-    async fn recv_task(chain: Arc<Mutex<Chain>>, mut rx: mpsc::UnboundedReceiver<Message>) {
+    async fn recv_task(
+        self: Arc<Self>,
+        chain: Arc<Mutex<Chain>>,
+        mut rx: mpsc::UnboundedReceiver<Message>,
+    ) {
         while let Some(message) = rx.next().await {
-            let mut chain = chain.lock().await;
-            chain.state.transition(Instant::now(), message);
+            let (hashboard_idx, temp) = {
+                let mut chain = chain.lock().await;
+                chain.state.transition(Instant::now(), message);
+                (chain.hashboard_idx, chain.get_temperature(Instant::now()))
+            };
+            // Let an incoming measurement pre-empt the periodic tick for the most
+            // safety-critical transitions - this mirrors alert-pin driven thermal handling.
+            self.handle_critical_temp(hashboard_idx, temp).await;
         }
     }
 
     /// Registers hashchain within monitor
     /// The `hashboard_idx` parameter is for debugging purposes
-    pub async fn register_hashchain(&self, hashboard_idx: usize) -> mpsc::UnboundedSender<Message> {
+    /// The `sensor_interpreter` is specific to the hashchain's hardware model and determines how
+    /// its raw sensor readings are turned into a `ChainTemperature`
+    pub async fn register_hashchain(
+        self: &Arc<Self>,
+        hashboard_idx: usize,
+        sensor_interpreter: Arc<dyn SensorInterpreter>,
+    ) -> mpsc::UnboundedSender<Message> {
         let (tx, rx) = mpsc::unbounded();
-        let chain = Arc::new(Mutex::new(Chain::new(hashboard_idx)));
-        {
-            let mut inner = self.inner.lock().await;
-            inner.chains.push(chain.clone());
-            tokio::spawn(Self::recv_task(chain, rx));
-        }
+        let mut inner = self.inner.lock().await;
+        let chain = Arc::new(Mutex::new(Chain::new(
+            hashboard_idx,
+            sensor_interpreter,
+            inner.config.temp_filter_time_constant,
+            inner.config.min_poll_interval,
+            inner.config.max_sample_age,
+        )));
+        inner.chains.push(chain.clone());
+        tokio::spawn(Self::recv_task(self.clone(), chain, rx));
+        drop(inner);
         tx
     }
 
@@ -704,6 +1765,14 @@ impl Monitor {
         let mut inner = self.inner.lock().await;
         f(&mut inner.config)
     }
+
+    /// Full accumulated thermal history - see `ThermalMetrics`. Unlike `Status::thermal_metrics`
+    /// (which is broadcast on every tick regardless of whether anyone reads it), this is only
+    /// computed on demand, since the per-chain histograms are too large to want cloned
+    /// unconditionally every `TICK_LENGTH`.
+    pub async fn metrics_snapshot(&self) -> ThermalMetrics {
+        self.inner.lock().await.metrics.clone()
+    }
 }
 
 #[cfg(test)]
@@ -734,7 +1803,7 @@ mod test {
             local: sensor::Measurement::Ok(10.0),
             remote: sensor::Measurement::Ok(22.0),
         };
-        match ChainTemperature::from_s9_sensor(temp) {
+        match S9SensorInterpreter.interpret(&temp) {
             ChainTemperature::Ok(t) => assert_relative_eq!(t, 22.0),
             _ => panic!("missing temperature"),
         };
@@ -742,7 +1811,7 @@ mod test {
             local: sensor::Measurement::Ok(10.0),
             remote: sensor::Measurement::OpenCircuit,
         };
-        match ChainTemperature::from_s9_sensor(temp) {
+        match S9SensorInterpreter.interpret(&temp) {
             ChainTemperature::Ok(t) => assert_relative_eq!(t, 25.0),
             _ => panic!("missing temperature"),
         };
@@ -751,7 +1820,7 @@ mod test {
             remote: sensor::Measurement::OpenCircuit,
         };
         assert_eq!(
-            ChainTemperature::from_s9_sensor(temp),
+            S9SensorInterpreter.interpret(&temp),
             ChainTemperature::Unknown
         );
     }
@@ -793,7 +1862,7 @@ mod test {
         );
         assert_variant!(
             send(ChainState::On(now), later, Message::Running(temp.clone())),
-            ChainState::Running{ .. }
+            ChainState::Running { .. }
         );
         assert_variant!(
             send(ChainState::On(now), later, Message::Off),
@@ -805,11 +1874,7 @@ mod test {
             ChainState::Broken(_)
         );
         assert_variant!(
-            send(
-                running_state.clone(),
-                later,
-                Message::Running(temp.clone())
-            ),
+            send(running_state.clone(), later, Message::Running(temp.clone())),
             ChainState::Running { .. }
         );
         assert_variant!(
@@ -873,7 +1938,7 @@ mod test {
         assert_variant!(tick(ChainState::On(now), short), ChainState::On(_));
         assert_variant!(
             tick(running_state.clone(), short),
-            ChainState::Running{..}
+            ChainState::Running { .. }
         );
 
         // different states have different update timeouts
@@ -939,6 +2004,35 @@ mod test {
         );
     }
 
+    /// Test that a chain's `hashboard_idx` resolves to the zone that lists it, and that a chain
+    /// absent from every zone is left unmanaged
+    #[test]
+    fn test_zone_idx_for_chain() {
+        let config = Config {
+            zones: vec![
+                ZoneConfig {
+                    hashboard_indices: vec![0, 1],
+                    fan_config: None,
+                    temp_config: None,
+                },
+                ZoneConfig {
+                    hashboard_indices: vec![2, 3],
+                    fan_config: None,
+                    temp_config: None,
+                },
+            ],
+            fans_on_while_warming_up: true,
+            temp_filter_time_constant: Duration::from_secs(15),
+            min_poll_interval: Duration::from_secs(0),
+            max_sample_age: Duration::from_secs(60),
+        };
+        assert_eq!(config.zone_idx_for_chain(0), Some(0));
+        assert_eq!(config.zone_idx_for_chain(1), Some(0));
+        assert_eq!(config.zone_idx_for_chain(2), Some(1));
+        assert_eq!(config.zone_idx_for_chain(3), Some(1));
+        assert_eq!(config.zone_idx_for_chain(4), None);
+    }
+
     /// Test temperature decision tree (non-exhaustive test)
     #[test]
     fn test_decide() {
@@ -948,146 +2042,345 @@ mod test {
         let temp_config = TempControlConfig {
             dangerous_temp: 100.0,
             hot_temp: 80.0,
+            throttle_temp: 80.0,
+            kp: 0.05,
+            ki: 0.0,
+            integral_max: 1000.0,
+            shutdown_grace: Duration::from_secs(30),
         };
+        let dt = Duration::from_secs(5);
         let fan_speed = fan::Speed::new(50);
         let fan_config = FanControlConfig {
             mode: FanControlMode::FixedSpeed(fan_speed),
             min_fans: 2,
+            low_signal_rpm: 100,
         };
         let fans_off = fan::Speed::STOPPED;
-        let fans_off_config = Config {
-            fans_on_while_warming_up: true,
+        let fans_off_config = ZoneConfig {
+            hashboard_indices: vec![0],
             fan_config: Some(FanControlConfig {
                 mode: FanControlMode::FixedSpeed(fans_off),
                 min_fans: 2,
+                low_signal_rpm: 100,
             }),
             temp_config: None,
         };
-        let all_off_config = Config {
-            fans_on_while_warming_up: true,
+        let all_off_config = ZoneConfig {
+            hashboard_indices: vec![0],
             fan_config: None,
             temp_config: None,
         };
-        let fans_on_config = Config {
-            fans_on_while_warming_up: true,
+        let fans_on_config = ZoneConfig {
+            hashboard_indices: vec![0],
             fan_config: Some(fan_config.clone()),
             temp_config: None,
         };
-        let temp_on_config = Config {
-            fans_on_while_warming_up: true,
+        let temp_on_config = ZoneConfig {
+            hashboard_indices: vec![0],
             fan_config: None,
             temp_config: Some(temp_config.clone()),
         };
-        let both_on_config = Config {
-            fans_on_while_warming_up: true,
+        let both_on_config = ZoneConfig {
+            hashboard_indices: vec![0],
             fan_config: Some(fan_config.clone()),
             temp_config: Some(temp_config.clone()),
         };
-        let both_on_pid_config = Config {
-            fans_on_while_warming_up: true,
+        let both_on_pid_config = ZoneConfig {
+            hashboard_indices: vec![0],
             fan_config: Some(FanControlConfig {
                 mode: FanControlMode::TargetTemperature(75.0),
                 min_fans: 2,
+                low_signal_rpm: 100,
             }),
             temp_config: Some(temp_config.clone()),
         };
 
         assert_variant!(
-            ControlDecision::decide(&all_off_config, 0, dang_temp.clone()).decision,
+            ControlDecision::decide(
+                &all_off_config,
+                0,
+                dang_temp,
+                dang_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Nothing
         );
         assert_variant!(
-            ControlDecision::decide(&all_off_config, 0, ChainTemperature::Failed).decision,
+            ControlDecision::decide(
+                &all_off_config,
+                0,
+                ChainTemperature::Failed,
+                ChainTemperature::Failed,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Nothing
         );
 
         assert_eq!(
-            ControlDecision::decide(&fans_on_config, 2, dang_temp.clone()).decision,
+            ControlDecision::decide(
+                &fans_on_config,
+                2,
+                dang_temp,
+                dang_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::UseFixedSpeed(fan_speed)
         );
         assert_eq!(
-            ControlDecision::decide(&fans_on_config, 0, dang_temp.clone()).decision,
+            ControlDecision::decide(
+                &fans_on_config,
+                0,
+                dang_temp,
+                dang_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&fans_on_config, 1, dang_temp.clone()).decision,
+            ControlDecision::decide(
+                &fans_on_config,
+                1,
+                dang_temp,
+                dang_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&fans_on_config, 2, ChainTemperature::Failed).decision,
+            ControlDecision::decide(
+                &fans_on_config,
+                2,
+                ChainTemperature::Failed,
+                ChainTemperature::Failed,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::UseFixedSpeed(fan_speed)
         );
 
         // fans set to 0 -> do not check if fans are running
         assert_eq!(
-            ControlDecision::decide(&fans_off_config, 0, dang_temp.clone()).decision,
+            ControlDecision::decide(
+                &fans_off_config,
+                0,
+                dang_temp,
+                dang_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::UseFixedSpeed(fans_off)
         );
 
         assert_eq!(
-            ControlDecision::decide(&temp_on_config, 0, ChainTemperature::Failed).decision,
+            ControlDecision::decide(
+                &temp_on_config,
+                0,
+                ChainTemperature::Failed,
+                ChainTemperature::Failed,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Shutdown
         );
         assert_variant!(
-            ControlDecision::decide(&temp_on_config, 0, ChainTemperature::Unknown).decision,
+            ControlDecision::decide(
+                &temp_on_config,
+                0,
+                ChainTemperature::Unknown,
+                ChainTemperature::Unknown,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Nothing
         );
         assert_eq!(
-            ControlDecision::decide(&temp_on_config, 0, dang_temp).decision,
+            ControlDecision::decide(
+                &temp_on_config,
+                0,
+                dang_temp,
+                dang_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Shutdown
         );
-        assert_variant!(
-            ControlDecision::decide(&temp_on_config, 0, hot_temp).decision,
-            ControlDecision::Nothing
+        assert_eq!(
+            ControlDecision::decide(
+                &temp_on_config,
+                0,
+                hot_temp,
+                hot_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
+            ControlDecision::Throttle {
+                load: ThermalLoad::new(0.75)
+            }
         );
 
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 0, low_temp).decision,
+            ControlDecision::decide(
+                &both_on_config,
+                0,
+                low_temp,
+                low_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 2, dang_temp).decision,
+            ControlDecision::decide(
+                &both_on_config,
+                2,
+                dang_temp,
+                dang_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 2, ChainTemperature::Failed).decision,
+            ControlDecision::decide(
+                &both_on_config,
+                2,
+                ChainTemperature::Failed,
+                ChainTemperature::Failed,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 2, ChainTemperature::Unknown).decision,
+            ControlDecision::decide(
+                &both_on_config,
+                2,
+                ChainTemperature::Unknown,
+                ChainTemperature::Unknown,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::UseFixedSpeed(fan::Speed::FULL_SPEED)
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 2, hot_temp).decision,
-            ControlDecision::UseFixedSpeed(fan_speed)
+            ControlDecision::decide(
+                &both_on_config,
+                2,
+                hot_temp,
+                hot_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
+            ControlDecision::Throttle {
+                load: ThermalLoad::new(0.75)
+            }
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_config, 2, low_temp).decision,
+            ControlDecision::decide(
+                &both_on_config,
+                2,
+                low_temp,
+                low_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::UseFixedSpeed(fan_speed)
         );
 
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 0, low_temp).decision,
+            ControlDecision::decide(
+                &both_on_pid_config,
+                0,
+                low_temp,
+                low_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 2, dang_temp).decision,
+            ControlDecision::decide(
+                &both_on_pid_config,
+                2,
+                dang_temp,
+                dang_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 2, ChainTemperature::Failed).decision,
+            ControlDecision::decide(
+                &both_on_pid_config,
+                2,
+                ChainTemperature::Failed,
+                ChainTemperature::Failed,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::Shutdown
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 2, ChainTemperature::Unknown).decision,
+            ControlDecision::decide(
+                &both_on_pid_config,
+                2,
+                ChainTemperature::Unknown,
+                ChainTemperature::Unknown,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::UseFixedSpeed(fan::Speed::FULL_SPEED)
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 2, hot_temp).decision,
-            ControlDecision::UseFixedSpeed(fan::Speed::FULL_SPEED)
+            ControlDecision::decide(
+                &both_on_pid_config,
+                2,
+                hot_temp,
+                hot_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
+            ControlDecision::Throttle {
+                load: ThermalLoad::new(0.75)
+            }
         );
         assert_eq!(
-            ControlDecision::decide(&both_on_pid_config, 2, low_temp).decision,
+            ControlDecision::decide(
+                &both_on_pid_config,
+                2,
+                low_temp,
+                low_temp,
+                dt,
+                &mut ThermalLoadController::new()
+            )
+            .decision,
             ControlDecision::UsePid {
                 target_temp: 75.0,
                 input_temp: 50.0
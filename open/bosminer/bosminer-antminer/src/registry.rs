@@ -45,6 +45,7 @@ impl<S: hal::BackendSolution + Clone + 'static> WorkRegistryItem<S> {
         let mut status = InsertSolutionStatus {
             duplicate: false,
             mismatched_nonce: false,
+            achieved_difficulty: 0.0,
             unique_solution: None,
         };
         // scan the current solutions and detect a duplicate
@@ -53,11 +54,18 @@ impl<S: hal::BackendSolution + Clone + 'static> WorkRegistryItem<S> {
             .iter()
             .find(|solution| solution.nonce() == new_solution.nonce());
         if matching_solution.is_none() {
-            // At this point, we know such solution has not been received yet. If it is valid (no
-            // hardware error detected == meets the target), it can be appended to the solution list
-            // for this work item
-            // TODO: call the evaluator for the solution
-            self.solutions.push(new_solution.clone());
+            // At this point, we know such solution has not been received yet. Run it through the
+            // evaluator - this recomputes the hash the chip claims to have found, which is the
+            // only way to tell an actual solution from a hardware error (the chip occasionally
+            // reports a nonce for the wrong midstate/solution index). Only solutions that really
+            // meet the work target are appended to the solution list for this work item.
+            let evaluation = Self::evaluate_solution(&self.work, &new_solution);
+            status.achieved_difficulty = evaluation.achieved_difficulty;
+            if evaluation.meets_work_target {
+                self.solutions.push(new_solution.clone());
+            } else {
+                status.mismatched_nonce = true;
+            }
         } else {
             // now we now it's a duplicate, but we return it anyway
             status.duplicate = true;
@@ -71,6 +79,34 @@ impl<S: hal::BackendSolution + Clone + 'static> WorkRegistryItem<S> {
         ));
         status
     }
+
+    /// Reconstructs the 80 byte block header for the midstate selected by
+    /// `solution.midstate_idx()` with the chip-reported `solution.nonce()` substituted in, runs
+    /// the double-SHA256 over it and forms the achieved target from the result.
+    ///
+    /// The achieved target is compared against the work (chip) target to detect hardware errors
+    /// and its difficulty is reported relative to the pool difficulty-1 target so callers can decide
+    /// whether the solution is worth submitting upstream.
+    fn evaluate_solution(work: &work::Assignment, solution: &S) -> SolutionEvaluation {
+        let achieved_hash =
+            work.get_block_hash(solution.midstate_idx(), solution.solution_idx(), solution.nonce());
+        let achieved_target = ii_bitcoin::Target::from_hash(&achieved_hash);
+
+        SolutionEvaluation {
+            meets_work_target: achieved_target <= *solution.target(),
+            achieved_difficulty: achieved_target.to_difficulty(),
+        }
+    }
+}
+
+/// Result of running a solution through `WorkRegistryItem::evaluate_solution`
+struct SolutionEvaluation {
+    /// Whether the achieved hash actually meets the work target. When `false`, the chip reported
+    /// a nonce that does not solve the midstate/solution index it claims to - a hardware error,
+    /// not a duplicate
+    meets_work_target: bool,
+    /// Difficulty represented by the achieved hash, i.e. `pool_diff_1_target / achieved_target`
+    achieved_difficulty: f64,
 }
 
 /// Helper container for the status after inserting the solution
@@ -80,6 +116,9 @@ pub struct InsertSolutionStatus {
     pub mismatched_nonce: bool,
     /// Solution is duplicate (given WorkRegistryItem) already has it
     pub duplicate: bool,
+    /// Difficulty represented by the hash actually achieved by the solution, computed as
+    /// `pool_diff_1_target / achieved_target`. Zero for duplicates, which are not re-evaluated.
+    pub achieved_difficulty: f64,
     /// actual solution (defined if the above 2 are false)
     /// TODO: rename `unique_solution` to solution
     pub unique_solution: Option<work::Solution>,
@@ -130,13 +169,16 @@ impl<S: hal::BackendSolution + Clone> WorkRegistry<S> {
 
     /// Store new work to work registry and generate `work_id` for it
     /// As a side effect, retire stale work.
-    /// Returns: new `work_id`
-    pub fn store_work(&mut self, work: work::Assignment, initial_work: bool) -> usize {
+    /// Returns: new `work_id`, plus the `work_id` retired in the same call - `None` until the
+    /// registry has filled up enough to start retiring, `Some` from then on. Callers pacing
+    /// submission against this retirement (e.g. `WorkPacer`) use it to know when to free up
+    /// room for more work.
+    pub fn store_work(&mut self, work: work::Assignment, initial_work: bool) -> (usize, Option<usize>) {
         let work_id = self.alloc_next_work_id();
 
         // retire stale work
         let retire_id = (work_id + self.registry_size / 2) % self.registry_size;
-        self.pending_work_list[retire_id] = None;
+        let retired_id = self.pending_work_list[retire_id].take().map(|_| retire_id);
 
         // put new work into registry
         self.pending_work_list[work_id] = Some(WorkRegistryItem {
@@ -145,8 +187,8 @@ impl<S: hal::BackendSolution + Clone> WorkRegistry<S> {
             initial_work,
         });
 
-        // return assigned work id
-        work_id
+        // return assigned work id and whatever got retired alongside it
+        (work_id, retired_id)
     }
 
     /// Look-up work id
@@ -154,6 +196,18 @@ impl<S: hal::BackendSolution + Clone> WorkRegistry<S> {
         assert!(work_id < self.registry_size);
         &mut self.pending_work_list[work_id]
     }
+
+    /// True once `work_id`'s slot has been retired (overwritten by newer work), meaning no
+    /// further solutions for it can still arrive
+    pub fn is_retired(&self, work_id: usize) -> bool {
+        assert!(work_id < self.registry_size);
+        self.pending_work_list[work_id].is_none()
+    }
+
+    /// True once every id in `work_ids` has been retired
+    pub fn all_retired(&self, work_ids: &[usize]) -> bool {
+        work_ids.iter().all(|&work_id| self.is_retired(work_id))
+    }
 }
 
 #[cfg(test)]
@@ -190,8 +244,8 @@ mod test {
         let work1 = null_work::prepare(0);
         let work2 = null_work::prepare(1);
 
-        assert_eq!(registry.store_work(work1, false), 0);
-        assert_eq!(registry.store_work(work2, false), 1);
+        assert_eq!(registry.store_work(work1, false), (0, None));
+        assert_eq!(registry.store_work(work2, false), (1, None));
         assert!(registry.find_work(0).is_some());
         assert!(registry.find_work(1).is_some());
         assert!(registry.find_work(2).is_none());
@@ -207,7 +261,10 @@ mod test {
         // we store more than REGISTRY_SIZE items so it has to roll over
         for i in 0..NUM_WORK_ITEMS {
             let work = null_work::prepare(i as u64);
-            assert_eq!(registry.store_work(work, false), i % REGISTRY_SIZE);
+            let (work_id, retired_id) = registry.store_work(work, false);
+            assert_eq!(work_id, i % REGISTRY_SIZE);
+            // retirement only kicks in once the registry has filled up enough to wrap around
+            assert_eq!(retired_id.is_some(), i >= REGISTRY_SIZE / 2);
         }
 
         // verify that half of registry is empty, half used
@@ -230,11 +287,11 @@ mod test {
         const REGISTRY_SIZE: usize = 4;
         let mut registry = WorkRegistry::<NullSolution>::new(REGISTRY_SIZE);
         let work = null_work::prepare(0);
-        assert_eq!(registry.store_work(work.clone(), false), 0);
-        assert_eq!(registry.store_work(work.clone(), false), 1);
-        assert_eq!(registry.store_work(work.clone(), false), 2);
-        assert_eq!(registry.store_work(work.clone(), false), 3);
-        assert_eq!(registry.store_work(work.clone(), false), 0);
+        assert_eq!(registry.store_work(work.clone(), false), (0, None));
+        assert_eq!(registry.store_work(work.clone(), false), (1, None));
+        assert_eq!(registry.store_work(work.clone(), false), (2, Some(0)));
+        assert_eq!(registry.store_work(work.clone(), false), (3, Some(1)));
+        assert_eq!(registry.store_work(work.clone(), false), (0, Some(2)));
     }
 
     /// Test that `initial_work` flag propagates to `WorkRegistryItem`
@@ -244,8 +301,8 @@ mod test {
         let work1 = null_work::prepare(0);
         let work2 = null_work::prepare(0);
 
-        assert_eq!(registry.store_work(work1, true), 0);
-        assert_eq!(registry.store_work(work2, false), 1);
+        assert_eq!(registry.store_work(work1, true), (0, None));
+        assert_eq!(registry.store_work(work2, false), (1, None));
         assert_eq!(
             registry
                 .find_work(0)
@@ -263,4 +320,27 @@ mod test {
             false
         );
     }
+
+    /// Test that `is_retired`/`all_retired` track retirement the same way `store_work` reports it
+    #[test]
+    fn test_is_retired() {
+        const REGISTRY_SIZE: usize = 4;
+        let mut registry = WorkRegistry::<NullSolution>::new(REGISTRY_SIZE);
+        let work = null_work::prepare(0);
+
+        // work ids 0 and 1 - nothing has been retired yet, the registry hasn't filled up
+        registry.store_work(work.clone(), false);
+        registry.store_work(work.clone(), false);
+        assert!(!registry.is_retired(0));
+        assert!(!registry.is_retired(1));
+        assert!(!registry.all_retired(&[0, 1]));
+
+        // work ids 2 and 3 retire 0 and 1 respectively (registry_size / 2 == 2 slots ahead)
+        registry.store_work(work.clone(), false);
+        registry.store_work(work.clone(), false);
+        assert!(registry.is_retired(0));
+        assert!(registry.is_retired(1));
+        assert!(registry.all_retired(&[0, 1]));
+        assert!(!registry.all_retired(&[0, 1, 2]));
+    }
 }
@@ -0,0 +1,53 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Persistent honggfuzz loop over the pool URL / user-info parsing layer.
+//!
+//! `Protocol::parse`, `Descriptor::create` and `UserInfo::parse` take operator-supplied strings
+//! straight off the command line or config file, so they must never panic and never allocate
+//! unboundedly no matter how malformed the input is. Run with `cargo hfuzz run parse_client_url`
+//! from this directory.
+
+use honggfuzz::fuzz;
+
+use bosminer_config::client::{Descriptor, UserInfo};
+
+fn main() {
+    loop {
+        fuzz!(|data: &[u8]| {
+            let input = match std::str::from_utf8(data) {
+                Ok(input) => input,
+                Err(_) => return,
+            };
+
+            // first line is fed to the URL parser, the rest to the user-info parser, so a single
+            // corpus entry can exercise both parsers in one iteration
+            let mut lines = input.splitn(2, '\n');
+            let url = lines.next().unwrap_or("");
+            let user_info = lines.next().unwrap_or("");
+
+            if let Ok(user_info) = UserInfo::parse(user_info) {
+                let _ = Descriptor::create(url, &user_info, false);
+            }
+        });
+    }
+}
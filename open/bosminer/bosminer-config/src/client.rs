@@ -28,6 +28,7 @@ use url::Url;
 
 use std::convert::TryFrom;
 use std::fmt;
+use std::time::{Duration, Instant};
 
 use failure::ResultExt;
 
@@ -132,14 +133,16 @@ impl<'a> UserInfo<'a> {
     }
 
     /// Parse user and password from user info (user[:password])
-    pub fn parse(value: &'a str) -> Self {
+    pub fn parse(value: &'a str) -> error::Result<Self> {
         let user_info: Vec<_> = value.rsplitn(2, Self::DELIMITER).collect();
         let mut user_info = user_info.iter().rev();
 
-        let user = user_info.next().expect("BUG: missing user");
+        let user = user_info
+            .next()
+            .ok_or_else(|| error::ErrorKind::Client("missing user in user info".to_string()))?;
         let password = user_info.next().map(|value| *value);
 
-        Self { user, password }
+        Ok(Self { user, password })
     }
 }
 
@@ -223,3 +226,230 @@ impl Descriptor {
                 .is_some()
     }
 }
+
+/// One `Descriptor` participating in a `ClientGroup`, together with the failover/load-balancing
+/// bookkeeping the group needs to drive connection selection.
+#[derive(Clone, Debug)]
+pub struct ClientGroupEntry {
+    pub descriptor: Descriptor,
+    /// Entries are tried in ascending priority order - the group fails over to the next
+    /// priority only when no reachable entry remains at a lower value
+    pub priority: usize,
+    /// Relative weight used for deficit-weighted round-robin among connected entries that share
+    /// `priority`. `None` is treated as a weight of 1
+    pub quota: Option<usize>,
+    connected: bool,
+    dead_since: Option<Instant>,
+    backoff: Duration,
+    /// Running credit accumulated for deficit-weighted round-robin, see `ClientGroup::select`
+    credit: f64,
+}
+
+impl ClientGroupEntry {
+    const INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+    const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+    fn new(descriptor: Descriptor, priority: usize, quota: Option<usize>) -> Self {
+        Self {
+            descriptor,
+            priority,
+            quota,
+            connected: false,
+            dead_since: None,
+            backoff: Self::INITIAL_BACKOFF,
+            credit: 0.0,
+        }
+    }
+
+    /// An entry is a failover candidate when it has not been marked dead (connected, or not yet
+    /// tried), or when it was marked dead long enough ago that its exponential backoff has elapsed
+    /// and it is due a re-probe
+    fn is_candidate(&self, now: Instant) -> bool {
+        match self.dead_since {
+            None => true,
+            Some(dead_since) => now.duration_since(dead_since) >= self.backoff,
+        }
+    }
+}
+
+/// Holds an ordered list of pool `Descriptor`s and drives connection selection among them:
+/// connection loss or repeated share rejection fails over to the next-highest priority reachable
+/// pool, while entries that share a priority distribute submitted work proportionally to their
+/// `quota` using deficit-weighted round-robin. Mixed V1/V2/insecure pools can coexist in one
+/// group since each entry preserves its own `Descriptor::create`/`Protocol::parse` semantics.
+#[derive(Clone, Debug, Default)]
+pub struct ClientGroup {
+    entries: Vec<ClientGroupEntry>,
+}
+
+impl ClientGroup {
+    pub fn new() -> Self {
+        Self { entries: vec![] }
+    }
+
+    /// Add a pool to the group at the given `priority` (lower tried first) with an optional
+    /// `quota` weight used to share work with other entries at the same priority
+    pub fn push(&mut self, descriptor: Descriptor, priority: usize, quota: Option<usize>) {
+        self.entries
+            .push(ClientGroupEntry::new(descriptor, priority, quota));
+        self.entries.sort_by_key(|entry| entry.priority);
+    }
+
+    pub fn entries(&self) -> &[ClientGroupEntry] {
+        &self.entries
+    }
+
+    /// Mark an entry dead, e.g. after connection loss or a failed connection attempt. It stops
+    /// being considered for selection until its exponential backoff elapses
+    pub fn mark_dead(&mut self, index: usize) {
+        let now = Instant::now();
+        if let Some(entry) = self.entries.get_mut(index) {
+            // Gate on `is_candidate`, not `connected` - an entry that has never successfully
+            // connected is still `!connected`, but it must be gated into `dead_since`/backoff on
+            // its first failed attempt the same as any other entry, or it would be retried with
+            // zero backoff forever. Gating on `is_candidate` also means a repeat `mark_dead` while
+            // an entry is already serving out its backoff (not yet retried) is a no-op instead of
+            // doubling the backoff again for no new failure.
+            if entry.is_candidate(now) {
+                entry.connected = false;
+                entry.dead_since = Some(now);
+                entry.backoff = (entry.backoff * 2).min(ClientGroupEntry::MAX_BACKOFF);
+            }
+        }
+    }
+
+    /// Mark an entry alive again, resetting its backoff back to the initial value
+    pub fn mark_alive(&mut self, index: usize) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.connected = true;
+            entry.dead_since = None;
+            entry.backoff = ClientGroupEntry::INITIAL_BACKOFF;
+        }
+    }
+
+    /// Select the entry that should handle the next unit of work: the highest-priority
+    /// candidate (not marked dead, or due for a backoff re-probe), broken by deficit-weighted round
+    /// robin among candidates that share that priority - each candidate's credit is incremented
+    /// by its quota normalized over the tier, the candidate with the highest credit is chosen,
+    /// and its credit is then decremented.
+    pub fn select(&mut self) -> Option<usize> {
+        let now = Instant::now();
+        let top_priority = self
+            .entries
+            .iter()
+            .filter(|entry| entry.is_candidate(now))
+            .map(|entry| entry.priority)
+            .min()?;
+
+        let tier: Vec<usize> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.priority == top_priority && entry.is_candidate(now))
+            .map(|(index, _)| index)
+            .collect();
+
+        let total_quota: usize = tier
+            .iter()
+            .map(|&index| self.entries[index].quota.unwrap_or(1))
+            .sum();
+        for &index in &tier {
+            let normalized_quota = self.entries[index].quota.unwrap_or(1) as f64 / total_quota as f64;
+            self.entries[index].credit += normalized_quota;
+        }
+
+        let chosen = *tier.iter().max_by(|&&a, &&b| {
+            self.entries[a]
+                .credit
+                .partial_cmp(&self.entries[b].credit)
+                .expect("BUG: credit is NaN")
+        })?;
+        self.entries[chosen].credit -= 1.0;
+
+        Some(chosen)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_descriptor(host: &str) -> Descriptor {
+        Descriptor::create(
+            &format!("stratum+tcp://{}:3333", host),
+            &UserInfo::new("user", None),
+            true,
+        )
+        .expect("BUG: invalid test descriptor")
+    }
+
+    /// A freshly pushed entry has never connected, but must still be a failover candidate right
+    /// away - and, crucially, `mark_dead` must still apply backoff to it on its first failure
+    /// instead of leaving it retried with zero backoff forever
+    #[test]
+    fn test_mark_dead_applies_backoff_to_never_connected_entry() {
+        let mut group = ClientGroup::new();
+        group.push(test_descriptor("a"), 0, None);
+
+        assert_eq!(group.select(), Some(0));
+
+        group.mark_dead(0);
+        assert_eq!(group.select(), None);
+    }
+
+    /// Calling `mark_dead` again on an entry that's already serving out its backoff (hasn't been
+    /// retried yet) must not panic and must leave it a non-candidate
+    #[test]
+    fn test_repeated_mark_dead_is_idempotent_while_backing_off() {
+        let mut group = ClientGroup::new();
+        group.push(test_descriptor("a"), 0, None);
+
+        group.mark_dead(0);
+        group.mark_dead(0);
+        assert_eq!(group.select(), None);
+    }
+
+    /// `mark_alive` reverses `mark_dead`: the entry becomes a candidate again
+    #[test]
+    fn test_mark_alive_restores_candidacy() {
+        let mut group = ClientGroup::new();
+        group.push(test_descriptor("a"), 0, None);
+
+        group.mark_dead(0);
+        assert_eq!(group.select(), None);
+
+        group.mark_alive(0);
+        assert_eq!(group.select(), Some(0));
+    }
+
+    /// A dead top-priority entry is skipped in favor of the next-highest priority entry that's
+    /// still a candidate
+    #[test]
+    fn test_failover_to_next_priority() {
+        let mut group = ClientGroup::new();
+        group.push(test_descriptor("primary"), 0, None);
+        group.push(test_descriptor("backup"), 1, None);
+
+        group.mark_dead(0);
+        assert_eq!(group.select(), Some(1));
+    }
+
+    /// Entries sharing a priority split selections proportionally to their `quota` via
+    /// deficit-weighted round robin
+    #[test]
+    fn test_round_robin_respects_quota() {
+        let mut group = ClientGroup::new();
+        group.push(test_descriptor("light"), 0, Some(1));
+        group.push(test_descriptor("heavy"), 0, Some(2));
+
+        let mut counts = [0usize; 2];
+        for _ in 0..30 {
+            let chosen = group.select().expect("BUG: no candidate");
+            counts[chosen] += 1;
+        }
+
+        // "heavy" (index 1, quota 2) should get roughly twice the selections "light" does
+        assert_eq!(counts[0], 10);
+        assert_eq!(counts[1], 20);
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Persistent, per-pool share accounting that survives restarts. Counts are kept in memory for
+//! the hot path and flushed to an embedded key-value store asynchronously so a crash or upgrade
+//! loses at most the last few shares instead of the miner's whole lifetime statistics.
+
+use crate::client::{Descriptor, Protocol};
+use crate::error;
+
+use ii_logging::macros::*;
+
+use futures::channel::mpsc;
+use futures::lock::Mutex;
+use futures::stream::StreamExt;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Stable identity of a pool used as the persistence key, computed from the connection URL
+/// (without credentials) plus the Stratum V2 upstream authority key when present, so that
+/// changing just the username/password doesn't reset accounting for an otherwise unchanged pool.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PoolIdentity(String);
+
+impl PoolIdentity {
+    pub fn new(descriptor: &Descriptor) -> Self {
+        let mut identity = descriptor.get_url(true, true, false);
+        if let Protocol::StratumV2(upstream_authority_public_key) = &descriptor.protocol {
+            identity.push('#');
+            identity.push_str(&upstream_authority_public_key.to_string());
+        }
+        Self(identity)
+    }
+}
+
+/// Lifetime share and achieved-difficulty accounting for a single pool
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PoolAccounting {
+    pub accepted: u64,
+    pub rejected: u64,
+    pub stale: u64,
+    /// Sum of `InsertSolutionStatus::achieved_difficulty` of every accepted share
+    pub achieved_difficulty: f64,
+}
+
+impl PoolAccounting {
+    fn account_accepted(&mut self, achieved_difficulty: f64) {
+        self.accepted += 1;
+        self.achieved_difficulty += achieved_difficulty;
+    }
+
+    fn account_rejected(&mut self) {
+        self.rejected += 1;
+    }
+
+    fn account_stale(&mut self) {
+        self.stale += 1;
+    }
+}
+
+/// Backing storage for persisted pool accounting. Implementations are only ever touched from the
+/// asynchronous flush task, never from the work/solution hot path.
+pub trait StatsBackend: Send + Sync {
+    fn load(&self, key: &PoolIdentity) -> error::Result<Option<PoolAccounting>>;
+    fn store(&self, key: &PoolIdentity, accounting: &PoolAccounting) -> error::Result<()>;
+}
+
+/// `StatsBackend` backed by an LMDB environment, one key-value pair per pool
+pub struct LmdbStatsBackend {
+    env: lmdb::Environment,
+    db: lmdb::Database,
+}
+
+impl LmdbStatsBackend {
+    pub fn open(path: &std::path::Path) -> error::Result<Self> {
+        std::fs::create_dir_all(path)
+            .map_err(|e| error::ErrorKind::Client(format!("cannot create stats directory: {}", e)))?;
+        let env = lmdb::Environment::new()
+            .set_map_size(16 * 1024 * 1024)
+            .open(path)
+            .map_err(|e| error::ErrorKind::Client(format!("cannot open stats database: {}", e)))?;
+        let db = env
+            .open_db(None)
+            .map_err(|e| error::ErrorKind::Client(format!("cannot open stats database: {}", e)))?;
+
+        Ok(Self { env, db })
+    }
+}
+
+impl StatsBackend for LmdbStatsBackend {
+    fn load(&self, key: &PoolIdentity) -> error::Result<Option<PoolAccounting>> {
+        use lmdb::Transaction;
+
+        let txn = self
+            .env
+            .begin_ro_txn()
+            .map_err(|e| error::ErrorKind::Client(format!("cannot read stats database: {}", e)))?;
+        match txn.get(self.db, &key.0) {
+            Ok(bytes) => serde_json::from_slice(bytes)
+                .map(Some)
+                .map_err(|e| error::ErrorKind::Client(format!("corrupt stats entry: {}", e)).into()),
+            Err(lmdb::Error::NotFound) => Ok(None),
+            Err(e) => Err(error::ErrorKind::Client(format!("cannot read stats database: {}", e)).into()),
+        }
+    }
+
+    fn store(&self, key: &PoolIdentity, accounting: &PoolAccounting) -> error::Result<()> {
+        let bytes = serde_json::to_vec(accounting)
+            .map_err(|e| error::ErrorKind::Client(format!("cannot serialize stats: {}", e)))?;
+
+        let mut txn = self
+            .env
+            .begin_rw_txn()
+            .map_err(|e| error::ErrorKind::Client(format!("cannot write stats database: {}", e)))?;
+        txn.put(self.db, &key.0, &bytes, lmdb::WriteFlags::empty())
+            .map_err(|e| error::ErrorKind::Client(format!("cannot write stats database: {}", e)))?;
+        txn.commit()
+            .map_err(|e| error::ErrorKind::Client(format!("cannot write stats database: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+/// Keeps in-memory lifetime accounting per pool and mirrors it to a `StatsBackend` so it survives
+/// restarts. Reads and the counters themselves are in-memory and cheap; persisting to the
+/// backend happens on a background task fed by an unbounded channel so it never blocks the
+/// work/solution hot path.
+#[derive(Clone)]
+pub struct PoolStatsStore {
+    backend: Arc<dyn StatsBackend>,
+    state: Arc<Mutex<HashMap<PoolIdentity, PoolAccounting>>>,
+    flush_sender: mpsc::UnboundedSender<PoolIdentity>,
+}
+
+impl PoolStatsStore {
+    /// Create a new store backed by `backend`, returning it together with the flush task that
+    /// must be spawned by the caller to actually persist updates
+    pub fn new(backend: Arc<dyn StatsBackend>) -> (Self, PoolStatsFlushTask) {
+        let (flush_sender, flush_receiver) = mpsc::unbounded();
+        let state = Arc::new(Mutex::new(HashMap::new()));
+
+        let store = Self {
+            backend: backend.clone(),
+            state: state.clone(),
+            flush_sender,
+        };
+        let flush_task = PoolStatsFlushTask {
+            backend,
+            state,
+            flush_receiver,
+        };
+
+        (store, flush_task)
+    }
+
+    /// Load a pool's persisted accounting (if any) into memory so its lifetime counters keep
+    /// accumulating instead of resetting to zero
+    pub async fn load(&self, descriptor: &Descriptor) -> error::Result<()> {
+        let key = PoolIdentity::new(descriptor);
+        let accounting = self.backend.load(&key)?.unwrap_or_default();
+        self.state.lock().await.insert(key, accounting);
+
+        Ok(())
+    }
+
+    pub async fn account_accepted(&self, descriptor: &Descriptor, achieved_difficulty: f64) {
+        let key = PoolIdentity::new(descriptor);
+        self.state
+            .lock()
+            .await
+            .entry(key.clone())
+            .or_default()
+            .account_accepted(achieved_difficulty);
+        let _ = self.flush_sender.unbounded_send(key);
+    }
+
+    pub async fn account_rejected(&self, descriptor: &Descriptor) {
+        let key = PoolIdentity::new(descriptor);
+        self.state
+            .lock()
+            .await
+            .entry(key.clone())
+            .or_default()
+            .account_rejected();
+        let _ = self.flush_sender.unbounded_send(key);
+    }
+
+    pub async fn account_stale(&self, descriptor: &Descriptor) {
+        let key = PoolIdentity::new(descriptor);
+        self.state
+            .lock()
+            .await
+            .entry(key.clone())
+            .or_default()
+            .account_stale();
+        let _ = self.flush_sender.unbounded_send(key);
+    }
+
+    /// Snapshot of all known pools' accounting for the telemetry layer to poll
+    pub async fn snapshot(&self) -> HashMap<PoolIdentity, PoolAccounting> {
+        self.state.lock().await.clone()
+    }
+}
+
+/// Background task that drains flush requests from `PoolStatsStore` and persists the
+/// corresponding entry to the `StatsBackend`. Intended to be spawned once and run for the
+/// lifetime of the miner.
+pub struct PoolStatsFlushTask {
+    backend: Arc<dyn StatsBackend>,
+    state: Arc<Mutex<HashMap<PoolIdentity, PoolAccounting>>>,
+    flush_receiver: mpsc::UnboundedReceiver<PoolIdentity>,
+}
+
+impl PoolStatsFlushTask {
+    pub async fn run(mut self) {
+        while let Some(key) = self.flush_receiver.next().await {
+            let accounting = match self.state.lock().await.get(&key) {
+                Some(accounting) => accounting.clone(),
+                None => continue,
+            };
+            if let Err(e) = self.backend.store(&key, &accounting) {
+                // persistence is best-effort: a failed flush is retried on the next share, the
+                // hot path must never be held up by it
+                warn!("failed to persist pool stats: {}", e);
+            }
+        }
+    }
+}
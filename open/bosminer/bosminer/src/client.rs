@@ -23,12 +23,14 @@
 //! This module contains common functionality related to mining protocol client and allows
 //! executing a specific type of mining protocol client instance.
 
+mod cluster;
 mod scheduler;
 
 // Sub-modules with client implementation
 pub mod drain;
 pub mod stratum_v2;
 
+use self::cluster::ClusterHandle;
 use crate::error;
 use crate::hal;
 use crate::job;
@@ -46,12 +48,50 @@ use bosminer_config::{
 };
 
 use futures::channel::mpsc;
+use futures::future;
 use futures::lock::Mutex;
-use ii_async_compat::futures;
+use futures::StreamExt;
+use ii_async_compat::{futures, tokio};
+use tokio::time::delay_for;
 
+use std::net::SocketAddr;
 use std::slice;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+use std::time::{Duration, Instant};
+
+/// How often `Handle::try_disable_graceful` re-checks for outstanding solutions while draining
+const GRACEFUL_DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A single delta pushed through `Manager::subscribe_to_clients_status_changes` - lets a UI apply
+/// exactly what changed instead of re-scanning every group and client after each wake-up. `group`/
+/// `index` are positions at the time the event was raised, same caveat as `GroupRegistry`'s/
+/// `Group`'s other index-based APIs: they're not stable across a later removal or move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClientEvent {
+    /// A new group was created at `index`
+    GroupCreated { index: usize },
+    /// A client was added to `group` at `index`
+    ClientAdded { group: usize, index: usize },
+    /// The client that was at `index` in `group` was removed
+    ClientRemoved { group: usize, index: usize },
+    /// The client at `group` moved from one position to another
+    ClientMoved {
+        group: usize,
+        from: usize,
+        to: usize,
+    },
+    /// The client at `index` in `group` transitioned from `old` to `new` - raised by
+    /// `crate::sync::Status` itself, so it covers transitions the node drives on its own (e.g. a
+    /// dropped connection), not just ones `Handle::start`/`stop` initiate
+    ClientStatusChanged {
+        group: usize,
+        index: usize,
+        old: crate::sync::Status,
+        new: crate::sync::Status,
+    },
+}
 
 #[derive(Debug)]
 pub struct Handle {
@@ -175,9 +215,19 @@ impl Handle {
             .unwrap_or(false)
     }
 
+    /// Hands `event_sender` to this client's `Status`, which keeps it for the lifetime of the
+    /// client so it can raise `ClientEvent::ClientStatusChanged { group, index, .. }` for every
+    /// transition it drives, not just the ones `start`/`stop` below initiate
     #[inline]
-    fn set_event_sender(&self, event_sender: event::Sender) -> Option<event::Sender> {
-        self.node.status().set_event_sender(event_sender)
+    fn set_event_sender(
+        &self,
+        event_sender: event::Sender,
+        group: usize,
+        index: usize,
+    ) -> Option<event::Sender> {
+        self.node
+            .status()
+            .set_event_sender(event_sender, group, index)
     }
 
     #[inline]
@@ -243,6 +293,39 @@ impl Handle {
         }
     }
 
+    /// An engine generator that reports no further work for any job - used by
+    /// `try_disable_graceful` so no new work enters the pipeline while draining, while the
+    /// connection and `solution_sender` stay alive long enough for solutions to jobs already
+    /// handed out to still be submitted and acknowledged
+    fn no_op_engine_generator() -> work::EngineGenerator {
+        Box::new(|_job| Arc::new(work::engine::Exhausted))
+    }
+
+    /// Gracefully disable the client instead of tearing the connection down immediately like
+    /// `try_disable` does, losing any work whose solution hasn't been submitted/acked yet. This
+    /// first swaps in `no_op_engine_generator` so no new work is produced, while the Stratum
+    /// connection and `solution_sender` stay up, then waits up to `timeout` for solutions to the
+    /// last jobs already handed out to drain before finally calling `stop()`. Same `Err(())` on an
+    /// already-disabled client as `try_disable`.
+    pub async fn try_disable_graceful(&self, timeout: Duration) -> Result<(), ()> {
+        let was_enabled = self.enabled.swap(false, Ordering::Relaxed);
+        if !was_enabled {
+            return Err(());
+        }
+
+        // Stop producing new work immediately, but keep the connection and solution channel
+        // alive so solutions for jobs already handed out can still be delivered and acknowledged
+        let _ = self.replace_engine_generator(Self::no_op_engine_generator());
+
+        let deadline = Instant::now() + timeout;
+        while self.node.has_pending_solutions() && Instant::now() < deadline {
+            delay_for(GRACEFUL_DRAIN_POLL_INTERVAL).await;
+        }
+
+        self.stop();
+        Ok(())
+    }
+
     /// Try restart the client
     /// `enabled` - enforces the method to return an error if the client is already disabled,
     /// so the initial stop fails. Set this parameter to false if you want to restart the client
@@ -292,8 +375,16 @@ pub struct Group {
     pub descriptor: GroupDescriptor,
     scheduler_client_handles: Mutex<Vec<scheduler::ClientHandle>>,
     event_sender: event::Sender,
+    /// This group's position in `GroupRegistry`'s list at the time it was created, used to fill
+    /// in `ClientEvent`'s `group` field - same index-stability caveat as the rest of the
+    /// `GroupRegistry` API: it goes stale if an earlier group is later removed
+    group_index: usize,
     /// All clients in the group must support the same amount of midstates
     midstate_count: usize,
+    /// Set via `set_cluster` when this group is declared cluster-shared - see
+    /// `GroupRegistry::set_group_cluster`. A plain `StdMutex` (rather than the async `Mutex` used
+    /// above) because `GroupRegistry::recalculate_quotas` reads it from a synchronous context.
+    cluster: StdMutex<Option<ClusterHandle>>,
 }
 
 impl Group {
@@ -301,15 +392,35 @@ impl Group {
         descriptor: GroupDescriptor,
         event_sender: event::Sender,
         midstate_count: usize,
+        group_index: usize,
     ) -> Self {
         Self {
             descriptor,
             scheduler_client_handles: Mutex::new(vec![]),
             event_sender,
+            group_index,
             midstate_count,
+            cluster: StdMutex::new(None),
         }
     }
 
+    /// Declares (or, passing `None`, clears) this group as cluster-shared - see
+    /// `GroupRegistry::set_group_cluster`
+    #[inline]
+    pub fn set_cluster(&self, cluster: Option<ClusterHandle>) {
+        *self.cluster.lock().expect("BUG: cluster lock poisoned") = cluster;
+    }
+
+    /// This group's cluster, if it's been declared cluster-shared - see
+    /// `GroupRegistry::recalculate_quotas`
+    #[inline]
+    pub fn cluster(&self) -> Option<ClusterHandle> {
+        self.cluster
+            .lock()
+            .expect("BUG: cluster lock poisoned")
+            .clone()
+    }
+
     #[inline]
     pub async fn len(&self) -> usize {
         self.scheduler_client_handles.lock().await.len()
@@ -336,16 +447,21 @@ impl Group {
             Arc::new(work::engine::VersionRolling::new(job, midstate_count))
         }));
         let _ = client_handle.try_disable();
-        client_handle.set_event_sender(self.event_sender.clone());
 
         let client_handle = Arc::new(client_handle);
         let scheduler_client_handle = scheduler::ClientHandle::new(client_handle.clone());
-        self.scheduler_client_handles
-            .lock()
-            .await
-            .push(scheduler_client_handle);
+        let index = {
+            let mut scheduler_client_handles = self.scheduler_client_handles.lock().await;
+            let index = scheduler_client_handles.len();
+            scheduler_client_handles.push(scheduler_client_handle);
+            index
+        };
+        client_handle.set_event_sender(self.event_sender.clone(), self.group_index, index);
         // Immediately notify about client addition to the group
-        self.event_sender.notify();
+        self.event_sender.notify_event(ClientEvent::ClientAdded {
+            group: self.group_index,
+            index,
+        });
 
         {
             // NOTE: Keep descriptor locked to synchronize descriptor changes
@@ -361,20 +477,33 @@ impl Group {
         client_handle
     }
 
-    pub async fn remove_client_at(&self, index: usize) -> Result<Arc<Handle>, error::Client> {
-        let mut scheduler_client_handles = self.scheduler_client_handles.lock().await;
-        if index >= scheduler_client_handles.len() {
-            Err(error::Client::Missing)
-        } else {
-            let client_handle = scheduler_client_handles.remove(index).client_handle;
-            // Immediately notify about client removal from the group
-            self.event_sender.notify();
-            // Remove event sender not to notify about removed client status changes
-            client_handle.take_event_sender();
-            // Immediately disable client to force scheduler to select another client
-            let _ = client_handle.try_disable();
-            Ok(client_handle)
-        }
+    /// Removes the client at `index` from the group, draining it gracefully: solutions to jobs
+    /// already handed out get up to `drain_timeout` to be submitted and acknowledged before its
+    /// connection is torn down, rather than discarding them immediately. The client stops
+    /// receiving new work as soon as it's removed from `scheduler_client_handles`, well before the
+    /// drain completes.
+    pub async fn remove_client_at(
+        &self,
+        index: usize,
+        drain_timeout: Duration,
+    ) -> Result<Arc<Handle>, error::Client> {
+        let client_handle = {
+            let mut scheduler_client_handles = self.scheduler_client_handles.lock().await;
+            if index >= scheduler_client_handles.len() {
+                return Err(error::Client::Missing);
+            }
+            scheduler_client_handles.remove(index).client_handle
+        };
+        // Immediately notify about client removal from the group
+        self.event_sender.notify_event(ClientEvent::ClientRemoved {
+            group: self.group_index,
+            index,
+        });
+        // Remove event sender not to notify about removed client status changes
+        client_handle.take_event_sender();
+        // Let in-flight solutions drain instead of discarding them immediately
+        let _ = client_handle.try_disable_graceful(drain_timeout).await;
+        Ok(client_handle)
     }
 
     /// Changes the position of a client within the group
@@ -408,8 +537,16 @@ impl Group {
         }
 
         let client_handle = scheduler_client_handles[index_to].client_handle.clone();
+        // Refresh the moved client's own (group, index) so its future `ClientStatusChanged`
+        // events carry its new position; clients merely shifted by the move keep reporting their
+        // old index until they themselves are next added, removed or moved
+        client_handle.set_event_sender(self.event_sender.clone(), self.group_index, index_to);
         // Immediately notify about client move in the group
-        self.event_sender.notify();
+        self.event_sender.notify_event(ClientEvent::ClientMoved {
+            group: self.group_index,
+            from: index_from,
+            to: index_to,
+        });
 
         Ok(client_handle)
     }
@@ -426,6 +563,19 @@ impl Group {
             })
             .map(|scheduler_client_handle| scheduler_client_handle.client_handle.clone())
     }
+
+    /// For a `LoadBalanceStrategy::Priority` group: the highest-priority (lowest-index) client
+    /// that's currently `is_running()` - the only one that should be handed a live work generator.
+    /// Returns `None` if every client in the group is disabled or disconnected, in which case the
+    /// scheduler should leave all of them without generated work until one comes back up.
+    pub async fn active_priority_client(&self) -> Option<Arc<Handle>> {
+        self.scheduler_client_handles
+            .lock()
+            .await
+            .iter()
+            .map(|scheduler_client_handle| scheduler_client_handle.client_handle.clone())
+            .find(|client_handle| client_handle.is_running())
+    }
 }
 
 /// Keeps track of all active clients
@@ -468,21 +618,25 @@ impl GroupRegistry {
         self.list.iter_mut()
     }
 
-    /// Creates a new group that handles clients connected to pools that support `midstate_count`
-    /// of midstates.
-    /// TODO: once this functionality is available through the API, we should review arbitrary
-    ///  recalculation of quotas
-    pub fn create_group(
+    /// Accounts `strategy`'s effect on `total_quota`/`fixed_share_ratio_count`/
+    /// `total_fixed_share_ratio`, rejecting a `FixedShareRatio` that would overflow the total
+    /// fixed share ratio to/past 1.0, or that would leave none of `other_groups` (every group
+    /// besides the one `strategy` is being assigned to) to carry the remaining share. `Priority`
+    /// carries no proportional share of its own, so it needs no bookkeeping here.
+    fn account_strategy(
         &mut self,
-        descriptor: GroupDescriptor,
-        midstate_count: usize,
-    ) -> Result<Arc<Group>, error::Client> {
-        match descriptor.strategy() {
+        strategy: LoadBalanceStrategy,
+        other_groups: usize,
+    ) -> Result<(), error::Client> {
+        match strategy {
             LoadBalanceStrategy::Quota(quota) => {
                 self.total_quota += quota;
             }
             LoadBalanceStrategy::FixedShareRatio(fixed_share_ratio) => {
-                if self.is_empty() {
+                // `fixed_share_ratio_count` only counts other groups here (the assigned group's
+                // previous contribution has been un-accounted), so this rejects when every other
+                // group is itself `FixedShareRatio` - including when there are no other groups
+                if other_groups <= self.fixed_share_ratio_count {
                     Err(error::Client::OnlyFixedShareRatio)?;
                 } else if self.total_fixed_share_ratio + fixed_share_ratio >= 1.0 {
                     Err(error::Client::FixedShareRatioOverflow)?;
@@ -490,20 +644,146 @@ impl GroupRegistry {
                 self.fixed_share_ratio_count += 1;
                 self.total_fixed_share_ratio += fixed_share_ratio;
             }
+            LoadBalanceStrategy::Priority => {}
+        }
+        Ok(())
+    }
+
+    /// Undoes `account_strategy`'s bookkeeping for a group that's being re-assigned or removed
+    fn unaccount_strategy(&mut self, strategy: LoadBalanceStrategy) {
+        match strategy {
+            LoadBalanceStrategy::Quota(quota) => {
+                self.total_quota -= quota;
+            }
+            LoadBalanceStrategy::FixedShareRatio(fixed_share_ratio) => {
+                self.fixed_share_ratio_count -= 1;
+                self.total_fixed_share_ratio -= fixed_share_ratio;
+            }
+            LoadBalanceStrategy::Priority => {}
         }
+    }
 
+    /// Creates a new group that handles clients connected to pools that support `midstate_count`
+    /// of midstates.
+    pub fn create_group(
+        &mut self,
+        descriptor: GroupDescriptor,
+        midstate_count: usize,
+    ) -> Result<Arc<Group>, error::Client> {
+        self.account_strategy(descriptor.strategy(), self.count())?;
+
+        let index = self.count();
         let group_handle = Arc::new(Group::new(
             descriptor,
             self.event_monitor.publish(),
             midstate_count,
+            index,
         ));
         let scheduler_group_handle = scheduler::GroupHandle::new(group_handle.clone());
         self.list.push(scheduler_group_handle);
         self.recalculate_quotas(true);
+        group_handle
+            .event_sender
+            .notify_event(ClientEvent::GroupCreated { index });
 
         Ok(group_handle)
     }
 
+    /// Changes group `index`'s load-balance strategy at runtime, re-running the same validation
+    /// `create_group` does before committing the change, then calls `recalculate_quotas`.
+    /// Resets every group's generated work, since the relative weight of every group shifts -
+    /// keeping the old `generated_work` totals around would otherwise starve groups that didn't
+    /// themselves change under the previous ratio's accumulated error.
+    pub fn change_group_strategy(
+        &mut self,
+        index: usize,
+        strategy: LoadBalanceStrategy,
+    ) -> Result<(), error::Client> {
+        let old_strategy = self
+            .list
+            .get(index)
+            .ok_or(error::Client::Missing)?
+            .strategy();
+
+        // tentatively undo the old contribution so the new one is validated against the totals
+        // it would actually leave behind
+        self.unaccount_strategy(old_strategy);
+        if let Err(err) = self.account_strategy(strategy, self.count() - 1) {
+            // the change didn't happen, restore the un-applied old contribution
+            self.account_strategy(old_strategy, self.count() - 1)
+                .expect("BUG: re-accounting the previous strategy must always succeed");
+            return Err(err);
+        }
+
+        self.list[index].set_strategy(strategy);
+        self.recalculate_quotas(true);
+        // notify the group's own subscribers, same as a client addition/removal would
+        self.list[index].group_handle.event_sender.notify();
+
+        Ok(())
+    }
+
+    /// Removes group `index` entirely, un-accounting its quota/fixed-share-ratio contribution.
+    /// Unlike a strategy change, a pure removal doesn't reset other groups' `generated_work` -
+    /// their relative weights are unaffected. Rejects the removal if it would leave only
+    /// `FixedShareRatio` groups with nothing left to carry the remaining share.
+    pub fn remove_group(&mut self, index: usize) -> Result<(), error::Client> {
+        let removed_strategy = self
+            .list
+            .get(index)
+            .ok_or(error::Client::Missing)?
+            .strategy();
+
+        let remaining_count = self.count() - 1;
+        let mut remaining_fixed_share_ratio_count = self.fixed_share_ratio_count;
+        if matches!(removed_strategy, LoadBalanceStrategy::FixedShareRatio(_)) {
+            remaining_fixed_share_ratio_count -= 1;
+        }
+        if remaining_count > 0 && remaining_fixed_share_ratio_count >= remaining_count {
+            Err(error::Client::OnlyFixedShareRatio)?;
+        }
+
+        self.unaccount_strategy(removed_strategy);
+        let removed = self.list.remove(index);
+        if !self.is_empty() {
+            self.recalculate_quotas(false);
+        }
+        // notify the removed group's own subscribers that it's gone
+        removed.group_handle.event_sender.notify();
+
+        Ok(())
+    }
+
+    /// Declares group `index` "cluster-shared": its `Quota` is no longer honored against this
+    /// node's own instances alone, but against every live member of `cluster` - see
+    /// `cluster::ClusterHandle` for the gossip membership/failure-detection this divides by.
+    /// Passing `None` reverts the group back to being honored locally only. Either way, every
+    /// group's `share_ratio` is recalculated and, since the divisor just changed for this group,
+    /// its `generated_work` is reset so it doesn't dump a burst of catch-up work on the pool.
+    pub fn set_group_cluster(
+        &mut self,
+        index: usize,
+        cluster: Option<ClusterHandle>,
+    ) -> Result<(), error::Client> {
+        self.list
+            .get_mut(index)
+            .ok_or(error::Client::Missing)?
+            .set_cluster(cluster);
+        self.recalculate_quotas(true);
+
+        Ok(())
+    }
+
+    /// Called whenever `cluster::ClusterHandle`'s gossip layer observes group `index`'s cluster
+    /// membership change (a peer joined, or was evicted after missing its ping rounds) - divides
+    /// the aggregate quota across the new member count and resets `generated_work` so the
+    /// remaining/surviving nodes don't burst catch-up work at the pool.
+    pub fn on_cluster_membership_changed(&mut self, index: usize) {
+        if self.list.get(index).is_some() {
+            self.recalculate_quotas(true);
+        }
+    }
+
     pub fn get_groups(&self) -> Vec<Arc<Group>> {
         self.list
             .iter()
@@ -549,15 +829,27 @@ impl GroupRegistry {
         // Update all groups with newly calculated share ratio.
         // Also reset generated work to prevent switching all future work to new group because
         // new group has zero shares and so maximal error.
+        //
+        // `Priority` groups are skipped entirely: they don't carry a proportional share at all,
+        // the scheduler instead hands 100% of generated work to whichever client within them is
+        // `active_priority_client()`.
         for mut scheduler_group_handle in self.list.iter_mut() {
             if reset_generated_work {
                 scheduler_group_handle.reset_generated_work();
             }
-            if !scheduler_group_handle.has_fixed_share_ratio() {
-                scheduler_group_handle.share_ratio = share_ratio_per_quota_unit
+            if !scheduler_group_handle.has_fixed_share_ratio()
+                && !scheduler_group_handle.is_priority()
+            {
+                let mut share_ratio = share_ratio_per_quota_unit
                     * scheduler_group_handle
                         .get_quota()
                         .expect("BUG: missing group quota") as f64;
+                // A cluster-shared group's quota is honored by the cluster as a whole, so this
+                // node only ever sends its own fraction of it
+                if let Some(cluster) = scheduler_group_handle.cluster() {
+                    share_ratio /= cluster.member_count() as f64;
+                }
+                scheduler_group_handle.share_ratio = share_ratio;
             }
         }
     }
@@ -612,6 +904,9 @@ impl Manager {
         Ok(())
     }
 
+    /// `event::Receiver` yields a `ClientEvent` for every group/client addition, removal, move and
+    /// status change, plus a payload-less wake-up for anything else the event monitor forwards -
+    /// see `ClientEvent` for what can be relied on to carry a concrete delta.
     #[inline]
     pub fn subscribe_to_clients_status_changes(&self) -> event::Receiver {
         self.event_monitor.subscribe()
@@ -650,4 +945,102 @@ impl Manager {
     pub async fn get_groups(&self) -> Vec<Arc<Group>> {
         self.group_registry.lock().await.get_groups()
     }
+
+    /// Change group `index`'s quota at runtime - see `GroupRegistry::change_group_strategy`
+    #[inline]
+    pub async fn set_group_quota(&self, index: usize, quota: usize) -> Result<(), error::Client> {
+        self.group_registry
+            .lock()
+            .await
+            .change_group_strategy(index, LoadBalanceStrategy::Quota(quota))
+    }
+
+    /// Change group `index`'s load-balance strategy at runtime - see
+    /// `GroupRegistry::change_group_strategy`
+    #[inline]
+    pub async fn change_group_strategy(
+        &self,
+        index: usize,
+        strategy: LoadBalanceStrategy,
+    ) -> Result<(), error::Client> {
+        self.group_registry
+            .lock()
+            .await
+            .change_group_strategy(index, strategy)
+    }
+
+    /// Remove group `index` at runtime - see `GroupRegistry::remove_group`
+    #[inline]
+    pub async fn remove_group(&self, index: usize) -> Result<(), error::Client> {
+        self.group_registry.lock().await.remove_group(index)
+    }
+
+    /// Declare group `index` cluster-shared (or revert it to local-only with `None`) - see
+    /// `GroupRegistry::set_group_cluster`
+    #[inline]
+    pub async fn set_group_cluster(
+        &self,
+        index: usize,
+        cluster: Option<ClusterHandle>,
+    ) -> Result<(), error::Client> {
+        self.group_registry
+            .lock()
+            .await
+            .set_group_cluster(index, cluster)
+    }
+
+    /// The entry point a config option or admin command calls to actually turn group `index`
+    /// into a cluster-shared one: binds `local_addr`, starts gossiping with `seed_peers` via
+    /// `cluster::ClusterHandle::join`, declares the group cluster-shared with the resulting
+    /// handle (see `set_group_cluster`), and spawns a task that forwards every membership-changed
+    /// notification from the gossip layer into `on_cluster_membership_changed` for as long as the
+    /// cluster runs.
+    pub async fn join_cluster(
+        &self,
+        index: usize,
+        local_addr: SocketAddr,
+        seed_peers: Vec<SocketAddr>,
+    ) -> error::Result<()> {
+        let (cluster, mut membership_events) = ClusterHandle::join(local_addr, seed_peers).await?;
+        self.set_group_cluster(index, Some(cluster))
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let manager = self.clone();
+        tokio::spawn(async move {
+            while membership_events.next().await.is_some() {
+                manager.on_cluster_membership_changed(index).await;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Forward group `index`'s `cluster::ClusterHandle` membership-changed notification - see
+    /// `GroupRegistry::on_cluster_membership_changed`
+    #[inline]
+    pub async fn on_cluster_membership_changed(&self, index: usize) {
+        self.group_registry
+            .lock()
+            .await
+            .on_cluster_membership_changed(index)
+    }
+
+    /// Gracefully disables every client in every group concurrently, for a clean process shutdown
+    /// instead of letting `Drop` abort each client's connection mid-flight. Each client gets up to
+    /// `drain_timeout` to submit and acknowledge solutions to its last jobs - see
+    /// `Handle::try_disable_graceful`.
+    pub async fn shutdown(&self, drain_timeout: Duration) {
+        let groups = self.get_groups().await;
+        future::join_all(groups.into_iter().map(|group| async move {
+            let clients = group.get_clients().await;
+            future::join_all(
+                clients
+                    .into_iter()
+                    .map(|client| async move { client.try_disable_graceful(drain_timeout).await }),
+            )
+            .await;
+        }))
+        .await;
+    }
 }
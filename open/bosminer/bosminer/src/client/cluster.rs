@@ -0,0 +1,386 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! SWIM-style gossip membership/failure-detection for "cluster-shared" groups (see
+//! `super::GroupRegistry::set_group_cluster`). Each `ClusterHandle` periodically pings a random
+//! known peer over UDP; a peer that misses a direct ping is given a second chance via a handful
+//! of random relays probing it indirectly before it's marked `Suspect`, and evicted only after it
+//! stays unreachable for `SUSPECT_TIMEOUT`. `member_count` - the only thing
+//! `GroupRegistry::recalculate_quotas` actually consumes - is always `1` (this node) plus the
+//! members currently believed `Alive`. Peers are discovered passively: any datagram from an
+//! address we don't yet know adds it to the member list, so a freshly-joined node only needs the
+//! address of one existing member to eventually become known to the whole cluster.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::channel::{mpsc, oneshot};
+use ii_logging::macros::*;
+use rand::seq::IteratorRandom;
+use serde::{Deserialize, Serialize};
+use tokio::net::UdpSocket;
+use tokio::time::delay_for;
+
+use crate::error;
+
+/// How often a node pings one randomly chosen member
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+/// How long to wait for a direct or indirect ping to be acknowledged before giving up on it
+const PING_TIMEOUT: Duration = Duration::from_millis(500);
+/// How many random relays are asked to indirectly probe a peer that missed a direct ping
+const INDIRECT_PROBE_COUNT: usize = 3;
+/// How long a peer may stay `Suspect` (unreachable, but not yet evicted) before it's dropped from
+/// the member list and `generated_work` is reset to avoid a burst of pool catch-up work
+const SUSPECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Large enough for any `Message` variant; gossip messages carry no application payload beyond a
+/// nonce, an address and a pair of counters, so this is generous headroom rather than a tight fit
+const MAX_MESSAGE_LEN: usize = 1024;
+
+/// Wire format gossiped between cluster members over UDP
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Message {
+    /// Direct liveness probe, acknowledged with `Ack` carrying the same `nonce`
+    Ping { nonce: u64 },
+    /// Acknowledges a `Ping` (sent by `target` itself) or a successful indirect probe (sent by the
+    /// relay on `target`'s behalf) - either way it carries `target`'s latest
+    /// generated_work/accepted-share counters
+    Ack { nonce: u64, counters: (u64, u64) },
+    /// Asks the receiver to probe `target` on the sender's behalf and, if it answers, forward an
+    /// `Ack { nonce, .. }` back to the sender
+    PingReq { nonce: u64, target: SocketAddr },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MemberState {
+    Alive,
+    /// Missed its last direct+indirect probe round; evicted once `since.elapsed() >=
+    /// SUSPECT_TIMEOUT` without being refuted by a later successful probe
+    Suspect {
+        since: Instant,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Member {
+    state: MemberState,
+    /// Latest generated_work/accepted-share counters this peer has reported for the shared group
+    counters: (u64, u64),
+}
+
+struct Inner {
+    local_addr: SocketAddr,
+    socket: tokio::sync::Mutex<UdpSocket>,
+    members: Mutex<HashMap<SocketAddr, Member>>,
+    counters: Mutex<(u64, u64)>,
+    pending: Mutex<HashMap<u64, oneshot::Sender<(u64, u64)>>>,
+    next_nonce: AtomicU64,
+    membership_events: mpsc::UnboundedSender<()>,
+}
+
+/// Cheaply cloneable handle onto a running gossip membership session - see the module
+/// documentation for the failure-detection algorithm
+#[derive(Clone)]
+pub struct ClusterHandle {
+    inner: Arc<Inner>,
+}
+
+impl ClusterHandle {
+    /// Binds `local_addr` and starts gossiping with `seed_peers` (initially assumed `Alive`;
+    /// unreachable seeds are simply suspected and evicted like any other member). Returns the
+    /// handle together with a receiver that's notified every time the member list changes (a peer
+    /// was discovered or evicted) - the caller is expected to forward these notifications into
+    /// `super::Manager::on_cluster_membership_changed` for the group this cluster backs.
+    pub async fn join(
+        local_addr: SocketAddr,
+        seed_peers: Vec<SocketAddr>,
+    ) -> error::Result<(Self, mpsc::UnboundedReceiver<()>)> {
+        let socket = UdpSocket::bind(local_addr).await?;
+        let members = seed_peers
+            .into_iter()
+            .filter(|peer| *peer != local_addr)
+            .map(|peer| {
+                (
+                    peer,
+                    Member {
+                        state: MemberState::Alive,
+                        counters: (0, 0),
+                    },
+                )
+            })
+            .collect();
+        let (membership_events, membership_event_receiver) = mpsc::unbounded();
+
+        let inner = Arc::new(Inner {
+            local_addr,
+            socket: tokio::sync::Mutex::new(socket),
+            members: Mutex::new(members),
+            counters: Mutex::new((0, 0)),
+            pending: Mutex::new(HashMap::new()),
+            next_nonce: AtomicU64::new(0),
+            membership_events,
+        });
+
+        tokio::spawn(Self::recv_task(inner.clone()));
+        tokio::spawn(Self::gossip_task(inner.clone()));
+
+        Ok((Self { inner }, membership_event_receiver))
+    }
+
+    /// Live cluster size as seen from this node: itself plus every peer currently believed
+    /// `Alive` - the divisor `GroupRegistry::recalculate_quotas` applies to a cluster-shared
+    /// group's `share_ratio`
+    pub fn member_count(&self) -> usize {
+        1 + self
+            .inner
+            .members
+            .lock()
+            .expect("BUG: cluster lock poisoned")
+            .values()
+            .filter(|member| matches!(member.state, MemberState::Alive))
+            .count()
+    }
+
+    /// Records this node's latest generated_work/accepted-share counters for the group this
+    /// cluster tracks, so the next ping/ack this node answers advertises them to peers
+    pub fn record_counters(&self, generated_work: u64, accepted_shares: u64) {
+        *self
+            .inner
+            .counters
+            .lock()
+            .expect("BUG: cluster lock poisoned") = (generated_work, accepted_shares);
+    }
+
+    async fn send_to(inner: &Arc<Inner>, target: SocketAddr, message: &Message) {
+        let bytes = match bincode::serialize(message) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                warn!("Cluster: failed to encode {:?}: {}", message, error);
+                return;
+            }
+        };
+        if let Err(error) = inner.socket.lock().await.send_to(&bytes, target).await {
+            warn!("Cluster: send to {} failed: {}", target, error);
+        }
+    }
+
+    /// Sends a direct `Ping` to `target` and waits up to `PING_TIMEOUT` for its `Ack`
+    async fn ping(inner: &Arc<Inner>, target: SocketAddr) -> Option<(u64, u64)> {
+        let nonce = inner.next_nonce.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        inner
+            .pending
+            .lock()
+            .expect("BUG: cluster lock poisoned")
+            .insert(nonce, tx);
+        Self::send_to(inner, target, &Message::Ping { nonce }).await;
+
+        let result = tokio::time::timeout(PING_TIMEOUT, rx).await;
+        inner
+            .pending
+            .lock()
+            .expect("BUG: cluster lock poisoned")
+            .remove(&nonce);
+        result.ok().and_then(|received| received.ok())
+    }
+
+    /// Pings `target` directly; on timeout, asks a few random other members to probe it
+    /// indirectly before giving up. Mirrors SWIM's direct-ping-then-indirect-probe round.
+    async fn probe(inner: &Arc<Inner>, target: SocketAddr) -> Option<(u64, u64)> {
+        if let Some(counters) = Self::ping(inner, target).await {
+            return Some(counters);
+        }
+
+        let relays: Vec<_> = {
+            let members = inner.members.lock().expect("BUG: cluster lock poisoned");
+            members
+                .keys()
+                .copied()
+                .filter(|peer| *peer != target)
+                .choose_multiple(&mut rand::thread_rng(), INDIRECT_PROBE_COUNT)
+        };
+        if relays.is_empty() {
+            return None;
+        }
+
+        let nonce = inner.next_nonce.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        inner
+            .pending
+            .lock()
+            .expect("BUG: cluster lock poisoned")
+            .insert(nonce, tx);
+        for relay in relays {
+            Self::send_to(inner, relay, &Message::PingReq { nonce, target }).await;
+        }
+
+        let result = tokio::time::timeout(PING_TIMEOUT, rx).await;
+        inner
+            .pending
+            .lock()
+            .expect("BUG: cluster lock poisoned")
+            .remove(&nonce);
+        result.ok().and_then(|received| received.ok())
+    }
+
+    /// Adds a never-seen-before peer to the member list as `Alive` - how membership spreads
+    /// without a full membership exchange: any datagram from an unknown address is treated as an
+    /// implicit join announcement
+    fn discover(inner: &Arc<Inner>, peer: SocketAddr) {
+        if peer == inner.local_addr {
+            return;
+        }
+        let mut members = inner.members.lock().expect("BUG: cluster lock poisoned");
+        if members.contains_key(&peer) {
+            return;
+        }
+        info!("Cluster: discovered new member {}", peer);
+        members.insert(
+            peer,
+            Member {
+                state: MemberState::Alive,
+                counters: (0, 0),
+            },
+        );
+        let _ = inner.membership_events.unbounded_send(());
+    }
+
+    fn mark_alive(inner: &Arc<Inner>, peer: SocketAddr, counters: (u64, u64)) {
+        let mut members = inner.members.lock().expect("BUG: cluster lock poisoned");
+        let is_new = !members.contains_key(&peer);
+        let member = members.entry(peer).or_insert_with(|| Member {
+            state: MemberState::Alive,
+            counters,
+        });
+        if matches!(member.state, MemberState::Suspect { .. }) {
+            info!("Cluster: {} refuted suspicion, marked alive", peer);
+        }
+        member.state = MemberState::Alive;
+        member.counters = counters;
+        drop(members);
+
+        if is_new {
+            let _ = inner.membership_events.unbounded_send(());
+        }
+    }
+
+    /// Marks `peer` `Suspect` on its first missed probe round, or evicts it once it has stayed
+    /// `Suspect` for `SUSPECT_TIMEOUT`
+    fn mark_suspect_or_evict(inner: &Arc<Inner>, peer: SocketAddr) {
+        let mut members = inner.members.lock().expect("BUG: cluster lock poisoned");
+        let evict = match members.get_mut(&peer) {
+            Some(member) => match member.state {
+                MemberState::Alive => {
+                    warn!("Cluster: {} missed its ping round, marking suspect", peer);
+                    member.state = MemberState::Suspect {
+                        since: Instant::now(),
+                    };
+                    false
+                }
+                MemberState::Suspect { since } => since.elapsed() >= SUSPECT_TIMEOUT,
+            },
+            None => false,
+        };
+        if evict {
+            warn!("Cluster: {} evicted after missing ping rounds", peer);
+            members.remove(&peer);
+        }
+        drop(members);
+
+        if evict {
+            let _ = inner.membership_events.unbounded_send(());
+        }
+    }
+
+    async fn recv_task(inner: Arc<Inner>) {
+        let mut buf = [0u8; MAX_MESSAGE_LEN];
+        loop {
+            let (len, from) = {
+                let mut socket = inner.socket.lock().await;
+                match socket.recv_from(&mut buf).await {
+                    Ok(received) => received,
+                    Err(error) => {
+                        warn!("Cluster: recv failed: {}", error);
+                        continue;
+                    }
+                }
+            };
+            let message: Message = match bincode::deserialize(&buf[..len]) {
+                Ok(message) => message,
+                Err(error) => {
+                    warn!("Cluster: malformed message from {}: {}", from, error);
+                    continue;
+                }
+            };
+            Self::discover(&inner, from);
+
+            match message {
+                Message::Ping { nonce } => {
+                    let counters = *inner.counters.lock().expect("BUG: cluster lock poisoned");
+                    Self::send_to(&inner, from, &Message::Ack { nonce, counters }).await;
+                }
+                Message::Ack { nonce, counters } => {
+                    if let Some(tx) = inner
+                        .pending
+                        .lock()
+                        .expect("BUG: cluster lock poisoned")
+                        .remove(&nonce)
+                    {
+                        let _ = tx.send(counters);
+                    }
+                }
+                Message::PingReq { nonce, target } => {
+                    let inner = inner.clone();
+                    tokio::spawn(async move {
+                        if let Some(counters) = Self::ping(&inner, target).await {
+                            Self::send_to(&inner, from, &Message::Ack { nonce, counters }).await;
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    /// Drives the periodic direct-ping-then-indirect-probe round against one randomly chosen
+    /// member per tick
+    async fn gossip_task(inner: Arc<Inner>) {
+        loop {
+            delay_for(PING_INTERVAL).await;
+
+            let target = {
+                let members = inner.members.lock().expect("BUG: cluster lock poisoned");
+                members.keys().copied().choose(&mut rand::thread_rng())
+            };
+            let target = match target {
+                Some(target) => target,
+                None => continue,
+            };
+
+            match Self::probe(&inner, target).await {
+                Some(counters) => Self::mark_alive(&inner, target, counters),
+                None => Self::mark_suspect_or_evict(&inner, target),
+            }
+        }
+    }
+}
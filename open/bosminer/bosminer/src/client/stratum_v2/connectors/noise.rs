@@ -0,0 +1,50 @@
+//! Adapter module for connecting to Stratum V2 endpoints secured with the Noise protocol - the
+//! encrypted counterpart to `insecure`, which only speaks plaintext. Performs the Stratum V2
+//! Noise handshake (the NX pattern on first contact, falling back to the cached XK pattern once
+//! the upstream's static key has already been learned) over Noise_25519_ChaChaPoly_BLAKE2s,
+//! verifying the upstream against its known static public key before handing back the same
+//! framed sink/stream pair `insecure::Connector` produces.
+use std::pin::Pin;
+
+use tokio::net::TcpStream;
+
+use ii_async_compat::prelude::*;
+use ii_logging::macros::*;
+use ii_stratum::v2;
+use ii_stratum::v2::noise;
+
+use crate::error;
+
+/// The upstream's Noise static public key (Curve25519), supplied at construction and verified
+/// during the handshake - a connection to an upstream presenting any other key is rejected.
+pub(crate) type AuthorityPublicKey = [u8; 32];
+
+#[derive(Clone)]
+pub(crate) struct Connector {
+    upstream_authority_public_key: AuthorityPublicKey,
+}
+
+impl Connector {
+    pub fn new(upstream_authority_public_key: AuthorityPublicKey) -> Self {
+        Self {
+            upstream_authority_public_key,
+        }
+    }
+
+    pub async fn connect(
+        self,
+        connection: TcpStream,
+    ) -> error::Result<(v2::DynFramedSink, v2::DynFramedStream)> {
+        trace!("Stratum V2 noise connector: {:?}", connection);
+        let noise_connection =
+            noise::Initiator::handshake(connection, &self.upstream_authority_public_key).await?;
+        let noise_framed_connection =
+            ii_wire::Connection::<v2::Framing>::new(noise_connection).into_inner();
+        let (noise_sink, noise_stream) = noise_framed_connection.split();
+        Ok((Pin::new(Box::new(noise_sink)), noise_stream.boxed()))
+    }
+
+    pub fn into_connector_fn(self) -> super::DynConnectFn {
+        Box::new(move |connection| self.connect(connection).boxed())
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Framing for the CGMiner API wire protocol: each request/response is a single JSON document
+//! terminated by a `\0` byte on an otherwise plain TCP stream. `Codec` buffers incoming bytes
+//! until a complete, NUL-delimited frame is available - only then does it attempt UTF-8 and JSON
+//! decoding, so a read boundary landing mid-message (including mid- multi-byte UTF-8 character)
+//! just means waiting for more bytes rather than a spurious error.
+
+use crate::request::Command;
+use crate::support;
+
+use bytes::{Buf, BytesMut};
+use serde_json as json;
+use std::io;
+use std::str;
+use thiserror::Error;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Frames are delimited by a single NUL byte, per the CGMiner API protocol.
+const FRAME_TERMINATOR: u8 = 0;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("frame is not valid UTF-8: {0}")]
+    InvalidUtf8(#[from] str::Utf8Error),
+    #[error("frame is not valid JSON: {0}")]
+    InvalidJson(#[from] json::Error),
+}
+
+/// Tokio codec for the CGMiner API protocol: decodes `Command` requests, encodes
+/// `support::SingleResponse` replies.
+#[derive(Default)]
+pub struct Codec;
+
+impl Decoder for Codec {
+    type Item = Command;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let frame_len = match src.iter().position(|&byte| byte == FRAME_TERMINATOR) {
+            Some(pos) => pos,
+            // no full frame buffered yet - wait for more bytes, even if that means the boundary
+            // fell in the middle of a multi-byte UTF-8 character
+            None => return Ok(None),
+        };
+
+        let frame = src.split_to(frame_len);
+        src.advance(1); // drop the terminator itself
+
+        let text = str::from_utf8(&frame)?;
+        let command = json::from_str(text)?;
+        Ok(Some(command))
+    }
+}
+
+impl Encoder<support::SingleResponse> for Codec {
+    type Error = Error;
+
+    fn encode(
+        &mut self,
+        item: support::SingleResponse,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        let text = json::to_string(&item)?;
+        dst.extend_from_slice(text.as_bytes());
+        dst.extend_from_slice(&[FRAME_TERMINATOR]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::request::Command;
+
+    /// Feed a codec every byte of `data` in its own `decode` call, as if each byte arrived in a
+    /// separate `read`, collecting whatever items fall out along the way.
+    fn decode_byte_by_byte(data: &[u8]) -> Vec<Command> {
+        let mut codec = Codec;
+        let mut buf = BytesMut::new();
+        let mut items = Vec::new();
+
+        for &byte in data {
+            buf.extend_from_slice(&[byte]);
+            while let Some(item) = codec.decode(&mut buf).unwrap() {
+                items.push(item);
+            }
+        }
+        items
+    }
+
+    #[test]
+    fn test_decode_one_message_split_across_single_byte_reads() {
+        let items = decode_byte_by_byte(b"{\"command\":\"pools\"}\0");
+        assert_eq!(items, vec![Command::Pools]);
+    }
+
+    #[test]
+    fn test_decode_tolerates_multi_byte_utf8_character_split_across_reads() {
+        // the pool URL below contains a multi-byte UTF-8 character (é, encoded as 2 bytes);
+        // feeding it one byte at a time must not panic or error on the byte that lands in the
+        // middle of that character - only once the full, valid frame is buffered
+        let message =
+            "{\"command\":\"addpool\",\"parameter\":{\"url\":\"é.example\",\"user\":\"u\",\"pass\":\"p\"}}\0";
+        let items = decode_byte_by_byte(message.as_bytes());
+        assert_eq!(
+            items,
+            vec![Command::AddPool {
+                url: "é.example".to_string(),
+                user: "u".to_string(),
+                pass: "p".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_waits_for_terminator_before_attempting_to_parse() {
+        let mut codec = Codec;
+        let mut buf = BytesMut::new();
+        // deliberately invalid JSON with no terminator yet - must not error, just wait
+        buf.extend_from_slice(b"{not json");
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn test_decode_surfaces_invalid_json_once_frame_is_complete() {
+        let mut codec = Codec;
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"{not json\0");
+        assert!(codec.decode(&mut buf).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Optional `chrono`-backed (de)serialization helpers for the raw UNIX timestamp/duration fields
+//! in `response` (`Time`, `Elapsed`), gated behind the `chrono` cargo feature. Modeled on
+//! shiplift's `datetime_from_unix_timestamp` helper: the wire format never changes (still a plain
+//! integer), only the Rust-side type consumers see does.
+
+#![cfg(feature = "chrono")]
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// `#[serde(with = "datetime::timestamp")]` for fields typed as `response::Time`.
+pub mod timestamp {
+    use super::*;
+
+    pub fn serialize<S>(date: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        date.timestamp().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let timestamp = i64::deserialize(deserializer)?;
+        Ok(DateTime::from_utc(
+            NaiveDateTime::from_timestamp(timestamp, 0),
+            Utc,
+        ))
+    }
+}
+
+/// `#[serde(with = "datetime::duration")]` for fields typed as `response::Elapsed`.
+pub mod duration {
+    use super::*;
+
+    pub fn serialize<S>(elapsed: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        elapsed.num_seconds().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let seconds = i64::deserialize(deserializer)?;
+        Ok(Duration::seconds(seconds))
+    }
+}
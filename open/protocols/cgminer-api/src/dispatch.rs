@@ -0,0 +1,135 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Ties the inbound `request::Command` parser to handlers, DAP-style: a `Dispatcher` is just a
+//! registry mapping a command's name (the same literal `request::Command::from_str` matches
+//! against, e.g. `"switchpool"`) to a handler closure, instead of one big match statement grown
+//! at the call site. A handler receives the already-parsed, already-typed `Command` variant - so
+//! missing/malformed parameters are rejected by the parser before any handler runs - and produces
+//! a `Dispatch` directly, which means existing conversions like `From<Coin>`/`From<Lcd>` plug in
+//! as a handler body unchanged (`|_command| coin.clone().into()`).
+
+use crate::request::Command;
+use crate::response::{Dispatch, ErrorCode};
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// A single command handler: given the parsed `Command`, produce the `Dispatch` to send back.
+trait Handler {
+    fn call(&self, command: Command) -> Dispatch;
+}
+
+impl<F> Handler for F
+where
+    F: Fn(Command) -> Dispatch,
+{
+    fn call(&self, command: Command) -> Dispatch {
+        self(command)
+    }
+}
+
+/// Registry of command handlers, keyed by command name. Build one at startup with `register`,
+/// then feed it raw command lines (`"switchpool|2"` or bare `"pools"`) via `dispatch`.
+#[derive(Default)]
+pub struct Dispatcher {
+    handlers: HashMap<&'static str, Box<dyn Handler>>,
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `handler` to run whenever `dispatch` sees `name` as the command name. Re-
+    /// registering the same `name` replaces the previous handler.
+    pub fn register<F>(&mut self, name: &'static str, handler: F)
+    where
+        F: Fn(Command) -> Dispatch + 'static,
+    {
+        self.handlers.insert(name, Box::new(handler));
+    }
+
+    /// Parse `line` as a legacy pipe-delimited command (see `Command::from_str`) and route it to
+    /// the handler registered for its command name, producing a structured error `Dispatch`
+    /// (reusing `ErrorCode`/`Status`/`StatusInfo`) if the command name is unknown to this
+    /// dispatcher or the parameters don't parse.
+    pub fn dispatch(&self, line: &str) -> Dispatch {
+        let name = line.splitn(2, '|').next().unwrap_or("");
+
+        match self.handlers.get(name) {
+            Some(handler) => match Command::from_str(line) {
+                Ok(command) => handler.call(command),
+                Err(err) => err.into(),
+            },
+            None => ErrorCode::InvalidCommand.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::response::{Status, StatusCode, StatusCodeType};
+
+    #[test]
+    fn test_dispatch_reports_unknown_command() {
+        let dispatcher = Dispatcher::new();
+        let dispatch = dispatcher.dispatch("bogus");
+        let response = dispatch.into_response(0, &"sig".to_string(), &"desc".to_string());
+        assert_eq!(response.status_info.status, Status::E);
+        assert_eq!(
+            response.status_info.code,
+            StatusCodeType::Protocol(StatusCode::InvalidCommand)
+        );
+    }
+
+    #[test]
+    fn test_dispatch_reports_missing_parameter_without_running_handler() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register("switchpool", |_command| {
+            panic!("handler must not run when the parameter is missing")
+        });
+
+        let dispatch = dispatcher.dispatch("switchpool");
+        let response = dispatch.into_response(0, &"sig".to_string(), &"desc".to_string());
+        assert_eq!(
+            response.status_info.code,
+            StatusCodeType::Protocol(StatusCode::MissingPoolParameter)
+        );
+    }
+
+    #[test]
+    fn test_dispatch_routes_parsed_command_to_its_handler() {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register("switchpool", |command| match command {
+            Command::SwitchPool { idx } => {
+                Dispatch::from_custom_success::<(), _>(0u32, format!("switched to pool {}", idx), None)
+            }
+            _ => unreachable!(),
+        });
+
+        let dispatch = dispatcher.dispatch("switchpool|3");
+        let response = dispatch.into_response(0, &"sig".to_string(), &"desc".to_string());
+        assert_eq!(response.status_info.msg, "switched to pool 3");
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Pluggable serialization backends for `Dispatch`/`SingleResponse`, so the same `Body<S>` types
+//! can go out as the traditional JSON or, on bandwidth-constrained telemetry links, a compact
+//! binary format. Backend choice is per-connection (pick an implementor of `ResponseEncoder`),
+//! selected at the crate level behind the `serialize_json` (default), `serialize_bincode`, and
+//! `serialize_postcard` cargo features. Self-describing formats (JSON) reuse the existing
+//! `#[serde(rename = ...)]` field tags unchanged; compact formats fall back to field order.
+
+use crate::response::StatusInfo;
+
+use serde::Serialize;
+
+/// A response body already encoded by a `ResponseEncoder`, paired with its CGMiner body name
+/// (`"STATS"`, `"COIN"`, ...) - self-describing formats need the name spelled out, compact ones
+/// ignore it.
+pub struct EncodedBody {
+    pub name: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+/// A pluggable wire format for `Dispatch`. Implementors turn a `Body<S>`'s list into bytes
+/// (`encode_body`), then combine that with the reply's `StatusInfo` header into the bytes that
+/// actually go out on the connection (`encode_response`).
+pub trait ResponseEncoder {
+    type Error;
+
+    fn encode_body<S: Serialize>(
+        &self,
+        name: &'static str,
+        list: &[S],
+    ) -> Result<EncodedBody, Self::Error>;
+
+    fn encode_response(
+        &self,
+        status_info: &StatusInfo,
+        body: Option<&EncodedBody>,
+    ) -> Result<Vec<u8>, Self::Error>;
+}
+
+#[cfg(feature = "serialize_json")]
+pub mod json {
+    use super::*;
+    use serde_json as json;
+
+    /// Default backend: the traditional CGMiner-compatible `{"STATUS": [...], "NAME": [...]}`
+    /// JSON document.
+    #[derive(Default)]
+    pub struct JsonEncoder;
+
+    impl ResponseEncoder for JsonEncoder {
+        type Error = json::Error;
+
+        fn encode_body<S: Serialize>(
+            &self,
+            name: &'static str,
+            list: &[S],
+        ) -> Result<EncodedBody, Self::Error> {
+            Ok(EncodedBody {
+                name,
+                bytes: json::to_vec(list)?,
+            })
+        }
+
+        fn encode_response(
+            &self,
+            status_info: &StatusInfo,
+            body: Option<&EncodedBody>,
+        ) -> Result<Vec<u8>, Self::Error> {
+            let mut map = json::Map::new();
+            map.insert("STATUS".to_string(), json::to_value(&[status_info])?);
+            if let Some(body) = body {
+                let list: json::Value = json::from_slice(&body.bytes)?;
+                map.insert(body.name.to_string(), list);
+            }
+            json::to_vec(&json::Value::Object(map))
+        }
+    }
+}
+
+#[cfg(feature = "serialize_bincode")]
+pub mod bincode {
+    use super::*;
+
+    /// Compact binary backend for bandwidth-constrained telemetry links; field names from
+    /// `#[serde(rename = ...)]` are dropped in favor of declaration order.
+    #[derive(Default)]
+    pub struct BincodeEncoder;
+
+    impl ResponseEncoder for BincodeEncoder {
+        type Error = ::bincode::Error;
+
+        fn encode_body<S: Serialize>(
+            &self,
+            name: &'static str,
+            list: &[S],
+        ) -> Result<EncodedBody, Self::Error> {
+            Ok(EncodedBody {
+                name,
+                bytes: ::bincode::serialize(list)?,
+            })
+        }
+
+        fn encode_response(
+            &self,
+            status_info: &StatusInfo,
+            body: Option<&EncodedBody>,
+        ) -> Result<Vec<u8>, Self::Error> {
+            ::bincode::serialize(&(status_info, body.map(|body| &body.bytes)))
+        }
+    }
+}
+
+#[cfg(feature = "serialize_postcard")]
+pub mod postcard {
+    use super::*;
+
+    /// Compact, `no_std`-friendly binary backend; same field-order tradeoff as `bincode`.
+    #[derive(Default)]
+    pub struct PostcardEncoder;
+
+    impl ResponseEncoder for PostcardEncoder {
+        type Error = ::postcard::Error;
+
+        fn encode_body<S: Serialize>(
+            &self,
+            name: &'static str,
+            list: &[S],
+        ) -> Result<EncodedBody, Self::Error> {
+            Ok(EncodedBody {
+                name,
+                bytes: ::postcard::to_stdvec(list)?,
+            })
+        }
+
+        fn encode_response(
+            &self,
+            status_info: &StatusInfo,
+            body: Option<&EncodedBody>,
+        ) -> Result<Vec<u8>, Self::Error> {
+            ::postcard::to_stdvec(&(status_info, body.map(|body| &body.bytes)))
+        }
+    }
+}
+
+#[cfg(all(
+    test,
+    feature = "serialize_json",
+    not(feature = "serialize_bincode"),
+    not(feature = "serialize_postcard")
+))]
+mod test {
+    use super::json::JsonEncoder;
+    use super::*;
+
+    #[test]
+    fn test_json_encoder_keeps_body_name_and_encodes_list() {
+        let encoder = JsonEncoder::default();
+        let body = encoder.encode_body("COIN", &["not-a-real-coin-struct"]).unwrap();
+        assert_eq!(body.name, "COIN");
+        assert_eq!(body.bytes, br#"["not-a-real-coin-struct"]"#);
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Schema-driven counterparts of a subset of `response`'s body types, generated at build time by
+//! `build.rs` from `schema/responses.json`. These coexist with the hand-written `AscCount`/`Coin`
+//! in `response.rs` rather than replacing them - this module is the first proof that the schema
+//! produces field-for-field identical output (same `#[serde(rename = ...)]` tags, same lenient
+//! deserializers, same `Dispatch` message), not yet the place callers should construct responses
+//! from. Once that's trusted for the rest of `response.rs`'s body types, the hand-written versions
+//! can be deleted in favor of generating all of them this way.
+
+use crate::response::{Difficulty, Interval};
+
+use serde::{Deserialize, Serialize};
+
+include!(concat!(env!("OUT_DIR"), "/generated_responses.rs"));
@@ -0,0 +1,145 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Lenient field deserializers for `response` fields whose wire representation varies across
+//! CGMiner-family firmware: hashrate/difficulty/percentage numbers sometimes arrive as a JSON
+//! number and sometimes as a numeric string, and booleans show up as `true`/`false`, `"Y"`/`"N"`,
+//! or `0`/`1`. Modeled on the per-field `deserialize_with` helpers used by the MikroTik RouterOS
+//! API bindings. Intended to live alongside `support`, whose module isn't part of this checkout.
+
+use serde::de::{self, Deserializer, Unexpected, Visitor};
+use std::fmt;
+
+/// `#[serde(deserialize_with = "lenient::deserialize_f64")]` for the numeric aliases
+/// (`MegaHashes`/`Difficulty`/`Percent`) that some firmware emits as a JSON string.
+pub fn deserialize_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct F64Visitor;
+
+    impl<'de> Visitor<'de> for F64Visitor {
+        type Value = f64;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a number or a numeric string")
+        }
+
+        fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+            Ok(value)
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+            Ok(value as f64)
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+            Ok(value as f64)
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            value
+                .parse()
+                .map_err(|_| de::Error::invalid_value(Unexpected::Str(value), &self))
+        }
+    }
+
+    deserializer.deserialize_any(F64Visitor)
+}
+
+/// `#[serde(deserialize_with = "lenient::deserialize_bool")]` - accepts `true`/`false`, `"Y"`/
+/// `"N"`, and `0`/`1`.
+pub fn deserialize_bool<'de, D>(deserializer: D) -> Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct BoolVisitor;
+
+    impl<'de> Visitor<'de> for BoolVisitor {
+        type Value = bool;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a bool, \"Y\"/\"N\", or 0/1")
+        }
+
+        fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+            Ok(value)
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            match value {
+                0 => Ok(false),
+                1 => Ok(true),
+                other => Err(de::Error::invalid_value(Unexpected::Unsigned(other), &self)),
+            }
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            match value {
+                "Y" => Ok(true),
+                "N" => Ok(false),
+                other => Err(de::Error::invalid_value(Unexpected::Str(other), &self)),
+            }
+        }
+    }
+
+    deserializer.deserialize_any(BoolVisitor)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json as json;
+
+    #[derive(Deserialize)]
+    struct F64Field(#[serde(deserialize_with = "deserialize_f64")] f64);
+
+    #[derive(Deserialize)]
+    struct BoolField(#[serde(deserialize_with = "deserialize_bool")] bool);
+
+    #[test]
+    fn test_deserialize_f64_accepts_number_or_numeric_string() {
+        assert_eq!(json::from_str::<F64Field>("12.5").unwrap().0, 12.5);
+        assert_eq!(json::from_str::<F64Field>("\"12.5\"").unwrap().0, 12.5);
+        assert_eq!(json::from_str::<F64Field>("7").unwrap().0, 7.0);
+    }
+
+    #[test]
+    fn test_deserialize_bool_accepts_bool_y_n_and_0_1() {
+        assert_eq!(json::from_str::<BoolField>("true").unwrap().0, true);
+        assert_eq!(json::from_str::<BoolField>("false").unwrap().0, false);
+        assert_eq!(json::from_str::<BoolField>("\"Y\"").unwrap().0, true);
+        assert_eq!(json::from_str::<BoolField>("\"N\"").unwrap().0, false);
+        assert_eq!(json::from_str::<BoolField>("1").unwrap().0, true);
+        assert_eq!(json::from_str::<BoolField>("0").unwrap().0, false);
+    }
+}
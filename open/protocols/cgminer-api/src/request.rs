@@ -0,0 +1,178 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Defines the CGMiner API *requests* that `response`'s `StatusCode` command variants reply to.
+//! `Command` is an adjacently-tagged enum in the style of cln-rpc's `Request` type, so it
+//! deserializes straight out of the JSON form (`{"command":"switchpool","parameter":{"idx":2}}`); for
+//! the legacy pipe-delimited text form (`"switchpool|2"`) CGMiner's socket API has always also
+//! accepted, use `Command::from_str` instead.
+
+use crate::response::ErrorCode;
+
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "command", content = "parameter", rename_all = "lowercase")]
+pub enum Command {
+    Pools,
+    Devs,
+    Summary,
+    Version,
+    SwitchPool { idx: usize },
+    Config,
+    EnablePool { idx: usize },
+    DisablePool { idx: usize },
+    AddPool { url: String, user: String, pass: String },
+    RemovePool { idx: usize },
+    DevDetails,
+    Stats,
+    Check { cmd: String },
+    Coin,
+    AscCount,
+    Asc { idx: usize },
+    Lcd,
+}
+
+impl FromStr for Command {
+    type Err = ErrorCode;
+
+    /// Parse the legacy pipe-delimited text form, e.g. `"switchpool|2"` or bare `"pools"` for
+    /// commands that take no parameter.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.splitn(2, '|');
+        let command = parts.next().unwrap_or("");
+        let parameter = parts.next();
+
+        match command {
+            "pools" => Ok(Command::Pools),
+            "devs" => Ok(Command::Devs),
+            "summary" => Ok(Command::Summary),
+            "version" => Ok(Command::Version),
+            "switchpool" => Ok(Command::SwitchPool {
+                idx: parse_pool_idx(parameter)?,
+            }),
+            "config" => Ok(Command::Config),
+            "enablepool" => Ok(Command::EnablePool {
+                idx: parse_pool_idx(parameter)?,
+            }),
+            "disablepool" => Ok(Command::DisablePool {
+                idx: parse_pool_idx(parameter)?,
+            }),
+            "addpool" => parse_add_pool(parameter),
+            "removepool" => Ok(Command::RemovePool {
+                idx: parse_pool_idx(parameter)?,
+            }),
+            "devdetails" => Ok(Command::DevDetails),
+            "stats" => Ok(Command::Stats),
+            "check" => Ok(Command::Check {
+                cmd: parameter
+                    .map(str::to_string)
+                    .ok_or(ErrorCode::MissingCheckCmd)?,
+            }),
+            "coin" => Ok(Command::Coin),
+            "asccount" => Ok(Command::AscCount),
+            "asc" => Ok(Command::Asc {
+                idx: parse_asc_idx(parameter)?,
+            }),
+            "lcd" => Ok(Command::Lcd),
+            _ => Err(ErrorCode::InvalidCommand),
+        }
+    }
+}
+
+/// Pool index parameter shared by `switchpool`/`enablepool`/`disablepool`/`removepool` - missing
+/// or non-numeric is reported the same way, the exact valid range is only known once the command
+/// is actually dispatched against the pool list.
+fn parse_pool_idx(parameter: Option<&str>) -> Result<usize, ErrorCode> {
+    parameter
+        .and_then(|parameter| parameter.parse().ok())
+        .ok_or(ErrorCode::MissingPoolParameter)
+}
+
+fn parse_asc_idx(parameter: Option<&str>) -> Result<usize, ErrorCode> {
+    parameter
+        .and_then(|parameter| parameter.parse().ok())
+        .ok_or(ErrorCode::MissingAscParameter)
+}
+
+fn parse_add_pool(parameter: Option<&str>) -> Result<Command, ErrorCode> {
+    let parameter = parameter.ok_or(ErrorCode::MissingAddPoolDetails)?;
+    let mut fields = parameter.splitn(3, ',');
+
+    match (fields.next(), fields.next(), fields.next()) {
+        (Some(url), Some(user), Some(pass)) => Ok(Command::AddPool {
+            url: url.to_string(),
+            user: user.to_string(),
+            pass: pass.to_string(),
+        }),
+        _ => Err(ErrorCode::InvalidAddPoolDetails(parameter.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_str_parses_legacy_pipe_form() {
+        assert_eq!(
+            "switchpool|2".parse::<Command>().unwrap(),
+            Command::SwitchPool { idx: 2 }
+        );
+        assert_eq!("pools".parse::<Command>().unwrap(), Command::Pools);
+        assert_eq!(
+            "addpool|stratum+tcp://pool:3333,user,pass"
+                .parse::<Command>()
+                .unwrap(),
+            Command::AddPool {
+                url: "stratum+tcp://pool:3333".to_string(),
+                user: "user".to_string(),
+                pass: "pass".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_str_reports_missing_and_invalid_parameters() {
+        assert!(matches!(
+            "switchpool".parse::<Command>(),
+            Err(ErrorCode::MissingPoolParameter)
+        ));
+        assert!(matches!(
+            "switchpool|nope".parse::<Command>(),
+            Err(ErrorCode::MissingPoolParameter)
+        ));
+        assert!(matches!(
+            "addpool".parse::<Command>(),
+            Err(ErrorCode::MissingAddPoolDetails)
+        ));
+        assert!(matches!(
+            "addpool|onlyurl".parse::<Command>(),
+            Err(ErrorCode::InvalidAddPoolDetails(_))
+        ));
+        assert!(matches!(
+            "bogus".parse::<Command>(),
+            Err(ErrorCode::InvalidCommand)
+        ));
+    }
+}
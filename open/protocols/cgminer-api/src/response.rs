@@ -24,13 +24,25 @@
 
 pub mod ext;
 
+use crate::binary::HashField;
+#[cfg(feature = "chrono")]
+use crate::datetime;
+use crate::lenient;
 use crate::support;
 
-use serde::{Serialize, Serializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json as json;
+use std::convert::TryFrom;
 
+#[cfg(not(feature = "chrono"))]
 pub type Time = u32;
+#[cfg(feature = "chrono")]
+pub type Time = chrono::DateTime<chrono::Utc>;
+
+#[cfg(not(feature = "chrono"))]
 pub type Elapsed = u64;
+#[cfg(feature = "chrono")]
+pub type Elapsed = chrono::Duration;
 pub type Interval = f64;
 pub type Percent = f64;
 pub type Difficulty = f64;
@@ -43,7 +55,7 @@ pub type Temperature = f64;
 #[allow(dead_code)]
 /// CGMiner API Status indicator.
 /// (warning and info levels not currently used.)
-#[derive(Serialize, Eq, PartialEq, Copy, Clone, Debug)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Copy, Clone, Debug)]
 pub enum Status {
     W,
     I,
@@ -58,6 +70,21 @@ pub enum Bool {
     Y,
 }
 
+impl<'de> Deserialize<'de> for Bool {
+    /// More lenient than the derived impl would be: accepts `true`/`false`, `"Y"`/`"N"`, and
+    /// `0`/`1` - see `lenient::deserialize_bool`.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(if crate::lenient::deserialize_bool(deserializer)? {
+            Bool::Y
+        } else {
+            Bool::N
+        })
+    }
+}
+
 impl<T> From<Option<T>> for Bool {
     fn from(value: Option<T>) -> Self {
         match value {
@@ -68,7 +95,7 @@ impl<T> From<Option<T>> for Bool {
 }
 
 #[allow(dead_code)]
-#[derive(Serialize, Eq, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub enum PoolStatus {
     Disabled,
@@ -79,7 +106,7 @@ pub enum PoolStatus {
 }
 
 #[allow(dead_code)]
-#[derive(Serialize, Eq, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub enum AscStatus {
     Alive,
@@ -91,7 +118,7 @@ pub enum AscStatus {
 }
 
 #[allow(dead_code)]
-#[derive(Serialize, Eq, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub enum MultipoolStrategy {
     Failover,
@@ -166,6 +193,54 @@ impl From<StatusCode> for StatusCodeType {
     }
 }
 
+impl TryFrom<u32> for StatusCode {
+    type Error = u32;
+
+    /// Map a raw status code number back to its named variant, so `StatusCodeType`'s
+    /// `Deserialize` impl below can recover a `Protocol(_)` value. Fails with the unrecognized
+    /// code on anything not listed above (`CustomBase` included - it's only a threshold marker,
+    /// never a status code in its own right).
+    fn try_from(code: u32) -> std::result::Result<Self, Self::Error> {
+        match code {
+            7 => Ok(StatusCode::Pool),
+            9 => Ok(StatusCode::Devs),
+            11 => Ok(StatusCode::Summary),
+            22 => Ok(StatusCode::Version),
+            27 => Ok(StatusCode::SwitchPool),
+            33 => Ok(StatusCode::MineConfig),
+            47 => Ok(StatusCode::EnablePool),
+            48 => Ok(StatusCode::DisablePool),
+            55 => Ok(StatusCode::AddPool),
+            68 => Ok(StatusCode::RemovePool),
+            69 => Ok(StatusCode::DevDetails),
+            70 => Ok(StatusCode::Stats),
+            72 => Ok(StatusCode::Check),
+            78 => Ok(StatusCode::Coin),
+            104 => Ok(StatusCode::AscCount),
+            106 => Ok(StatusCode::Asc),
+            125 => Ok(StatusCode::Lcd),
+            200 => Ok(StatusCode::TempCtrl),
+            201 => Ok(StatusCode::Temps),
+            202 => Ok(StatusCode::Fans),
+            203 => Ok(StatusCode::TunerStatus),
+            49 => Ok(StatusCode::PoolAlreadyEnabled),
+            50 => Ok(StatusCode::PoolAlreadyDisabled),
+            14 => Ok(StatusCode::InvalidCommand),
+            15 => Ok(StatusCode::MissingAscParameter),
+            23 => Ok(StatusCode::InvalidJSON),
+            24 => Ok(StatusCode::MissingCommand),
+            25 => Ok(StatusCode::MissingPoolParameter),
+            26 => Ok(StatusCode::InvalidPoolId),
+            45 => Ok(StatusCode::AccessDeniedCmd),
+            52 => Ok(StatusCode::MissingAddPoolDetails),
+            53 => Ok(StatusCode::InvalidAddPoolDetails),
+            71 => Ok(StatusCode::MissingCheckCmd),
+            107 => Ok(StatusCode::InvalidAscId),
+            other => Err(other),
+        }
+    }
+}
+
 impl Serialize for StatusCodeType {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -179,6 +254,25 @@ impl Serialize for StatusCodeType {
     }
 }
 
+impl<'de> Deserialize<'de> for StatusCodeType {
+    /// Inverse of `Serialize` above: values at or past `CustomBase` become `Custom`, everything
+    /// else is looked up via `TryFrom<u32>` and falls back to `Custom` if it isn't a code we know
+    /// about (e.g. a status code added to CGMiner after this crate was written).
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let code = u32::deserialize(deserializer)?;
+        if code >= StatusCode::CustomBase as u32 {
+            return Ok(StatusCodeType::Custom(code - StatusCode::CustomBase as u32));
+        }
+        Ok(match StatusCode::try_from(code) {
+            Ok(status_code) => StatusCodeType::Protocol(status_code),
+            Err(code) => StatusCodeType::Custom(code),
+        })
+    }
+}
+
 pub enum InfoCode {
     PoolAlreadyEnabled(i32, String),
     PoolAlreadyDisabled(i32, String),
@@ -326,18 +420,19 @@ impl From<Error> for Dispatch {
 }
 
 /// STATUS structure present in all replies
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct StatusInfo {
     #[serde(rename = "STATUS")]
     pub status: Status,
+    #[cfg_attr(feature = "chrono", serde(with = "datetime::timestamp"))]
     pub when: Time,
     pub code: StatusCodeType,
     pub msg: String,
     pub description: String,
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Pool {
     #[serde(rename = "POOL")]
     pub idx: i32,
@@ -370,6 +465,7 @@ pub struct Pool {
     #[serde(rename = "User")]
     pub user: String,
     #[serde(rename = "Last Share Time")]
+    #[cfg_attr(feature = "chrono", serde(with = "datetime::timestamp"))]
     pub last_share_time: Time,
     #[serde(rename = "Diff1 Shares")]
     pub diff1_shares: u64,
@@ -378,32 +474,44 @@ pub struct Pool {
     #[serde(rename = "Proxy")]
     pub proxy: String,
     #[serde(rename = "Difficulty Accepted")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub difficulty_accepted: Difficulty,
     #[serde(rename = "Difficulty Rejected")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub difficulty_rejected: Difficulty,
     #[serde(rename = "Difficulty Stale")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub difficulty_stale: Difficulty,
     #[serde(rename = "Last Share Difficulty")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub last_share_difficulty: Difficulty,
     #[serde(rename = "Work Difficulty")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub work_difficulty: Difficulty,
     #[serde(rename = "Has Stratum")]
+    #[serde(deserialize_with = "lenient::deserialize_bool")]
     pub has_stratum: bool,
     #[serde(rename = "Stratum Active")]
+    #[serde(deserialize_with = "lenient::deserialize_bool")]
     pub stratum_active: bool,
     #[serde(rename = "Stratum URL")]
     pub stratum_url: String,
     #[serde(rename = "Stratum Difficulty")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub stratum_difficulty: Difficulty,
     #[serde(rename = "Has Vmask")]
+    #[serde(deserialize_with = "lenient::deserialize_bool")]
     pub has_vmask: bool,
     #[serde(rename = "Has GBT")]
+    #[serde(deserialize_with = "lenient::deserialize_bool")]
     pub has_gbt: bool,
     #[serde(rename = "Best Share")]
     pub best_share: u64,
     #[serde(rename = "Pool Rejected%")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub pool_rejected_ratio: Percent,
     #[serde(rename = "Pool Stale%")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub pool_stale_ratio: Percent,
     #[serde(rename = "Bad Work")]
     pub bad_work: u64,
@@ -413,10 +521,11 @@ pub struct Pool {
     pub current_block_version: u32,
     // Follows attribute extensions
     #[serde(rename = "AsicBoost")]
+    #[serde(deserialize_with = "lenient::deserialize_bool")]
     pub asic_boost: bool,
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Pools {
     pub list: Vec<Pool>,
 }
@@ -435,7 +544,7 @@ impl From<Pools> for Dispatch {
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Asc {
     #[serde(rename = "ASC")]
     pub idx: i32,
@@ -450,14 +559,19 @@ pub struct Asc {
     #[serde(rename = "Temperature")]
     pub temperature: Temperature,
     #[serde(rename = "MHS av")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_av: MegaHashes,
     #[serde(rename = "MHS 5s")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_5s: MegaHashes,
     #[serde(rename = "MHS 1m")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_1m: MegaHashes,
     #[serde(rename = "MHS 5m")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_5m: MegaHashes,
     #[serde(rename = "MHS 15m")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_15m: MegaHashes,
     #[serde(rename = "Accepted")]
     pub accepted: i32,
@@ -470,29 +584,39 @@ pub struct Asc {
     #[serde(rename = "Last Share Pool")]
     pub last_share_pool: i32,
     #[serde(rename = "Last Share Time")]
+    #[cfg_attr(feature = "chrono", serde(with = "datetime::timestamp"))]
     pub last_share_time: Time,
     #[serde(rename = "Total MH")]
     pub total_mega_hashes: TotalMegaHashes,
     #[serde(rename = "Diff1 Work")]
     pub diff1_work: u64,
     #[serde(rename = "Difficulty Accepted")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub difficulty_accepted: Difficulty,
     #[serde(rename = "Difficulty Rejected")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub difficulty_rejected: Difficulty,
     #[serde(rename = "Last Share Difficulty")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub last_share_difficulty: Difficulty,
     #[serde(rename = "Last Valid Work")]
+    #[cfg_attr(feature = "chrono", serde(with = "datetime::timestamp"))]
     pub last_valid_work: Time,
     #[serde(rename = "Device Hardware%")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub device_hardware_ratio: Percent,
     #[serde(rename = "Device Rejected%")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub device_rejected_ratio: Percent,
     #[serde(rename = "Device Elapsed")]
+    #[cfg_attr(feature = "chrono", serde(with = "datetime::duration"))]
     pub device_elapsed: Elapsed,
     // Follows attribute extensions
     #[serde(rename = "Hardware Error MHS 15m")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub hardware_error_mhs_15m: MegaHashes,
     #[serde(rename = "Nominal MHS")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub nominal_mhs: MegaHashes,
 }
 
@@ -510,7 +634,7 @@ impl From<Asc> for Dispatch {
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Devs {
     pub list: Vec<Asc>,
 }
@@ -529,19 +653,25 @@ impl From<Devs> for Dispatch {
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Summary {
     #[serde(rename = "Elapsed")]
+    #[cfg_attr(feature = "chrono", serde(with = "datetime::duration"))]
     pub elapsed: Elapsed,
     #[serde(rename = "MHS av")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_av: MegaHashes,
     #[serde(rename = "MHS 5s")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_5s: MegaHashes,
     #[serde(rename = "MHS 1m")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_1m: MegaHashes,
     #[serde(rename = "MHS 5m")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_5m: MegaHashes,
     #[serde(rename = "MHS 15m")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_15m: MegaHashes,
     #[serde(rename = "Found Blocks")]
     pub found_blocks: u32,
@@ -572,25 +702,34 @@ pub struct Summary {
     #[serde(rename = "Work Utility")]
     pub work_utility: Utility,
     #[serde(rename = "Difficulty Accepted")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub difficulty_accepted: Difficulty,
     #[serde(rename = "Difficulty Rejected")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub difficulty_rejected: Difficulty,
     #[serde(rename = "Difficulty Stale")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub difficulty_stale: Difficulty,
     #[serde(rename = "Best Share")]
     pub best_share: u64,
     #[serde(rename = "Device Hardware%")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub device_hardware_ratio: Percent,
     #[serde(rename = "Device Rejected%")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub device_rejected_ratio: Percent,
     #[serde(rename = "Pool Rejected%")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub pool_rejected_ratio: Percent,
     #[serde(rename = "Pool Stale%")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub pool_stale_ratio: Percent,
     #[serde(rename = "Last getwork")]
+    #[cfg_attr(feature = "chrono", serde(with = "datetime::timestamp"))]
     pub last_getwork: Time,
     // Follows attribute extensions
     #[serde(rename = "MHS 24h")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub mhs_24h: MegaHashes,
 }
 
@@ -627,6 +766,55 @@ impl Serialize for Version {
     }
 }
 
+impl<'de> Deserialize<'de> for Version {
+    /// Inverse of `Serialize` above: reads the 2-entry map back out, taking the entry keyed
+    /// `"API"` as `api` and the other (whatever its key turns out to be, e.g. the miner's
+    /// signature string) as the `signature`/`miner` pair.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::{Error, MapAccess, Visitor};
+        use std::fmt;
+
+        struct VersionVisitor;
+
+        impl<'de> Visitor<'de> for VersionVisitor {
+            type Value = Version;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map with the miner's signature entry and an \"API\" entry")
+            }
+
+            fn visit_map<M>(self, mut map: M) -> std::result::Result<Self::Value, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                let mut signature = None;
+                let mut miner = None;
+                let mut api = None;
+
+                while let Some(key) = map.next_key::<String>()? {
+                    if key == "API" {
+                        api = Some(map.next_value()?);
+                    } else {
+                        signature = Some(key);
+                        miner = Some(map.next_value()?);
+                    }
+                }
+
+                Ok(Version {
+                    signature: signature.ok_or_else(|| Error::custom("missing signature entry"))?,
+                    miner: miner.ok_or_else(|| Error::custom("missing signature entry"))?,
+                    api: api.ok_or_else(|| Error::missing_field("API"))?,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(VersionVisitor)
+    }
+}
+
 impl From<Version> for Dispatch {
     fn from(version: Version) -> Self {
         Dispatch::from_success(
@@ -658,7 +846,7 @@ impl From<SwitchPool> for Dispatch {
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Config {
     #[serde(rename = "ASC Count")]
     pub asc_count: i32,
@@ -751,7 +939,7 @@ impl From<RemovePool> for Dispatch {
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct DevDetail<T> {
     #[serde(rename = "DEVDETAILS")]
     pub idx: i32,
@@ -771,7 +959,7 @@ pub struct DevDetail<T> {
     pub info: T,
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct DevDetails<T> {
     pub list: Vec<DevDetail<T>>,
 }
@@ -792,7 +980,7 @@ where
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct PoolStats {
     #[serde(flatten)]
     pub header: StatsHeader,
@@ -809,18 +997,24 @@ pub struct PoolStats {
     #[serde(rename = "Pool Av")]
     pub pool_av: f64,
     #[serde(rename = "Work Had Roll Time")]
+    #[serde(deserialize_with = "lenient::deserialize_bool")]
     pub work_had_roll_time: bool,
     #[serde(rename = "Work Can Roll")]
+    #[serde(deserialize_with = "lenient::deserialize_bool")]
     pub work_can_roll: bool,
     #[serde(rename = "Work Had Expire")]
+    #[serde(deserialize_with = "lenient::deserialize_bool")]
     pub work_had_expire: bool,
     #[serde(rename = "Work Roll Time")]
     pub work_roll_time: u32,
     #[serde(rename = "Work Diff")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub work_diff: Difficulty,
     #[serde(rename = "Min Diff")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub min_diff: Difficulty,
     #[serde(rename = "Max Diff")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub max_diff: Difficulty,
     #[serde(rename = "Min Diff Count")]
     pub min_diff_count: u32,
@@ -840,26 +1034,27 @@ pub struct PoolStats {
     pub net_bytes_recv: u64,
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct AscStats {
     #[serde(flatten)]
     pub header: StatsHeader,
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 #[serde(untagged)]
 enum StatsType {
     Pool(PoolStats),
     Asc(AscStats),
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct StatsHeader {
     #[serde(rename = "STATS")]
     pub idx: i32,
     #[serde(rename = "ID")]
     pub id: String,
     #[serde(rename = "Elapsed")]
+    #[cfg_attr(feature = "chrono", serde(with = "datetime::duration"))]
     pub elapsed: Elapsed,
     #[serde(rename = "Calls")]
     pub calls: u32,
@@ -871,7 +1066,7 @@ pub struct StatsHeader {
     pub min: Interval,
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Stats {
     pub asc_stats: Vec<AscStats>,
     pub pool_stats: Vec<PoolStats>,
@@ -904,7 +1099,7 @@ impl From<Stats> for Dispatch {
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub(crate) struct Check {
     #[serde(rename = "Exists")]
     pub exists: Bool,
@@ -925,17 +1120,19 @@ impl From<Check> for Dispatch {
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Coin {
     #[serde(rename = "Hash Method")]
     pub hash_method: String,
     #[serde(rename = "Current Block Time")]
     pub current_block_time: Interval,
     #[serde(rename = "Current Block Hash")]
-    pub current_block_hash: String,
+    pub current_block_hash: HashField,
     #[serde(rename = "LP")]
+    #[serde(deserialize_with = "lenient::deserialize_bool")]
     pub lp: bool,
     #[serde(rename = "Network Difficulty")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub network_difficulty: Difficulty,
 }
 
@@ -952,7 +1149,7 @@ impl From<Coin> for Dispatch {
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct AscCount {
     #[serde(rename = "Count")]
     pub count: i32,
@@ -971,24 +1168,28 @@ impl From<AscCount> for Dispatch {
     }
 }
 
-#[derive(Serialize, PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct Lcd {
     #[serde(rename = "Elapsed")]
     pub elapsed: Elapsed,
     #[serde(rename = "GHS av")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub ghs_av: GigaHashes,
     #[serde(rename = "GHS 5m")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub ghs_5m: GigaHashes,
     #[serde(rename = "GHS 5s")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub ghs_5s: GigaHashes,
     #[serde(rename = "Temperature")]
     pub temperature: Temperature,
     #[serde(rename = "Last Share Difficulty")]
+    #[serde(deserialize_with = "lenient::deserialize_f64")]
     pub last_share_difficulty: Difficulty,
     #[serde(rename = "Last Share Time")]
     pub last_share_time: Time,
     #[serde(rename = "Best Share")]
-    pub best_share: u64,
+    pub best_share: HashField,
     #[serde(rename = "Last Valid Work")]
     pub last_valid_work: Time,
     #[serde(rename = "Found Blocks")]
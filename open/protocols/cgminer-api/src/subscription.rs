@@ -0,0 +1,237 @@
+// Copyright (C) 2019  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Long-lived subscription/streaming mode: instead of one `Dispatch` per request, a client holds
+//! a connection open and receives periodic or event-driven `support::SingleResponse` records,
+//! each framed with a 4-byte big-endian length prefix. `SubscriptionCodec` handles that framing
+//! as defensively as `codec::Codec` handles the NUL-delimited request framing: bytes are buffered
+//! until a complete frame is present, and decoding (including the UTF-8 check baked into JSON
+//! parsing) is only attempted then - a read landing mid-frame, mid-length-prefix, or mid
+//! multi-byte UTF-8 character just means waiting for more bytes.
+
+use crate::support;
+
+use bytes::{Buf, BytesMut};
+use ii_async_compat::futures;
+use futures::channel::mpsc;
+use serde_json as json;
+use std::collections::HashMap;
+use std::time::Duration;
+use thiserror::Error;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Size of the big-endian frame length prefix.
+const LENGTH_PREFIX_BYTES: usize = 4;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("frame is not valid JSON: {0}")]
+    InvalidJson(#[from] json::Error),
+}
+
+/// Pull one complete length-prefixed frame out of `src` if one is fully buffered, advancing past
+/// both the prefix and the frame itself. `frame_len` caches the prefix across calls so a prefix
+/// that arrives split across reads isn't re-parsed from scratch (and isn't mistaken for data).
+fn take_frame(src: &mut BytesMut, frame_len: &mut Option<usize>) -> Option<BytesMut> {
+    let len = match *frame_len {
+        Some(len) => len,
+        None => {
+            if src.len() < LENGTH_PREFIX_BYTES {
+                return None;
+            }
+            let len = u32::from_be_bytes([src[0], src[1], src[2], src[3]]) as usize;
+            src.advance(LENGTH_PREFIX_BYTES);
+            *frame_len = Some(len);
+            len
+        }
+    };
+
+    if src.len() < len {
+        return None;
+    }
+
+    *frame_len = None;
+    Some(src.split_to(len))
+}
+
+/// Length-delimited framing for the streaming/subscription channel.
+///
+/// NOTE: assumes `support::SingleResponse` is `Serialize`/`Deserialize`/`Clone`, matching how
+/// `Dispatch::into_response` already builds and returns one in `response.rs` - `support.rs`
+/// itself isn't part of this checkout so that can't be verified directly.
+#[derive(Default)]
+pub struct SubscriptionCodec {
+    frame_len: Option<usize>,
+}
+
+impl Decoder for SubscriptionCodec {
+    type Item = support::SingleResponse;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match take_frame(src, &mut self.frame_len) {
+            Some(frame) => Ok(Some(json::from_slice(&frame)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Encoder<support::SingleResponse> for SubscriptionCodec {
+    type Error = Error;
+
+    fn encode(
+        &mut self,
+        item: support::SingleResponse,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        let body = json::to_vec(&item)?;
+        dst.extend_from_slice(&(body.len() as u32).to_be_bytes());
+        dst.extend_from_slice(&body);
+        Ok(())
+    }
+}
+
+/// Which periodic dispatch a subscriber wants pushed - one entry per streamable `StatusCode`
+/// command.
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
+pub enum Topic {
+    Stats,
+    Lcd,
+    Coin,
+}
+
+/// One registered subscriber: how often it wants `Topic` pushed, and the channel to push
+/// encoded records to.
+struct Subscription {
+    #[allow(dead_code)]
+    interval: Duration,
+    sender: mpsc::UnboundedSender<support::SingleResponse>,
+}
+
+/// Registry of live subscriptions, keyed by `Topic`, so e.g. a monitoring dashboard can watch
+/// `Lcd`'s `GHS 5s`/`Temperature`/`Last Share Time` live instead of polling.
+#[derive(Default)]
+pub struct SubscriptionRegistry {
+    subscriptions: HashMap<Topic, Vec<Subscription>>,
+}
+
+impl SubscriptionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new subscriber; the caller is expected to push `support::SingleResponse`
+    /// values no more often than `interval` and the registry's job is just fan-out, not pacing.
+    pub fn subscribe(
+        &mut self,
+        topic: Topic,
+        interval: Duration,
+        sender: mpsc::UnboundedSender<support::SingleResponse>,
+    ) {
+        self.subscriptions
+            .entry(topic)
+            .or_insert_with(Vec::new)
+            .push(Subscription { interval, sender });
+    }
+
+    /// Push `response` to every subscriber registered for `topic`, dropping any whose receiver
+    /// has gone away.
+    pub fn publish(&mut self, topic: Topic, response: support::SingleResponse) {
+        if let Some(subscribers) = self.subscriptions.get_mut(&topic) {
+            subscribers.retain(|subscription| subscription.sender.unbounded_send(response.clone()).is_ok());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_take_frame_waits_for_full_length_prefix() {
+        let mut frame_len = None;
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&[0, 0]); // only 2 of the 4 length-prefix bytes so far
+        assert!(take_frame(&mut buf, &mut frame_len).is_none());
+    }
+
+    #[test]
+    fn test_take_frame_waits_for_full_body() {
+        let mut frame_len = None;
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(&5u32.to_be_bytes());
+        buf.extend_from_slice(b"ab"); // only 2 of the 5 promised body bytes so far
+        assert!(take_frame(&mut buf, &mut frame_len).is_none());
+        // the prefix must not be re-read (and re-consumed) on the next attempt
+        assert_eq!(frame_len, Some(5));
+    }
+
+    #[test]
+    fn test_take_frame_one_byte_at_a_time() {
+        let message = b"hello";
+        let mut full = BytesMut::new();
+        full.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        full.extend_from_slice(message);
+
+        let mut frame_len = None;
+        let mut buf = BytesMut::new();
+        let mut frames = Vec::new();
+        for &byte in full.as_ref() {
+            buf.extend_from_slice(&[byte]);
+            if let Some(frame) = take_frame(&mut buf, &mut frame_len) {
+                frames.push(frame.to_vec());
+            }
+        }
+
+        assert_eq!(frames, vec![message.to_vec()]);
+    }
+
+    #[test]
+    fn test_subscription_registry_drops_disconnected_subscribers() {
+        use crate::response::{Status, StatusCode, StatusCodeType, StatusInfo};
+
+        let mut registry = SubscriptionRegistry::new();
+        let (sender, receiver) = mpsc::unbounded();
+        registry.subscribe(Topic::Stats, Duration::from_secs(1), sender);
+        drop(receiver);
+
+        // NOTE: `support::SingleResponse`'s fields are inferred from how `Dispatch::into_response`
+        // builds one (`response.rs`), since `support.rs` isn't part of this checkout.
+        let response = support::SingleResponse {
+            status_info: StatusInfo {
+                status: Status::S,
+                when: 0,
+                code: StatusCodeType::Protocol(StatusCode::Stats),
+                msg: "stats".to_string(),
+                description: "test".to_string(),
+            },
+            body: None,
+        };
+
+        // publishing to a subscriber whose receiver is gone must not panic, and it should be
+        // pruned rather than retried forever
+        registry.publish(Topic::Stats, response);
+        assert!(registry.subscriptions.get(&Topic::Stats).unwrap().is_empty());
+    }
+}
@@ -20,37 +20,309 @@
 // of such proprietary license or if you have any other questions, please
 // contact us at opensource@braiins.com.
 
+use std::fmt;
+use std::future::Future;
 use std::net::TcpListener as StdTcpListener;
 use std::net::ToSocketAddrs as StdToSocketAddrs;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use ii_async_compat::prelude::*;
 use pin_project::pin_project;
-use tokio::net::{TcpListener, TcpStream};
+use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
+/// Number of concurrently accepted connections when `Server::bind`/`Server::bind_unix` is used
+/// instead of `Server::with_max_connections`/`Server::with_max_connections_unix`
+const DEFAULT_MAX_CONNECTIONS: usize = 1024;
+
+/// Underlying transport of an accepted `Connection`, abstracting over TCP and Unix-domain
+/// sockets so downstream code that consumes `Server` as a `Stream` keeps working regardless of
+/// which transport it was bound with.
+#[pin_project(project = TransportProj)]
+#[derive(Debug)]
+enum Transport {
+    Tcp(#[pin] TcpStream),
+    Unix(#[pin] UnixStream),
+}
+
+impl tokio::io::AsyncRead for Transport {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.project() {
+            TransportProj::Tcp(stream) => stream.poll_read(cx, buf),
+            TransportProj::Unix(stream) => stream.poll_read(cx, buf),
+        }
+    }
+}
+
+impl tokio::io::AsyncWrite for Transport {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.project() {
+            TransportProj::Tcp(stream) => stream.poll_write(cx, buf),
+            TransportProj::Unix(stream) => stream.poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<std::io::Result<()>> {
+        match self.project() {
+            TransportProj::Tcp(stream) => stream.poll_flush(cx),
+            TransportProj::Unix(stream) => stream.poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<std::io::Result<()>> {
+        match self.project() {
+            TransportProj::Tcp(stream) => stream.poll_shutdown(cx),
+            TransportProj::Unix(stream) => stream.poll_shutdown(cx),
+        }
+    }
+}
+
+/// A connection accepted by `Server`. It owns the semaphore permit that admitted it, so dropping
+/// a `Connection` is what makes room for the next client to be accepted.
 #[pin_project]
 #[derive(Debug)]
-pub struct Server {
+pub struct Connection {
     #[pin]
-    tcp: TcpListener,
+    stream: Transport,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl Connection {
+    fn new(stream: Transport, permit: OwnedSemaphorePermit) -> Self {
+        Self {
+            stream,
+            _permit: permit,
+        }
+    }
+}
+
+impl tokio::io::AsyncRead for Connection {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.project().stream.poll_read(cx, buf)
+    }
+}
+
+impl tokio::io::AsyncWrite for Connection {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.project().stream.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<std::io::Result<()>> {
+        self.project().stream.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<std::io::Result<()>> {
+        self.project().stream.poll_shutdown(cx)
+    }
+}
+
+/// The bound listener backing a `Server`
+#[derive(Debug)]
+enum Listener {
+    Tcp(TcpListener),
+    Unix(UnixListener),
+}
+
+/// Address a `Server` is listening on, shared between transports for reporting purposes
+#[derive(Debug, Clone)]
+pub enum ServerAddr {
+    Tcp(std::net::SocketAddr),
+    Unix(Option<PathBuf>),
+}
+
+impl fmt::Display for ServerAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{}", addr),
+            Self::Unix(Some(path)) => write!(f, "{}", path.display()),
+            Self::Unix(None) => write!(f, "<unbound unix socket>"),
+        }
+    }
+}
+
+#[pin_project]
+pub struct Server {
+    listener: Listener,
+    /// Bounds the number of `Connection`s accepted and not yet dropped
+    max_connections: Arc<Semaphore>,
+    /// Permit currently being awaited before the next connection may be accepted
+    acquire: Option<Pin<Box<dyn Future<Output = OwnedSemaphorePermit> + Send>>>,
+    /// Set once `acquire` resolves, and held here (rather than re-polling the spent `acquire`
+    /// future on the next wake-up) until `accept()` also yields something
+    permit: Option<OwnedSemaphorePermit>,
+    nodelay: bool,
+    keepalive: Option<Duration>,
+}
+
+impl std::fmt::Debug for Server {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Server")
+            .field("listener", &self.listener)
+            .field(
+                "available_permits",
+                &self.max_connections.available_permits(),
+            )
+            .finish()
+    }
 }
 
 impl Server {
     pub fn bind<A: StdToSocketAddrs>(addr: A) -> std::io::Result<Self> {
+        Self::with_max_connections(addr, DEFAULT_MAX_CONNECTIONS)
+    }
+
+    /// Bind `addr`, never holding more than `limit` `Connection`s at once. Once `limit` is
+    /// reached, `poll_next` simply doesn't resolve until a previously accepted `Connection` is
+    /// dropped, which naturally stops `accept()` from spinning when saturated.
+    pub fn with_max_connections<A: StdToSocketAddrs>(
+        addr: A,
+        limit: usize,
+    ) -> std::io::Result<Self> {
         let tcp = StdTcpListener::bind(addr)?;
         let tcp = TcpListener::from_std(tcp)?;
 
-        Ok(Server { tcp })
+        Ok(Self::new(Listener::Tcp(tcp), limit))
+    }
+
+    /// Bind a Unix-domain-socket at `path`. Useful for co-located processes (e.g. a local
+    /// stratum proxy talking to a supervising daemon, or an admin control socket) where a TCP
+    /// port would be unnecessary overhead and exposure.
+    pub fn bind_unix<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Self::with_max_connections_unix(path, DEFAULT_MAX_CONNECTIONS)
+    }
+
+    /// Unix-domain-socket equivalent of `with_max_connections`
+    pub fn with_max_connections_unix<P: AsRef<Path>>(
+        path: P,
+        limit: usize,
+    ) -> std::io::Result<Self> {
+        let unix = UnixListener::bind(path)?;
+
+        Ok(Self::new(Listener::Unix(unix), limit))
+    }
+
+    fn new(listener: Listener, limit: usize) -> Self {
+        Self {
+            listener,
+            max_connections: Arc::new(Semaphore::new(limit)),
+            acquire: None,
+            permit: None,
+            nodelay: false,
+            keepalive: None,
+        }
+    }
+
+    /// Apply `TCP_NODELAY` to every stream accepted from now on. No-op for Unix-domain sockets.
+    pub fn set_nodelay(&mut self, nodelay: bool) {
+        self.nodelay = nodelay;
+    }
+
+    /// Apply the given TCP keepalive to every stream accepted from now on. No-op for
+    /// Unix-domain sockets.
+    pub fn set_keepalive(&mut self, keepalive: Option<Duration>) {
+        self.keepalive = keepalive;
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> std::io::Result<ServerAddr> {
+        match &self.listener {
+            Listener::Tcp(tcp) => tcp.local_addr().map(ServerAddr::Tcp),
+            Listener::Unix(unix) => Ok(ServerAddr::Unix(
+                unix.local_addr()?.as_pathname().map(Path::to_path_buf),
+            )),
+        }
     }
 }
 
 impl Stream for Server {
-    type Item = std::io::Result<TcpStream>;
+    type Item = std::io::Result<Connection>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
-        let mut tcp = self.project().tcp;
+        let this = self.project();
+
+        // make sure we hold, or are waiting for, a permit before accepting anything
+        if this.permit.is_none() {
+            if this.acquire.is_none() {
+                let max_connections = this.max_connections.clone();
+                *this.acquire = Some(Box::pin(async move {
+                    max_connections
+                        .acquire_owned()
+                        .await
+                        .expect("BUG: semaphore closed")
+                }));
+            }
+
+            match this.acquire.as_mut().unwrap().as_mut().poll(cx) {
+                // store the permit itself rather than leaving the now-spent `acquire` future in
+                // place to be polled again, which is disallowed for `async fn`-based futures
+                Poll::Ready(permit) => {
+                    *this.acquire = None;
+                    *this.permit = Some(permit);
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+
+        let accepted = match this.listener {
+            Listener::Tcp(tcp) => match Pin::new(&mut tcp.incoming()).poll_next(cx) {
+                Poll::Ready(Some(result)) => Poll::Ready(Some(result.map(Transport::Tcp))),
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            },
+            Listener::Unix(unix) => match Pin::new(&mut unix.incoming()).poll_next(cx) {
+                Poll::Ready(Some(result)) => Poll::Ready(Some(result.map(Transport::Unix))),
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            },
+        };
 
-        Pin::new(&mut tcp.incoming()).poll_next(cx)
+        match accepted {
+            Poll::Ready(Some(Ok(stream))) => {
+                // the permit is now owned by the yielded `Connection`, start waiting for the next
+                // one on the following call
+                let permit = this.permit.take().expect("BUG: missing permit");
+                if let Transport::Tcp(tcp) = &stream {
+                    if *this.nodelay {
+                        let _ = tcp.set_nodelay(true);
+                    }
+                    if let Some(keepalive) = *this.keepalive {
+                        let _ = tcp.set_keepalive(Some(keepalive));
+                    }
+                }
+                Poll::Ready(Some(Ok(Connection::new(stream, permit))))
+            }
+            Poll::Ready(Some(Err(e))) => {
+                // accept failed - drop `permit` (below) instead of leaking it, nothing was
+                // actually admitted
+                *this.permit = None;
+                Poll::Ready(Some(Err(e)))
+            }
+            Poll::Ready(None) => {
+                *this.permit = None;
+                Poll::Ready(None)
+            }
+            // still holding `permit` (not polling `acquire` again) until `accept()` also yields
+            Poll::Pending => Poll::Pending,
+        }
     }
 }
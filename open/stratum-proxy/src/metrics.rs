@@ -0,0 +1,189 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Operator-facing counters/gauges for the proxy, exported in the open-metrics/Prometheus text
+//! format over a small standalone HTTP `/metrics` endpoint. `Metrics` is cheap to `Clone` - every
+//! `ConnTranslation` and `ProxyServer` gets its own handle backed by the same underlying
+//! collectors registered in the one `Registry` its `Metrics::new` created, the usual way a metrics crate wires up
+//! per-subsystem collectors.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+
+use futures::future;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server as HttpServer};
+use prometheus::{Encoder, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+
+use ii_logging::macros::*;
+
+use crate::error::{ErrorKind, Result, ResultExt};
+
+/// `frames`'s `direction` label for frames received from the V1 upstream
+pub const DIRECTION_V1: &str = "v1";
+/// `frames`'s `direction` label for frames received from the V2 downstream
+pub const DIRECTION_V2: &str = "v2";
+
+/// A cloneable handle onto the proxy's Prometheus collectors
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    /// Currently open translated sessions - incremented once `ConnTranslation::run` has started
+    /// its send tasks, decremented when it returns
+    pub open_sessions: IntGauge,
+    /// Total V2 connections accepted by `ProxyServer::next`
+    pub accepted_connections: IntCounter,
+    /// Total incoming V2 connections `ProxyServer::next` failed to accept (accept or peer address
+    /// error); a later upstream failure in `handle_connection` is not counted here
+    pub failed_connections: IntCounter,
+    /// Total frames translated, labeled by `direction` (`DIRECTION_V1`/`DIRECTION_V2`)
+    pub frames: IntCounterVec,
+    /// Total bytes sent downstream to V2 clients via `v2_try_send_frame`
+    pub bytes_sent: IntCounter,
+    /// Total upstream V1 read timeouts
+    pub v1_upstream_timeouts: IntCounter,
+    /// Total downstream V2 read timeouts
+    pub v2_downstream_timeouts: IntCounter,
+    /// Total protocol translation errors
+    pub translation_errors: IntCounter,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let open_sessions = IntGauge::new(
+            "stratum_proxy_open_sessions",
+            "Currently open V2-to-V1 translated sessions",
+        )
+        .expect("BUG: invalid metric");
+        let accepted_connections = IntCounter::new(
+            "stratum_proxy_accepted_connections_total",
+            "Total V2 connections accepted",
+        )
+        .expect("BUG: invalid metric");
+        let failed_connections = IntCounter::new(
+            "stratum_proxy_failed_connections_total",
+            "Total incoming V2 connections that failed to be accepted",
+        )
+        .expect("BUG: invalid metric");
+        let frames = IntCounterVec::new(
+            Opts::new(
+                "stratum_proxy_frames_total",
+                "Total frames translated, by direction",
+            ),
+            &["direction"],
+        )
+        .expect("BUG: invalid metric");
+        let bytes_sent = IntCounter::new(
+            "stratum_proxy_bytes_sent_total",
+            "Total bytes sent downstream to V2 clients",
+        )
+        .expect("BUG: invalid metric");
+        let v1_upstream_timeouts = IntCounter::new(
+            "stratum_proxy_v1_upstream_timeouts_total",
+            "Total upstream V1 read timeouts",
+        )
+        .expect("BUG: invalid metric");
+        let v2_downstream_timeouts = IntCounter::new(
+            "stratum_proxy_v2_downstream_timeouts_total",
+            "Total downstream V2 read timeouts",
+        )
+        .expect("BUG: invalid metric");
+        let translation_errors = IntCounter::new(
+            "stratum_proxy_translation_errors_total",
+            "Total protocol translation errors",
+        )
+        .expect("BUG: invalid metric");
+
+        macro_rules! register {
+            ($($collector:expr),* $(,)?) => {
+                $(registry
+                    .register(Box::new($collector.clone()))
+                    .expect("BUG: duplicate metric registration");)*
+            };
+        }
+        register!(
+            open_sessions,
+            accepted_connections,
+            failed_connections,
+            frames,
+            bytes_sent,
+            v1_upstream_timeouts,
+            v2_downstream_timeouts,
+            translation_errors,
+        );
+
+        Self {
+            registry,
+            open_sessions,
+            accepted_connections,
+            failed_connections,
+            frames,
+            bytes_sent,
+            v1_upstream_timeouts,
+            v2_downstream_timeouts,
+            translation_errors,
+        }
+    }
+
+    /// Renders every registered collector in the open-metrics/Prometheus text format
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("BUG: failed to encode metrics");
+        buffer
+    }
+
+    /// Serves `GET /metrics` on `listen_addr` until the process exits or the server errors -
+    /// intended to be spawned as its own task alongside `ProxyServer::run`.
+    pub async fn serve(self, listen_addr: SocketAddr) -> Result<()> {
+        let make_service = make_service_fn(move |_conn| {
+            let metrics = self.clone();
+            let service = service_fn(move |req: Request<Body>| {
+                let response = if req.uri().path() == "/metrics" {
+                    Response::new(Body::from(metrics.encode()))
+                } else {
+                    Response::builder()
+                        .status(404)
+                        .body(Body::empty())
+                        .expect("BUG: invalid response")
+                };
+                future::ready(Ok::<_, Infallible>(response))
+            });
+            future::ready(Ok::<_, Infallible>(service))
+        });
+
+        info!("Metrics endpoint listening @ {}", listen_addr);
+        HttpServer::bind(&listen_addr)
+            .serve(make_service)
+            .await
+            .context(ErrorKind::Io("metrics HTTP server failed".to_string()))
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -22,6 +22,8 @@
 
 use std::net::SocketAddr;
 use std::net::ToSocketAddrs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time;
 
 use futures::channel::mpsc;
@@ -33,10 +35,187 @@ use ii_logging::macros::*;
 use ii_stratum::v1;
 use ii_stratum::v2;
 use ii_wire::{Connection, Server};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
 
 use crate::error::{ErrorKind, Result, ResultExt};
+use crate::metrics::{self, Metrics};
 use crate::translation::V2ToV1Translation;
 
+/// How long a backend that just failed to connect is skipped for, before being retried
+const BACKEND_COOLDOWN: time::Duration = time::Duration::from_secs(30);
+
+/// One upstream V1 pool candidate out of the pool farm `ProxyServer` load-balances across, with a
+/// simple health flag so a backend that's currently unreachable is skipped instead of being
+/// retried on every single incoming connection. `host` is kept as the original hostname rather
+/// than a single resolved `SocketAddr` and re-resolved on every connection attempt, so a pool
+/// hostname that moves behind a new IP (DNS failover, rolling deploy) is picked up without
+/// restarting the proxy.
+#[derive(Debug)]
+struct Backend {
+    host: String,
+    /// Set to the instant this backend may be tried again after a connect/resolution failure;
+    /// `None` means it's healthy
+    down_until: Mutex<Option<time::Instant>>,
+}
+
+impl Backend {
+    fn new(host: String) -> Self {
+        Self {
+            host,
+            down_until: Mutex::new(None),
+        }
+    }
+
+    fn is_down(&self) -> bool {
+        match *self.down_until.lock().expect("BUG: backend lock poisoned") {
+            Some(down_until) => time::Instant::now() < down_until,
+            None => false,
+        }
+    }
+
+    fn mark_down(&self) {
+        *self.down_until.lock().expect("BUG: backend lock poisoned") =
+            Some(time::Instant::now() + BACKEND_COOLDOWN);
+    }
+
+    /// Re-resolves `host` into every address it currently maps to
+    fn resolve(&self) -> Result<Vec<SocketAddr>> {
+        let addrs: Vec<SocketAddr> = self
+            .host
+            .to_socket_addrs()
+            .context(ErrorKind::BadIp(self.host.clone()))?
+            .collect();
+        if addrs.is_empty() {
+            Err(ErrorKind::BadIp(self.host.clone()))?;
+        }
+        Ok(addrs)
+    }
+}
+
+/// Picks an upstream V1 backend to connect to, starting from `cursor` (round-robin, shared across
+/// connections) and trying up to one full lap over `backends`, skipping any currently in their
+/// cooldown window. Each backend is re-resolved fresh and every address it currently maps to is
+/// tried in turn, not just the first. Returns the first successfully established connection, or
+/// `None` once every backend has either been skipped, failed to resolve, or failed to connect on
+/// all of its addresses.
+async fn connect_upstream(
+    backends: &[Backend],
+    cursor: &AtomicUsize,
+) -> Option<(SocketAddr, TcpStream)> {
+    for _ in 0..backends.len() {
+        let backend = &backends[cursor.fetch_add(1, Ordering::Relaxed) % backends.len()];
+        if backend.is_down() {
+            continue;
+        }
+
+        let addrs = match backend.resolve() {
+            Ok(addrs) => addrs,
+            Err(e) => {
+                warn!(
+                    "Failed to resolve Stratum V1 backend '{}': {}",
+                    backend.host, e
+                );
+                backend.mark_down();
+                continue;
+            }
+        };
+
+        let mut connected = None;
+        for addr in addrs {
+            match TcpStream::connect(&addr).await {
+                Ok(stream) => {
+                    connected = Some((addr, stream));
+                    break;
+                }
+                Err(e) => {
+                    warn!(
+                        "Connection to Stratum V1 backend '{}' ({}) failed: {}",
+                        backend.host, addr, e
+                    );
+                }
+            }
+        }
+
+        match connected {
+            Some(result) => return Some(result),
+            None => backend.mark_down(),
+        }
+    }
+    None
+}
+
+/// Whether `handle_connection` prepends a PROXY protocol header to the upstream V1 connection so
+/// the pool sees the original client's address instead of the proxy's - off by default since a
+/// pool that doesn't expect the header will choke on it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ProxyProtocolVersion {
+    /// Don't send any PROXY protocol header
+    Off,
+    /// Send a PROXY protocol v2 binary header
+    V2,
+}
+
+impl Default for ProxyProtocolVersion {
+    fn default() -> Self {
+        Self::Off
+    }
+}
+
+/// PROXY protocol v2 signature, common to every header regardless of address family
+const PROXY_PROTOCOL_V2_SIGNATURE: [u8; 12] = [
+    0x0D, 0x0A, 0x0D, 0x0A, 0x00, 0x0D, 0x0A, 0x51, 0x55, 0x49, 0x54, 0x0A,
+];
+/// Version 2, PROXY command (as opposed to LOCAL)
+const PROXY_PROTOCOL_V2_VERSION_COMMAND: u8 = 0x21;
+/// Address family/transport byte: TCP over IPv4
+const PROXY_PROTOCOL_V2_TCP_IPV4: u8 = 0x11;
+/// Address family/transport byte: TCP over IPv6
+const PROXY_PROTOCOL_V2_TCP_IPV6: u8 = 0x21;
+
+/// Writes a PROXY protocol v2 header to `stream` as the very first bytes on the wire, carrying
+/// `src_addr` (the real downstream V2 client) and `dst_addr` (the upstream V1 pool) so the pool's
+/// per-IP banning/geo-routing/abuse accounting sees the client's real address rather than the
+/// proxy's. `src_addr` and `dst_addr` must be the same address family - PROXY protocol v2 has no
+/// encoding for a mixed-family pair.
+async fn write_proxy_protocol_v2_header(
+    stream: &mut TcpStream,
+    src_addr: SocketAddr,
+    dst_addr: SocketAddr,
+) -> Result<()> {
+    let mut header = PROXY_PROTOCOL_V2_SIGNATURE.to_vec();
+    header.push(PROXY_PROTOCOL_V2_VERSION_COMMAND);
+
+    match (src_addr, dst_addr) {
+        (SocketAddr::V4(src), SocketAddr::V4(dst)) => {
+            header.push(PROXY_PROTOCOL_V2_TCP_IPV4);
+            header.extend_from_slice(&12u16.to_be_bytes());
+            header.extend_from_slice(&src.ip().octets());
+            header.extend_from_slice(&dst.ip().octets());
+            header.extend_from_slice(&src.port().to_be_bytes());
+            header.extend_from_slice(&dst.port().to_be_bytes());
+        }
+        (SocketAddr::V6(src), SocketAddr::V6(dst)) => {
+            header.push(PROXY_PROTOCOL_V2_TCP_IPV6);
+            header.extend_from_slice(&36u16.to_be_bytes());
+            header.extend_from_slice(&src.ip().octets());
+            header.extend_from_slice(&dst.ip().octets());
+            header.extend_from_slice(&src.port().to_be_bytes());
+            header.extend_from_slice(&dst.port().to_be_bytes());
+        }
+        _ => Err(format!(
+            "PROXY protocol: source ({}) and destination ({}) address families don't match",
+            src_addr, dst_addr
+        ))?,
+    }
+
+    stream.write_all(&header).await.context(ErrorKind::Io(
+        "failed to write PROXY protocol header".to_string(),
+    ))?;
+    Ok(())
+}
+
 /// Represents a single protocol translation session (one V2 client talking to one V1 server)
 pub struct ConnTranslation {
     /// Actual protocol translator
@@ -51,6 +230,11 @@ pub struct ConnTranslation {
     v2_conn: Connection<v2::Framing>,
     /// Frames from the translator to be sent out via V2 connection
     v2_translation_rx: mpsc::Receiver<v2::Frame>,
+    /// Metrics handle shared with `ProxyServer`, `None` when metrics are disabled
+    metrics: Option<Metrics>,
+    /// Fires once `ProxyServer::run` observes a quit signal, so this session can drain and stop
+    /// instead of outliving the listener it was spawned from
+    cancel_rx: broadcast::Receiver<()>,
 }
 
 impl ConnTranslation {
@@ -58,7 +242,12 @@ impl ConnTranslation {
     const V1_UPSTREAM_TIMEOUT: time::Duration = time::Duration::from_secs(60);
     const V2_DOWNSTREAM_TIMEOUT: time::Duration = time::Duration::from_secs(60);
 
-    fn new(v2_conn: Connection<v2::Framing>, v1_conn: Connection<v1::Framing>) -> Self {
+    fn new(
+        v2_conn: Connection<v2::Framing>,
+        v1_conn: Connection<v1::Framing>,
+        metrics: Option<Metrics>,
+        cancel_rx: broadcast::Receiver<()>,
+    ) -> Self {
         let (v1_translation_tx, v1_translation_rx) =
             mpsc::channel(Self::MAX_TRANSLATION_CHANNEL_SIZE);
         let (v2_translation_tx, v2_translation_rx) =
@@ -72,14 +261,26 @@ impl ConnTranslation {
             v1_translation_rx,
             v2_conn,
             v2_translation_rx,
+            metrics,
+            cancel_rx,
         }
     }
 
     async fn v1_handle_frame(
         translation: &mut V2ToV1Translation,
         frame: v1::framing::Frame,
+        metrics: Option<&Metrics>,
     ) -> Result<()> {
-        let v1_msg = v1::build_message_from_frame(frame)?;
+        if let Some(metrics) = metrics {
+            metrics
+                .frames
+                .with_label_values(&[metrics::DIRECTION_V1])
+                .inc();
+        }
+        let v1_msg = v1::build_message_from_frame(frame).map_err(|e| {
+            Self::count_translation_error(metrics);
+            e
+        })?;
         v1_msg.accept(translation).await;
         Ok(())
     }
@@ -88,10 +289,20 @@ impl ConnTranslation {
     async fn v2_handle_frame(
         translation: &mut V2ToV1Translation,
         frame: v2::framing::Frame,
+        metrics: Option<&Metrics>,
     ) -> Result<()> {
+        if let Some(metrics) = metrics {
+            metrics
+                .frames
+                .with_label_values(&[metrics::DIRECTION_V2])
+                .inc();
+        }
         match frame.header.extension_type {
             v2::extensions::BASE => {
-                let event_msg = v2::build_message_from_frame(frame)?;
+                let event_msg = v2::build_message_from_frame(frame).map_err(|e| {
+                    Self::count_translation_error(metrics);
+                    e
+                })?;
                 event_msg.accept(translation).await;
             }
             // Report any other extension down the line
@@ -102,6 +313,12 @@ impl ConnTranslation {
         Ok(())
     }
 
+    fn count_translation_error(metrics: Option<&Metrics>) {
+        if let Some(metrics) = metrics {
+            metrics.translation_errors.inc();
+        }
+    }
+
     /// Attempt to send a frame via a specified connection. Attempt to send 'None' results in an
     /// error. The intention is to have a single place for sending out frames and handling
     /// errors/timeouts.
@@ -109,18 +326,27 @@ impl ConnTranslation {
         connection: &mut S,
         frame: Option<v2::framing::Frame>,
         peer_addr: &SocketAddr,
+        metrics: Option<&Metrics>,
     ) -> Result<()>
     where
         S: v2::FramedSink,
     {
-        let status = match frame {
-            Some(v2_translated_frame) => connection.send(v2_translated_frame).await,
+        let v2_translated_frame = match frame {
+            Some(v2_translated_frame) => v2_translated_frame,
             None => Err(ErrorKind::Io("No more frames".to_string()))?,
         };
-        status.map_err(|e| {
-            info!("Send error: {} for (peer: {:?})", e, peer_addr);
-            e.into()
-        })
+        let frame_len = v2_translated_frame.len();
+        let status = connection.send(v2_translated_frame).await;
+        status
+            .map(|()| {
+                if let Some(metrics) = metrics {
+                    metrics.bytes_sent.inc_by(frame_len as i64);
+                }
+            })
+            .map_err(|e| {
+                info!("Send error: {} for (peer: {:?})", e, peer_addr);
+                e.into()
+            })
     }
 
     /// Send all V2 frames via the specified V2 connection
@@ -130,6 +356,7 @@ impl ConnTranslation {
         mut conn_sender: S,
         mut translation_receiver: mpsc::Receiver<v2::Frame>,
         peer_addr: SocketAddr,
+        metrics: Option<Metrics>,
     ) -> Result<()>
     where
         S: v2::FramedSink,
@@ -140,7 +367,12 @@ impl ConnTranslation {
             select! {
                 // Send out frames translated into V2
                 v2_translated_frame = translation_receiver.next().fuse() => {
-                    Self::v2_try_send_frame(&mut conn_sender, v2_translated_frame, &peer_addr)
+                    Self::v2_try_send_frame(
+                        &mut conn_sender,
+                        v2_translated_frame,
+                        &peer_addr,
+                        metrics.as_ref(),
+                    )
                         .await?;
                 },
             }
@@ -150,6 +382,8 @@ impl ConnTranslation {
     async fn run(self) -> Result<()> {
         let mut v1_translation_rx = self.v1_translation_rx;
         let mut translation = self.translation;
+        let metrics = self.metrics;
+        let mut cancel_rx = self.cancel_rx;
 
         let v1_peer_addr = self.v1_conn.peer_addr()?;
         let v2_peer_addr = self.v2_conn.peer_addr()?;
@@ -158,8 +392,6 @@ impl ConnTranslation {
         let (mut v1_conn_tx, mut v1_conn_rx) = self.v1_conn.into_inner().split();
         let (v2_conn_tx, mut v2_conn_rx) = self.v2_conn.into_inner().split();
 
-        // TODO factor out the frame pumping functionality and append the JoinHandle of this task
-        //  to the select statement to detect any problems and to terminate the translation, too
         // V1 message send out loop
         let v1_send_task = async move {
             while let Some(frame) = v1_translation_rx.next().await {
@@ -169,61 +401,140 @@ impl ConnTranslation {
                 }
             }
         };
-        tokio::spawn(v1_send_task);
+        let mut v1_send_task = tokio::spawn(v1_send_task);
 
-        tokio::spawn(Self::v2_send_task(
+        let mut v2_send_task = tokio::spawn(Self::v2_send_task(
             v2_conn_tx,
             self.v2_translation_rx,
             v2_peer_addr.clone(),
+            metrics.clone(),
         ));
 
-        // TODO: add cancel handler into the select statement
-        loop {
-            select! {
-                // Receive V1 frame and translate it to V2 message
-                v1_frame = v1_conn_rx.next().timeout(Self::V1_UPSTREAM_TIMEOUT).fuse()=> {
-                    // Unwrap the potentially elapsed timeout
-                    match v1_frame? {
-                        Some(v1_frame) => {
-                            Self::v1_handle_frame(&mut translation, v1_frame?).await?;
-                        }
-                        None => {
-                            Err(format!(
-                                "Upstream V1 stratum connection dropped ({:?})",
-                                v1_peer_addr
-                            ))?;
-                        }
-                    }
-                },
-                // Receive V2 frame and translate it to V1 message
-                v2_frame = v2_conn_rx.next().timeout(Self::V2_DOWNSTREAM_TIMEOUT).fuse() => {
-                    match v2_frame? {
-                        Some(v2_frame) => {
-                            Self::v2_handle_frame(&mut translation, v2_frame?).await?;
+        if let Some(metrics) = &metrics {
+            metrics.open_sessions.inc();
+        }
+
+        let result: Result<()> = async {
+            loop {
+                select! {
+                    // Receive V1 frame and translate it to V2 message
+                    v1_frame = v1_conn_rx.next().timeout(Self::V1_UPSTREAM_TIMEOUT).fuse()=> {
+                        // Unwrap the potentially elapsed timeout
+                        let v1_frame = v1_frame.map_err(|e| {
+                            if let Some(metrics) = &metrics {
+                                metrics.v1_upstream_timeouts.inc();
+                            }
+                            e
+                        })?;
+                        match v1_frame {
+                            Some(v1_frame) => {
+                                Self::v1_handle_frame(&mut translation, v1_frame?, metrics.as_ref())
+                                    .await?;
+                            }
+                            None => {
+                                Err(format!(
+                                    "Upstream V1 stratum connection dropped ({:?})",
+                                    v1_peer_addr
+                                ))?;
+                            }
                         }
-                        None => {
-                            Err(format!("V2 client disconnected ({:?})", v2_peer_addr))?;
+                    },
+                    // Receive V2 frame and translate it to V1 message
+                    v2_frame = v2_conn_rx.next().timeout(Self::V2_DOWNSTREAM_TIMEOUT).fuse() => {
+                        let v2_frame = v2_frame.map_err(|e| {
+                            if let Some(metrics) = &metrics {
+                                metrics.v2_downstream_timeouts.inc();
+                            }
+                            e
+                        })?;
+                        match v2_frame {
+                            Some(v2_frame) => {
+                                Self::v2_handle_frame(&mut translation, v2_frame?, metrics.as_ref())
+                                    .await?;
+                            }
+                            None => {
+                                Err(format!("V2 client disconnected ({:?})", v2_peer_addr))?;
+                            }
                         }
-                    }
+                    },
+                    // The V1 send task ended - either it hit a connection error (already logged)
+                    // or panicked - either way this session can no longer make forward progress
+                    v1_send_result = (&mut v1_send_task).fuse() => {
+                        v1_send_result
+                            .map_err(|e| format!("V1 send task panicked: {}", e))?;
+                        Err(format!(
+                            "V1 send task terminated unexpectedly ({:?})",
+                            v1_peer_addr
+                        ))?;
+                    },
+                    // Same as above, for the V2 send task
+                    v2_send_result = (&mut v2_send_task).fuse() => {
+                        v2_send_result.map_err(|e| format!("V2 send task panicked: {}", e))??;
+                        Err(format!(
+                            "V2 send task terminated unexpectedly ({:?})",
+                            v2_peer_addr
+                        ))?;
+                    },
+                    // ProxyServer::run observed a quit signal - drain this session cleanly instead
+                    // of leaving it running past the listener's lifetime
+                    _ = cancel_rx.recv().fuse() => {
+                        info!(
+                            "Terminating translation for (v1: {:?}, v2: {:?}) - quit signal received",
+                            v1_peer_addr, v2_peer_addr
+                        );
+                        return Ok(());
+                    },
                 }
             }
         }
+        .await;
+
+        // Whatever ended the session above, the send tasks no longer have anyone to hand frames
+        // to - abort them instead of leaving them running detached in the background.
+        v1_send_task.abort();
+        v2_send_task.abort();
+
+        if let Some(metrics) = &metrics {
+            metrics.open_sessions.dec();
+        }
+        result
     }
 }
 
-async fn handle_connection(conn_v2: Connection<v2::Framing>, stratum_addr: SocketAddr) {
-    info!("Opening connection to V1: {:?}", stratum_addr);
-    let conn_v1 = match Connection::connect(&stratum_addr).await {
-        Ok(conn) => conn,
-        Err(e) => {
-            error!("Connection to Stratum V1 failed: {}", e);
+async fn handle_connection(
+    conn_v2: Connection<v2::Framing>,
+    backends: Arc<Vec<Backend>>,
+    backend_cursor: Arc<AtomicUsize>,
+    proxy_protocol: ProxyProtocolVersion,
+    metrics: Option<Metrics>,
+    cancel_rx: broadcast::Receiver<()>,
+) {
+    // At this point, we already know the peer address is valid
+    let peer_addr = conn_v2.peer_addr().expect("BUG: invalid peer address");
+
+    let (stratum_addr, mut v1_stream) = match connect_upstream(&backends, &backend_cursor).await {
+        Some(result) => result,
+        None => {
+            error!("No healthy Stratum V1 backend available");
             return;
         }
     };
+    info!("Opened connection to V1: {:?}", stratum_addr);
+
+    if proxy_protocol == ProxyProtocolVersion::V2 {
+        if let Err(e) =
+            write_proxy_protocol_v2_header(&mut v1_stream, peer_addr, stratum_addr).await
+        {
+            error!(
+                "Failed to write PROXY protocol header to {}: {}",
+                stratum_addr, e
+            );
+            return;
+        }
+    }
+    let conn_v1 = Connection::<v1::Framing>::new(v1_stream);
     info!("V1 connection setup");
-    // At this point, we already know the peer address is valid
-    let peer_addr = conn_v2.peer_addr().expect("BUG: invalid peer address");
-    let translation = ConnTranslation::new(conn_v2, conn_v1);
+    let translation = ConnTranslation::new(conn_v2, conn_v1, metrics, cancel_rx);
 
     if let Err(e) = translation.run().await {
         info!("Terminating connection from: {} ({})", peer_addr, e);
@@ -241,34 +552,70 @@ async fn handle_connection(conn_v2: Connection<v2::Framing>, stratum_addr: Socke
 pub struct ProxyServer {
     server: Server<v2::Framing>,
     listen_addr: SocketAddr,
-    stratum_addr: SocketAddr,
+    backends: Arc<Vec<Backend>>,
+    /// Round-robin position into `backends`, shared so every connection picks up where the last
+    /// one left off instead of always starting over at the first backend
+    backend_cursor: Arc<AtomicUsize>,
+    proxy_protocol: ProxyProtocolVersion,
+    /// Metrics registry handle cloned into every `handle_connection`/`ConnTranslation`, `None`
+    /// when `listen` was given no `metrics_listen_addr`
+    metrics: Option<Metrics>,
+    /// Broadcasts a quit notification to every live `ConnTranslation`, so sessions drain instead
+    /// of outliving the listener - a fresh `subscribe()`'d receiver is handed to each one
+    cancel_tx: broadcast::Sender<()>,
     quit_tx: mpsc::Sender<()>,
     quit_rx: Option<mpsc::Receiver<()>>,
 }
 
 impl ProxyServer {
-    /// Constructor, binds the listening socket
-    pub fn listen(listen_addr: String, stratum_addr: String) -> Result<ProxyServer> {
+    /// Constructor, binds the listening socket. `stratum_addrs` is the upstream V1 pool farm to
+    /// load-balance across round-robin, with transparent failover to the next one if a connect
+    /// attempt fails - see `connect_upstream`. `proxy_protocol` controls whether upstream V1
+    /// connections are prefixed with a PROXY protocol header - see `ProxyProtocolVersion`.
+    /// `metrics_listen_addr` optionally binds a `/metrics` HTTP endpoint - see `Metrics`.
+    pub fn listen(
+        listen_addr: String,
+        stratum_addrs: Vec<String>,
+        proxy_protocol: ProxyProtocolVersion,
+        metrics_listen_addr: Option<String>,
+    ) -> Result<ProxyServer> {
         let listen_addr = listen_addr
             .to_socket_addrs()
             .context(ErrorKind::BadIp(listen_addr))?
             .next()
             .expect("Cannot resolve any IP address");
 
-        let stratum_addr = stratum_addr
-            .to_socket_addrs()
-            .context(ErrorKind::BadIp(stratum_addr))?
-            .next()
-            .expect("Cannot resolve any IP address");
+        // Addresses aren't resolved up front - `Backend` re-resolves its hostname on every
+        // connection attempt, see `Backend::resolve`.
+        let backends: Vec<Backend> = stratum_addrs.into_iter().map(Backend::new).collect();
 
         let server = Server::<v2::Framing>::bind(&listen_addr)?;
 
         let (quit_tx, quit_rx) = mpsc::channel(1);
+        let (cancel_tx, _) = broadcast::channel(1);
+
+        let metrics = match metrics_listen_addr {
+            Some(metrics_listen_addr) => {
+                let metrics_listen_addr = metrics_listen_addr
+                    .to_socket_addrs()
+                    .context(ErrorKind::BadIp(metrics_listen_addr))?
+                    .next()
+                    .expect("Cannot resolve any IP address");
+                let metrics = Metrics::new();
+                tokio::spawn(metrics.clone().serve(metrics_listen_addr));
+                Some(metrics)
+            }
+            None => None,
+        };
 
         Ok(ProxyServer {
             server,
             listen_addr,
-            stratum_addr,
+            backends: Arc::new(backends),
+            backend_cursor: Arc::new(AtomicUsize::new(0)),
+            proxy_protocol,
+            metrics,
+            cancel_tx,
             quit_rx: Some(quit_rx),
             quit_tx,
         })
@@ -320,11 +667,25 @@ impl ProxyServer {
         let do_connect = move || {
             let conn = conn?;
             let peer_addr = conn.peer_addr()?;
-            tokio::spawn(handle_connection(conn, self.stratum_addr));
+            tokio::spawn(handle_connection(
+                conn,
+                self.backends.clone(),
+                self.backend_cursor.clone(),
+                self.proxy_protocol,
+                self.metrics.clone(),
+                self.cancel_tx.subscribe(),
+            ));
             Ok(peer_addr)
         };
 
-        Some(do_connect())
+        let result = do_connect();
+        if let Some(metrics) = &self.metrics {
+            match &result {
+                Ok(_) => metrics.accepted_connections.inc(),
+                Err(_) => metrics.failed_connections.inc(),
+            }
+        }
+        Some(result)
     }
 
     /// Creates a proxy server task that calls `.next()`
@@ -333,8 +694,9 @@ impl ProxyServer {
     /// connection errors via the logging crate.
     pub async fn run(mut self) {
         info!(
-            "Stratum proxy service starting @ {} -> {}",
-            self.listen_addr, self.stratum_addr
+            "Stratum proxy service starting @ {} -> {:?}",
+            self.listen_addr,
+            self.backends.iter().map(|b| &b.host).collect::<Vec<_>>()
         );
 
         while let Some(result) = self.next().await {
@@ -344,6 +706,10 @@ impl ProxyServer {
             }
         }
 
+        // Tell every still-running ConnTranslation to drain and stop - ignore the error, it only
+        // means no session was alive to receive it
+        let _ = self.cancel_tx.send(());
+
         info!("Stratum proxy service terminated");
     }
 }
@@ -25,12 +25,13 @@
 pub mod test_utils;
 
 use async_trait::async_trait;
+use embedded_hal::blocking::i2c::{Read as HalRead, Write as HalWrite, WriteRead as HalWriteRead};
 use std::fmt::{self, Display};
 use std::sync::Arc;
 use thiserror::Error;
 
 use futures::lock::Mutex;
-use ii_async_compat::futures;
+use ii_async_compat::{futures, tokio};
 
 /// Local error definition
 #[derive(Error, Debug)]
@@ -43,6 +44,14 @@ pub enum Error {
     TestInaccessibleRegister(Address, u8),
     #[error("general error {0}")]
     General(String),
+    #[error("embedded-hal I2C controller error: {0}")]
+    Hal(String),
+    #[error("device at address {addr} did not ACK register {reg:#02x}")]
+    Nack { addr: Address, reg: u8 },
+    #[error("lost arbitration to another master")]
+    ArbitrationLost,
+    #[error("bus operation timed out")]
+    Timeout,
 }
 
 /// Convenience type alias
@@ -83,6 +92,105 @@ where
     async fn read(&mut self, addr: Address, reg: u8) -> Result<u8>;
 
     async fn write(&mut self, addr: Address, reg: u8, val: u8) -> Result<()>;
+
+    /// Write a single raw byte with no register/value split - for command-based protocols
+    /// (e.g. TSYS01's reset/start-conversion commands) that have no addressable registers.
+    async fn write_raw(&mut self, addr: Address, byte: u8) -> Result<()>;
+
+    /// Write a single raw command byte, then read back `buf.len()` sequential bytes. The
+    /// counterpart to `write_raw` for commands that return multi-byte results (ADC samples,
+    /// calibration words) instead of a single register byte.
+    async fn read_raw(&mut self, addr: Address, cmd: u8, buf: &mut [u8]) -> Result<()>;
+
+    /// Read `buf.len()` sequential bytes with no preceding write - for devices (e.g. HTU21's
+    /// no-hold measurement mode) where the triggering command and the readback of its result are
+    /// separate bus transactions with a conversion delay in between.
+    async fn read_only(&mut self, addr: Address, buf: &mut [u8]) -> Result<()>;
+
+    /// Write `reg`, then read `buf.len()` bytes back without releasing the bus in between
+    /// (repeated-START) - the standard embedded-hal `WriteRead` transaction. Needed whenever an
+    /// intervening STOP would reset the device's register pointer. The default implementation
+    /// is only a `write_raw` followed by a separate `read_only` - two transactions, with no
+    /// repeated-START; backends that can issue a real `WriteRead` override it.
+    async fn write_read(&mut self, addr: Address, reg: u8, buf: &mut [u8]) -> Result<()> {
+        self.write_raw(addr, reg).await?;
+        self.read_only(addr, buf).await
+    }
+
+    /// Read `buf.len()` consecutive registers starting at `reg` in one transaction, relying on
+    /// the device's register-pointer auto-increment instead of one `read` round trip per byte.
+    /// Default implementation falls back to `write_read`.
+    async fn read_block(&mut self, addr: Address, reg: u8, buf: &mut [u8]) -> Result<()> {
+        self.write_read(addr, reg, buf).await
+    }
+
+    /// Write `data` to consecutive registers starting at `reg` in one transaction, relying on
+    /// the device's register-pointer auto-increment. Default implementation falls back to one
+    /// `write` per byte for backends that can't do real block transfers.
+    async fn write_block(&mut self, addr: Address, reg: u8, data: &[u8]) -> Result<()> {
+        for (i, &byte) in data.iter().enumerate() {
+            self.write(addr, reg + i as u8, byte).await?;
+        }
+        Ok(())
+    }
+
+    /// Issue a START condition. Optional low-level framing primitive for callers driving a
+    /// multi-step protocol explicitly (e.g. a write followed by a repeated-START read that has
+    /// to stay one transaction at the wire level). Backends that only expose whole-transaction
+    /// ops (most of them - see `write_read`'s default) have nothing useful to do here, so the
+    /// default is a no-op; only a backend with real bit-level bus control needs to override it.
+    async fn start(&mut self, _addr: Address) -> Result<()> {
+        Ok(())
+    }
+
+    /// Issue a repeated-START condition (a START without a preceding STOP). See `start`.
+    async fn restart(&mut self, _addr: Address) -> Result<()> {
+        Ok(())
+    }
+
+    /// Issue a STOP condition. See `start`.
+    async fn stop(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Sample the SDA line's current level. Used by `recover`'s default implementation to tell
+    /// whether a wedged slave has released the bus yet; a backend with no way to read SDA
+    /// independently of a transaction (i.e. one that can't get wedged the way a bit-banged
+    /// controller can) can leave the default, which reports the bus as already free.
+    async fn read_sda(&mut self) -> Result<bool> {
+        Ok(true)
+    }
+
+    /// Pulse SCL once (low then high) without driving SDA, the building block of the bus
+    /// recovery sequence in `recover`. No-op by default - see `read_sda`.
+    async fn pulse_clock(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Recover a wedged bus: a slave that dropped off mid-transfer can be left holding SDA low,
+    /// which blocks every other transaction. Clock up to 9 pulses (the most an addressed slave
+    /// could ever need to finish clocking out a byte and release SDA) while sampling SDA after
+    /// each one, then issue a STOP. Returns `Ok(())` once SDA reads high, or `Err(Error::Timeout)`
+    /// if it's still held low after all 9 pulses and the STOP.
+    ///
+    /// The default implementation is built purely on `read_sda`/`pulse_clock`/`stop`, so backends
+    /// that can't get wedged (or can't see `SDA` outside of a transaction) inherit correct
+    /// behavior - "already free" - for free; only a bit-banged/GPIO-level backend needs to
+    /// override these three to make `recover` do anything real.
+    async fn recover(&mut self) -> Result<()> {
+        for _ in 0..9 {
+            if self.read_sda().await? {
+                break;
+            }
+            self.pulse_clock().await?;
+        }
+        self.stop().await?;
+        if self.read_sda().await? {
+            Ok(())
+        } else {
+            Err(Error::Timeout)
+        }
+    }
 }
 
 /// `Device` represents (async) ops on a device on I2C bus
@@ -102,8 +210,35 @@ where
     /// * `reg_read_back` - address of register to read! because it often is that those
     ///   two are different
     /// * `val` - value to write to the register
+    ///
+    /// A bus-level failure on either leg (in particular `Error::Nack`, if the device didn't
+    /// acknowledge) propagates as-is instead of being folded into `Error::FailedReadBack`, so
+    /// callers can tell "device is gone" from "device wrote the wrong value" and retry only the
+    /// former.
     async fn write_readback(&mut self, reg: u8, reg_read_back: u8, val: u8) -> Result<()>;
 
+    /// Write a single raw command byte - see `Bus::write_raw`.
+    async fn write_command(&mut self, cmd: u8) -> Result<()>;
+
+    /// Write a single raw command byte, then read back `buf.len()` sequential bytes - see
+    /// `Bus::read_raw`.
+    async fn read_command(&mut self, cmd: u8, buf: &mut [u8]) -> Result<()>;
+
+    /// Read `buf.len()` sequential bytes with no preceding write - see `Bus::read_only`.
+    async fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()>;
+
+    /// Write `reg` then read `buf.len()` bytes back without releasing the bus - see
+    /// `Bus::write_read`.
+    async fn write_read(&mut self, reg: u8, buf: &mut [u8]) -> Result<()>;
+
+    /// Read `buf.len()` consecutive registers starting at `reg` in one transaction - see
+    /// `Bus::read_block`.
+    async fn read_block(&mut self, reg: u8, buf: &mut [u8]) -> Result<()>;
+
+    /// Write `data` to consecutive registers starting at `reg` in one transaction - see
+    /// `Bus::write_block`.
+    async fn write_block(&mut self, reg: u8, data: &[u8]) -> Result<()>;
+
     /// Return I2C address of device
     fn get_address(&self) -> Address;
 }
@@ -153,23 +288,204 @@ where
         }
         Ok(())
     }
+
+    async fn write_command(&mut self, cmd: u8) -> Result<()> {
+        self.bus.write_raw(self.address, cmd).await
+    }
+
+    async fn read_command(&mut self, cmd: u8, buf: &mut [u8]) -> Result<()> {
+        self.bus.read_raw(self.address, cmd, buf).await
+    }
+
+    async fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.bus.read_only(self.address, buf).await
+    }
+
+    async fn write_read(&mut self, reg: u8, buf: &mut [u8]) -> Result<()> {
+        self.bus.write_read(self.address, reg, buf).await
+    }
+
+    async fn read_block(&mut self, reg: u8, buf: &mut [u8]) -> Result<()> {
+        self.bus.read_block(self.address, reg, buf).await
+    }
+
+    async fn write_block(&mut self, reg: u8, data: &[u8]) -> Result<()> {
+        self.bus.write_block(self.address, reg, data).await
+    }
 }
 
-/// We can make any bus shared by wrapping it in a lock
-#[derive(Clone)]
-pub struct SharedBus<T> {
+/// Lightweight `Device` handle that stores only its `Address` and borrows the bus for each
+/// operation, instead of owning/cloning it like `DeviceOnBus` or locking it like `SharedBus`.
+/// Meant for the common driver-initialization sequence, where a single task owns the bus
+/// outright before any other task could be scheduled: `bus` can be multiplexed across many
+/// devices with no `Clone` bound and no synchronization, the compiler enforcing exclusive access
+/// per operation instead of a runtime lock.
+#[derive(Clone, Copy)]
+pub struct BorrowingDevice {
+    address: Address,
+}
+
+impl BorrowingDevice {
+    pub fn new(address: Address) -> Self {
+        Self { address }
+    }
+
+    /// Borrow `bus` for the lifetime of the returned handle - e.g.
+    /// `dev.borrow(&mut bus).write(reg, val).await`.
+    pub fn borrow<'a, T>(&self, bus: &'a mut T) -> DeviceRef<'a, T> {
+        DeviceRef {
+            bus,
+            address: self.address,
+        }
+    }
+
+    pub fn get_address(&self) -> Address {
+        self.address
+    }
+}
+
+/// A `BorrowingDevice` tied to a `&mut` borrow of its bus, returned by `BorrowingDevice::borrow`.
+/// Mirrors `Device`'s operations, but as inherent methods rather than a trait impl, since the
+/// borrowed lifetime doesn't fit `Device`'s `Sync + Send + 'static`-flavored `async_trait` bound.
+pub struct DeviceRef<'a, T> {
+    bus: &'a mut T,
+    address: Address,
+}
+
+impl<'a, T> DeviceRef<'a, T>
+where
+    T: Bus,
+{
+    pub async fn read(&mut self, reg: u8) -> Result<u8> {
+        self.bus.read(self.address, reg).await
+    }
+
+    pub async fn write(&mut self, reg: u8, val: u8) -> Result<()> {
+        self.bus.write(self.address, reg, val).await
+    }
+
+    /// See `Device::write_readback`.
+    pub async fn write_readback(&mut self, reg: u8, reg_read_back: u8, val: u8) -> Result<()> {
+        self.write(reg, val).await?;
+        let new_val = self.read(reg_read_back).await?;
+        if val != new_val {
+            Err(Error::FailedReadBack(reg, val, new_val))?
+        }
+        Ok(())
+    }
+
+    pub async fn write_command(&mut self, cmd: u8) -> Result<()> {
+        self.bus.write_raw(self.address, cmd).await
+    }
+
+    pub async fn read_command(&mut self, cmd: u8, buf: &mut [u8]) -> Result<()> {
+        self.bus.read_raw(self.address, cmd, buf).await
+    }
+
+    pub async fn read_bytes(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.bus.read_only(self.address, buf).await
+    }
+
+    pub async fn write_read(&mut self, reg: u8, buf: &mut [u8]) -> Result<()> {
+        self.bus.write_read(self.address, reg, buf).await
+    }
+
+    pub async fn read_block(&mut self, reg: u8, buf: &mut [u8]) -> Result<()> {
+        self.bus.read_block(self.address, reg, buf).await
+    }
+
+    pub async fn write_block(&mut self, reg: u8, data: &[u8]) -> Result<()> {
+        self.bus.write_block(self.address, reg, data).await
+    }
+
+    pub fn get_address(&self) -> Address {
+        self.address
+    }
+}
+
+/// A synchronous counterpart to `SharedBus` for chip bring-up code that runs before the async
+/// executor is up and so can't `.await` anything. Shares the exact same lock type `SharedBus`
+/// does (just driven by busy-polling `try_lock` instead of an async wait), so `into_async` can
+/// hand the identical backing state - and any register values a device set up during blocking
+/// init - to the async world unchanged once the executor starts.
+pub struct BlockingSharedBus<T> {
     inner: Arc<Mutex<T>>,
 }
 
-impl<T> SharedBus<T>
+impl<T> BlockingSharedBus<T> {
+    pub fn new(bus: T) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(bus)),
+        }
+    }
+
+    /// Spin until the lock is free, then run `op` against the bus.
+    fn with_locked<R>(&self, op: impl FnOnce(&mut T) -> R) -> R {
+        loop {
+            if let Some(mut bus) = self.inner.try_lock() {
+                return op(&mut bus);
+            }
+            std::thread::yield_now();
+        }
+    }
+
+    /// Hand the same underlying bus and lock over to the async world - a device configured
+    /// during blocking init keeps its settings once callers switch to `SharedBus`.
+    pub fn into_async(self) -> SharedBus<T> {
+        SharedBus { inner: self.inner }
+    }
+}
+
+/// Manual impl so sharing a bus never requires `T: Clone` - see `SharedBus`'s.
+impl<T> Clone for BlockingSharedBus<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> BlockingSharedBus<T>
 where
     T: Bus,
 {
+    pub fn read(&self, addr: Address, reg: u8) -> Result<u8> {
+        self.with_locked(|bus| futures::executor::block_on(bus.read(addr, reg)))
+    }
+
+    pub fn write(&self, addr: Address, reg: u8, val: u8) -> Result<()> {
+        self.with_locked(|bus| futures::executor::block_on(bus.write(addr, reg, val)))
+    }
+}
+
+/// We can make any bus shared by wrapping it in a lock
+pub struct SharedBus<T> {
+    inner: Arc<Mutex<T>>,
+}
+
+impl<T> SharedBus<T> {
     pub fn new(bus: T) -> Self {
         Self {
             inner: Arc::new(Mutex::new(bus)),
         }
     }
+
+    /// Lock the shared bus, returning the guard itself rather than performing a single op with
+    /// it - so a caller that needs more than one downstream operation to be atomic (e.g.
+    /// `Mux`'s channel-select write followed by the actual transfer) can hold the lock across
+    /// all of them instead of once per call.
+    pub async fn lock(&self) -> futures::lock::MutexGuard<'_, T> {
+        self.inner.lock().await
+    }
+}
+
+/// Manual impl so sharing a bus never requires `T: Clone` - only the `Arc` is actually cloned.
+impl<T> Clone for SharedBus<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
 }
 
 #[async_trait]
@@ -178,14 +494,291 @@ where
     T: Bus,
 {
     async fn read(&mut self, addr: Address, reg: u8) -> Result<u8> {
-        let mut bus = self.inner.lock().await;
+        let mut bus = self.lock().await;
         bus.read(addr, reg).await
     }
 
     async fn write(&mut self, addr: Address, reg: u8, val: u8) -> Result<()> {
-        let mut bus = self.inner.lock().await;
+        let mut bus = self.lock().await;
         bus.write(addr, reg, val).await
     }
+
+    async fn write_raw(&mut self, addr: Address, byte: u8) -> Result<()> {
+        let mut bus = self.lock().await;
+        bus.write_raw(addr, byte).await
+    }
+
+    async fn read_raw(&mut self, addr: Address, cmd: u8, buf: &mut [u8]) -> Result<()> {
+        let mut bus = self.lock().await;
+        bus.read_raw(addr, cmd, buf).await
+    }
+
+    async fn read_only(&mut self, addr: Address, buf: &mut [u8]) -> Result<()> {
+        let mut bus = self.lock().await;
+        bus.read_only(addr, buf).await
+    }
+
+    /// Overridden (rather than relying on the default) so the whole repeated-START transaction
+    /// happens under one lock acquisition, not one per `write_raw`/`read_only` leg.
+    async fn write_read(&mut self, addr: Address, reg: u8, buf: &mut [u8]) -> Result<()> {
+        let mut bus = self.lock().await;
+        bus.write_read(addr, reg, buf).await
+    }
+
+    /// Overridden so the whole block write happens under one lock acquisition, not one per byte.
+    async fn write_block(&mut self, addr: Address, reg: u8, data: &[u8]) -> Result<()> {
+        let mut bus = self.lock().await;
+        bus.write_block(addr, reg, data).await
+    }
+}
+
+/// Downstream bus plus which channel was last selected on it, bundled together so both are
+/// protected by the same lock - see `Mux`.
+struct MuxState<T> {
+    bus: T,
+    last_channel: Option<u8>,
+}
+
+/// A TCA9548A-style I2C channel switch sitting on top of bus `T`: several downstream devices
+/// that would otherwise collide on the same `Address` are disambiguated by selecting one of the
+/// mux's channels first. `channel` hands out a `MuxChannel` - itself a `Bus` - for devices wired
+/// behind that channel.
+pub struct Mux<T> {
+    shared: SharedBus<MuxState<T>>,
+    address: Address,
+    select_reg: u8,
+}
+
+impl<T> Mux<T>
+where
+    T: Bus,
+{
+    /// `address` is the mux chip's own I2C address; `select_reg` is the register its one-hot
+    /// channel mask is written to.
+    pub fn new(bus: T, address: Address, select_reg: u8) -> Self {
+        Self {
+            shared: SharedBus::new(MuxState {
+                bus,
+                last_channel: None,
+            }),
+            address,
+            select_reg,
+        }
+    }
+
+    /// Get a `Bus` for devices wired behind mux channel `n`.
+    pub fn channel(&self, n: u8) -> MuxChannel<T> {
+        MuxChannel {
+            shared: self.shared.clone(),
+            address: self.address,
+            select_reg: self.select_reg,
+            channel: n,
+        }
+    }
+}
+
+/// One addressable channel behind a `Mux`, itself a usable `Bus`. Selecting this channel and
+/// performing the downstream transfer happen while holding the mux's shared lock for the whole
+/// duration (see `select`), so a concurrently-scheduled task working another channel can never
+/// observe - or clobber - a half-completed channel switch.
+pub struct MuxChannel<T> {
+    shared: SharedBus<MuxState<T>>,
+    address: Address,
+    select_reg: u8,
+    channel: u8,
+}
+
+/// Manual impl so a `MuxChannel` never requires `T: Clone` - see `SharedBus`'s.
+impl<T> Clone for MuxChannel<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+            address: self.address,
+            select_reg: self.select_reg,
+            channel: self.channel,
+        }
+    }
+}
+
+impl<T> MuxChannel<T>
+where
+    T: Bus,
+{
+    /// Lock the mux's shared bus and, unless this channel is already selected, write its
+    /// one-hot select byte before returning the still-locked guard - the caller performs its
+    /// actual transfer on the returned guard so the select and the transfer stay atomic.
+    async fn select(&self) -> Result<futures::lock::MutexGuard<'_, MuxState<T>>> {
+        let mut state = self.shared.lock().await;
+        if state.last_channel != Some(self.channel) {
+            let select_mask = 1u8 << self.channel;
+            state
+                .bus
+                .write(self.address, self.select_reg, select_mask)
+                .await?;
+            state.last_channel = Some(self.channel);
+        }
+        Ok(state)
+    }
+}
+
+#[async_trait]
+impl<T> Bus for MuxChannel<T>
+where
+    T: Bus,
+{
+    async fn read(&mut self, addr: Address, reg: u8) -> Result<u8> {
+        let mut state = self.select().await?;
+        state.bus.read(addr, reg).await
+    }
+
+    async fn write(&mut self, addr: Address, reg: u8, val: u8) -> Result<()> {
+        let mut state = self.select().await?;
+        state.bus.write(addr, reg, val).await
+    }
+
+    async fn write_raw(&mut self, addr: Address, byte: u8) -> Result<()> {
+        let mut state = self.select().await?;
+        state.bus.write_raw(addr, byte).await
+    }
+
+    async fn read_raw(&mut self, addr: Address, cmd: u8, buf: &mut [u8]) -> Result<()> {
+        let mut state = self.select().await?;
+        state.bus.read_raw(addr, cmd, buf).await
+    }
+
+    async fn read_only(&mut self, addr: Address, buf: &mut [u8]) -> Result<()> {
+        let mut state = self.select().await?;
+        state.bus.read_only(addr, buf).await
+    }
+
+    /// Overridden so the channel select and the whole repeated-START transaction happen under
+    /// one `select()` call, not one per `write_raw`/`read_only` leg.
+    async fn write_read(&mut self, addr: Address, reg: u8, buf: &mut [u8]) -> Result<()> {
+        let mut state = self.select().await?;
+        state.bus.write_read(addr, reg, buf).await
+    }
+
+    /// Overridden so the channel select and the whole block write happen under one `select()`
+    /// call, not one per byte.
+    async fn write_block(&mut self, addr: Address, reg: u8, data: &[u8]) -> Result<()> {
+        let mut state = self.select().await?;
+        state.bus.write_block(addr, reg, data).await
+    }
+}
+
+/// Turn our 8-bit `Address` (r/w bit baked into bit 0) into the plain 7-bit address
+/// `embedded-hal`'s I2C traits expect.
+fn to_hal_address(addr: Address) -> u8 {
+    addr.to_readable_hw_addr() >> 1
+}
+
+/// Adapter making any blocking `embedded-hal` I2C controller usable wherever this crate's async
+/// `Bus` is expected, so a driver written against `Bus` runs unmodified on real hardware instead
+/// of only against `test_utils::FakeI2cBus`. The controller is blocking, so every transfer is
+/// bounced through `spawn_blocking`; it lives behind an `Arc<std::sync::Mutex<_>>` rather than
+/// being borrowed, since `spawn_blocking`'s closure has to own what it touches.
+pub struct EmbeddedHalBus<I> {
+    inner: Arc<std::sync::Mutex<I>>,
+}
+
+impl<I> EmbeddedHalBus<I> {
+    pub fn new(i2c: I) -> Self {
+        Self {
+            inner: Arc::new(std::sync::Mutex::new(i2c)),
+        }
+    }
+}
+
+impl<I> Clone for EmbeddedHalBus<I> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<I, E> EmbeddedHalBus<I>
+where
+    I: HalRead<Error = E> + HalWrite<Error = E> + HalWriteRead<Error = E> + Send + 'static,
+    E: Display + Send + 'static,
+{
+    /// Run `op` against the wrapped controller on a blocking-friendly executor thread, mapping
+    /// any HAL error into `Error::Hal`.
+    async fn blocking<F, R>(&self, op: F) -> Result<R>
+    where
+        F: FnOnce(&mut I) -> std::result::Result<R, E> + Send + 'static,
+        R: Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || {
+            let mut i2c = inner
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            op(&mut i2c)
+        })
+        .await
+        .expect("blocking I2C task panicked")
+        .map_err(|err| Error::Hal(err.to_string()))
+    }
+}
+
+#[async_trait]
+impl<I, E> Bus for EmbeddedHalBus<I>
+where
+    I: HalRead<Error = E> + HalWrite<Error = E> + HalWriteRead<Error = E> + Send + 'static,
+    E: Display + Send + 'static,
+{
+    async fn read(&mut self, addr: Address, reg: u8) -> Result<u8> {
+        let mut buf = [0u8];
+        self.write_read(addr, reg, &mut buf).await?;
+        Ok(buf[0])
+    }
+
+    async fn write(&mut self, addr: Address, reg: u8, val: u8) -> Result<()> {
+        let hw_addr = to_hal_address(addr);
+        self.blocking(move |i2c| i2c.write(hw_addr, &[reg, val]))
+            .await
+    }
+
+    async fn write_raw(&mut self, addr: Address, byte: u8) -> Result<()> {
+        let hw_addr = to_hal_address(addr);
+        self.blocking(move |i2c| i2c.write(hw_addr, &[byte])).await
+    }
+
+    async fn read_raw(&mut self, addr: Address, cmd: u8, buf: &mut [u8]) -> Result<()> {
+        self.write_read(addr, cmd, buf).await
+    }
+
+    async fn read_only(&mut self, addr: Address, buf: &mut [u8]) -> Result<()> {
+        let hw_addr = to_hal_address(addr);
+        let len = buf.len();
+        let read = self
+            .blocking(move |i2c| {
+                let mut data = vec![0u8; len];
+                i2c.read(hw_addr, &mut data)?;
+                Ok(data)
+            })
+            .await?;
+        buf.copy_from_slice(&read);
+        Ok(())
+    }
+
+    /// Overridden to issue one real repeated-START `WriteRead` transaction instead of the
+    /// default's separate `write_raw`/`read_only` (each of which would be its own blocking task
+    /// and its own bus transaction, losing the repeated-START guarantee).
+    async fn write_read(&mut self, addr: Address, reg: u8, buf: &mut [u8]) -> Result<()> {
+        let hw_addr = to_hal_address(addr);
+        let len = buf.len();
+        let read = self
+            .blocking(move |i2c| {
+                let mut data = vec![0u8; len];
+                i2c.write_read(hw_addr, &[reg], &mut data)?;
+                Ok(data)
+            })
+            .await?;
+        buf.copy_from_slice(&read);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -245,6 +838,31 @@ mod test {
         assert!(dev.write(4, 5).await.is_err());
     }
 
+    #[tokio::test]
+    async fn test_borrowing_device() {
+        let mut bus = test_utils::FakeI2cBus::new(Address::new(0x16), &[], Some(0), Some(0x7f));
+        let dev = BorrowingDevice::new(Address::new(0x16));
+
+        dev.borrow(&mut bus).write(6, 0x5a).await.unwrap();
+        assert_eq!(dev.borrow(&mut bus).read(6).await.unwrap(), 0x5a);
+        dev.borrow(&mut bus).write_readback(8, 8, 0xaa).await.unwrap();
+        assert!(dev.borrow(&mut bus).write_readback(8, 9, 0xaa).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_blocking_shared_bus_state_survives_into_async() {
+        let bus = test_utils::FakeI2cBus::new(Address::new(0x16), &[], Some(0), Some(0x7f));
+        let blocking = BlockingSharedBus::new(bus);
+
+        // configure the device synchronously, as init code without a running executor would
+        blocking.write(Address::new(0x16), 3, 0x42).unwrap();
+
+        // ... then hand the same backing bus over to the async world
+        let shared = blocking.into_async();
+        let mut dev = DeviceOnBus::new(shared, Address::new(0x16));
+        assert_eq!(dev.read(3).await.unwrap(), 0x42);
+    }
+
     #[tokio::test]
     async fn test_shared_i2c_bus() {
         // FakeI2cBus is not "shared" by default, clone just creates another copy
@@ -265,4 +883,25 @@ mod test {
         assert_eq!(dev1.read(5).await.unwrap(), 0x22);
         assert_eq!(dev1.read(4).await.unwrap(), 0x00);
     }
+
+    #[tokio::test]
+    async fn test_mux_selects_channel_before_downstream_transfer() {
+        let mux_address = Address::new(0x70);
+        let bus = test_utils::FakeI2cBus::new(mux_address, &[], Some(0), Some(0));
+        let mux = Mux::new(bus, mux_address, 0);
+        let mut ch2 = mux.channel(2);
+        let mut ch5 = mux.channel(5);
+
+        // touching channel 2 selects it first (one-hot bit 2 set) ...
+        ch2.write(mux_address, 1, 0xaa).await.unwrap();
+        assert_eq!(ch2.read(mux_address, 0).await.unwrap(), 1 << 2);
+
+        // ... switching to channel 5 re-selects, since the two channels share the mux's last-
+        // selected-channel state ...
+        ch5.write(mux_address, 1, 0xbb).await.unwrap();
+        assert_eq!(ch5.read(mux_address, 0).await.unwrap(), 1 << 5);
+
+        // ... and going back to channel 2 selects again rather than leaving channel 5 selected
+        assert_eq!(ch2.read(mux_address, 0).await.unwrap(), 1 << 2);
+    }
 }
@@ -0,0 +1,156 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Driver implementation of sensor driver for HTU21, a digital relative-humidity and temperature
+//! sensor. Boards that need condensation protection rather than just die temperature pair it
+//! alongside a TMP451/TSYS01-style chip; see `HTU21::read_humidity` for how that reading is
+//! surfaced given this crate's `Sensor` trait only covers temperature.
+
+use super::Result;
+use super::{Measurement, Sensor, Temperature};
+use ii_async_i2c as i2c;
+
+use async_trait::async_trait;
+use std::boxed::Box;
+use std::time::Duration;
+use tokio::time::delay_for;
+
+const CMD_SOFT_RESET: u8 = 0xfe;
+const CMD_TRIGGER_TEMP_NO_HOLD: u8 = 0xf3;
+const CMD_TRIGGER_HUMIDITY_NO_HOLD: u8 = 0xf5;
+
+/// Worst-case 14-bit temperature conversion time per the datasheet, with a little headroom.
+const TEMP_CONVERSION_DELAY: Duration = Duration::from_millis(55);
+/// Worst-case 12-bit humidity conversion time per the datasheet, with a little headroom.
+const HUMIDITY_CONVERSION_DELAY: Duration = Duration::from_millis(20);
+
+/// The bottom two bits of each returned word are status bits (measurement type and, unused here,
+/// an on-chip heater flag), not part of the 16-bit reading `S` the datasheet formulas expect.
+const STATUS_MASK: u16 = 0xfffc;
+
+/// Datasheet CRC-8 check: polynomial x^8 + x^5 + x^4 + 1 (0x31), initial value 0x00, MSB first.
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc = 0u8;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x31
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Trigger a no-hold-master measurement, wait out the conversion time, then read back and
+/// CRC-check the 2 data bytes + checksum byte, returning the masked 16-bit reading `S`.
+async fn read_measurement(
+    i2c_dev: &mut Box<dyn i2c::Device>,
+    cmd: u8,
+    conversion_delay: Duration,
+) -> Result<u16> {
+    i2c_dev.write_command(cmd).await?;
+    delay_for(conversion_delay).await;
+
+    let mut buf = [0u8; 3];
+    i2c_dev.read_bytes(&mut buf).await?;
+
+    let computed = crc8(&buf[..2]);
+    if computed != buf[2] {
+        // NOTE: this crate's `Error` enum lives in `lib.rs`, which isn't part of this checkout,
+        // so there's no dedicated variant to raise here - fall back to `i2c::Error::General`,
+        // which `Result` (an alias over it) already accepts.
+        return Err(i2c::Error::General(format!(
+            "HTU21 CRC check failed: computed {:#04x}, received {:#04x}",
+            computed, buf[2]
+        )));
+    }
+
+    Ok(u16::from_be_bytes([buf[0], buf[1]]) & STATUS_MASK)
+}
+
+/// HTU21 driver (relative humidity and temperature, no remote sensor)
+pub struct HTU21 {
+    i2c_dev: Box<dyn i2c::Device>,
+}
+
+impl HTU21 {
+    pub fn new(i2c_dev: Box<dyn i2c::Device>) -> Box<dyn Sensor> {
+        Box::new(Self { i2c_dev }) as Box<dyn Sensor>
+    }
+
+    /// Trigger a relative-humidity measurement and convert it via the datasheet's
+    /// `RH = -6 + 125*S/2^16`.
+    ///
+    /// NOTE: not yet reachable as a `Sensor::read_humidity()` - `Sensor`/`Measurement` are
+    /// defined in this crate's `lib.rs`, which isn't part of this checkout, so there's no
+    /// provided `read_humidity()` (defaulting to `NotPresent`) to override here. Once it is,
+    /// this should become that override.
+    pub async fn read_humidity(&mut self) -> Result<Measurement> {
+        let raw =
+            read_measurement(&mut self.i2c_dev, CMD_TRIGGER_HUMIDITY_NO_HOLD, HUMIDITY_CONVERSION_DELAY)
+                .await?;
+        let rh = -6.0 + 125.0 * (raw as f32) / 65536.0;
+        Ok(Measurement::Ok(rh))
+    }
+}
+
+#[async_trait]
+impl Sensor for HTU21 {
+    async fn init(&mut self) -> Result<()> {
+        self.i2c_dev.write_command(CMD_SOFT_RESET).await
+    }
+
+    async fn read_temperature(&mut self) -> Result<Temperature> {
+        let raw =
+            read_measurement(&mut self.i2c_dev, CMD_TRIGGER_TEMP_NO_HOLD, TEMP_CONVERSION_DELAY)
+                .await?;
+        let temp = -46.85 + 175.72 * (raw as f32) / 65536.0;
+        Ok(Temperature {
+            local: Measurement::Ok(temp),
+            remote: Measurement::NotPresent,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_crc8_matches_datasheet_example() {
+        // worked example from the HTU21(D) datasheet: message 0xDC checksums to 0x79
+        assert_eq!(crc8(&[0xdc]), 0x79);
+        // 0x683A -> 0x7C, also from the datasheet's CRC examples
+        assert_eq!(crc8(&[0x68, 0x3a]), 0x7c);
+    }
+
+    #[test]
+    fn test_humidity_conversion_matches_datasheet_formula() {
+        // 0x6a20 masked to clear the status bits, per the datasheet's worked RH example
+        let raw: u16 = 0x6a20 & STATUS_MASK;
+        let rh = -6.0 + 125.0 * (raw as f32) / 65536.0;
+        assert_eq!(rh, 45.818848f32);
+    }
+}
@@ -33,18 +33,211 @@ const REG_LOCAL_TEMP: u8 = 0x00;
 const REG_REMOTE_TEMP: u8 = 0x01;
 const REG_STATUS: u8 = 0x02;
 const STATUS_OPEN_CIRCUIT: u8 = 0x04;
+const STATUS_LOCAL_HIGH: u8 = 0x40;
+const STATUS_LOCAL_LOW: u8 = 0x20;
+const STATUS_REMOTE_HIGH: u8 = 0x10;
+const STATUS_REMOTE_LOW: u8 = 0x08;
+const STATUS_REMOTE_THERM: u8 = 0x01;
 const REG_CONFIG: u8 = 0x03;
 const REG_CONFIG_W: u8 = 0x09;
 const CONFIG_RANGE: u8 = 0x04;
 const REG_OFFSET: u8 = 0x11;
 const REG_REMOTE_FRAC_TEMP: u8 = 0x10;
 const REG_LOCAL_FRAC_TEMP: u8 = 0x15;
+const REG_LOCAL_HIGH_LIMIT: u8 = 0x05;
+const REG_LOCAL_LOW_LIMIT: u8 = 0x06;
+const REG_REMOTE_HIGH_LIMIT: u8 = 0x07;
+const REG_REMOTE_LOW_LIMIT: u8 = 0x08;
+const REG_LOCAL_HIGH_LIMIT_W: u8 = 0x0b;
+const REG_LOCAL_LOW_LIMIT_W: u8 = 0x0c;
+const REG_REMOTE_HIGH_LIMIT_W: u8 = 0x0d;
+const REG_REMOTE_LOW_LIMIT_W: u8 = 0x0e;
+const REG_REMOTE_THERM_LIMIT: u8 = 0x19;
+const REG_THERM_HYSTERESIS: u8 = 0x21;
+const REG_OFFSET_FRAC: u8 = 0x12;
+const REG_BETA_RANGE: u8 = 0x25;
+const CONFIG_STANDBY: u8 = 0x40;
+const REG_CONV_RATE: u8 = 0x04;
+const REG_CONV_RATE_W: u8 = 0x0a;
+const REG_ONE_SHOT: u8 = 0x0f;
+const STATUS_BUSY: u8 = 0x80;
+/// One-shot conversions complete well within the chip's max conversion time; this just bounds
+/// how many bus round-trips we're willing to spend polling for it.
+const ONE_SHOT_MAX_POLLS: u32 = 100;
 
 /// Build a temperature from internal representation
 fn make_temp(whole: u8, fract: u8) -> f32 {
     (whole as f32 - 64.0) + (fract as f32 / 256.0)
 }
 
+/// Inverse of `make_temp`'s whole-degree half: limit registers use the same extended-range
+/// (offset-by-64) encoding as the temperature registers since `generic_init` always enables
+/// `CONFIG_RANGE`.
+fn encode_temp(celsius: f32) -> u8 {
+    (celsius + 64.0).round() as u8
+}
+
+/// Programmable ALERT/THERM thresholds, mirroring the watchdog/OS behavior of LM75-class parts.
+/// All temperatures are in whole degrees Celsius.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThermalLimits {
+    pub local_high: f32,
+    pub local_low: f32,
+    pub remote_high: f32,
+    pub remote_low: f32,
+    /// Remote-sensor THERM limit: crossing it asserts the dedicated THERM pin rather than ALERT.
+    pub remote_therm: f32,
+    /// Degrees below a high/THERM limit the temperature must fall before the corresponding
+    /// status bit (and THERM pin, for `remote_therm`) clears again.
+    pub therm_hysteresis: u8,
+}
+
+/// Signed remote-diode offset correction, applied in hardware via `REG_OFFSET`/`REG_OFFSET_FRAC`
+/// to compensate a transistor-as-diode whose readings are systematically skewed, mirroring
+/// hwmon's adt7461/w83627ehf offset registers. The chip adds this to the remote reading before
+/// it ever reaches `REG_REMOTE_TEMP`, so `make_temp` doesn't need to (and mustn't) apply it again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DiodeOffset {
+    whole: i8,
+    fract: u8,
+}
+
+impl DiodeOffset {
+    /// Build an offset from thousandths of a degree Celsius; positive nudges the reading up.
+    pub fn from_millidegrees(millidegrees: i32) -> Self {
+        let whole = (millidegrees / 1000) as i8;
+        let fract = (millidegrees % 1000).abs();
+        let fract = ((fract * 256) / 1000) as u8;
+        Self { whole, fract }
+    }
+}
+
+/// ADT7461 lets the non-ideality ("beta") compensation applied to the remote diode be tuned for
+/// transistors that deviate from its assumed ideal diode - e.g. some CPU/GPU on-die diodes vs.
+/// discrete diode-connected transistors such as a 2N3904.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BetaCompensationRange {
+    /// Tuned for standard discrete diode-connected transistors.
+    Standard,
+    /// Tuned for low-beta on-die CPU/GPU diodes.
+    LowBeta,
+}
+
+/// Power mode to leave the chip in after `init()`, mirroring LM75's shutdown bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionMode {
+    /// Free-running conversion at the configured `ConversionRate`.
+    Continuous,
+    /// No periodic conversions - use `one_shot()` to sample on demand, avoiding the
+    /// self-heating and bus traffic of continuous conversion between samples.
+    Standby,
+}
+
+impl ConversionMode {
+    fn config_bits(self) -> u8 {
+        match self {
+            Self::Continuous => 0,
+            Self::Standby => CONFIG_STANDBY,
+        }
+    }
+}
+
+/// Periodic conversion rate, from 1/16 Hz up to 16 Hz. Only meaningful in `ConversionMode::Continuous`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionRate {
+    Hz1_16,
+    Hz1_8,
+    Hz1_4,
+    Hz1_2,
+    Hz1,
+    Hz2,
+    Hz4,
+    Hz8,
+    Hz16,
+}
+
+impl ConversionRate {
+    fn code(self) -> u8 {
+        match self {
+            Self::Hz1_16 => 0,
+            Self::Hz1_8 => 1,
+            Self::Hz1_4 => 2,
+            Self::Hz1_2 => 3,
+            Self::Hz1 => 4,
+            Self::Hz2 => 5,
+            Self::Hz4 => 6,
+            Self::Hz8 => 7,
+            Self::Hz16 => 8,
+        }
+    }
+}
+
+/// Tripped ALERT/THERM channels, read back from the status register so callers can react to
+/// over-temperature without polling and comparing raw temperatures in software.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThermalAlarms {
+    pub local_high: bool,
+    pub local_low: bool,
+    pub remote_high: bool,
+    pub remote_low: bool,
+    pub remote_therm: bool,
+}
+
+/// Write local/remote high/low limits plus the remote THERM limit and hysteresis register.
+async fn configure_limits(
+    i2c_dev: &mut Box<dyn i2c::Device>,
+    limits: &ThermalLimits,
+) -> Result<()> {
+    i2c_dev
+        .write_readback(
+            REG_LOCAL_HIGH_LIMIT_W,
+            REG_LOCAL_HIGH_LIMIT,
+            encode_temp(limits.local_high),
+        )
+        .await?;
+    i2c_dev
+        .write_readback(
+            REG_LOCAL_LOW_LIMIT_W,
+            REG_LOCAL_LOW_LIMIT,
+            encode_temp(limits.local_low),
+        )
+        .await?;
+    i2c_dev
+        .write_readback(
+            REG_REMOTE_HIGH_LIMIT_W,
+            REG_REMOTE_HIGH_LIMIT,
+            encode_temp(limits.remote_high),
+        )
+        .await?;
+    i2c_dev
+        .write_readback(
+            REG_REMOTE_LOW_LIMIT_W,
+            REG_REMOTE_LOW_LIMIT,
+            encode_temp(limits.remote_low),
+        )
+        .await?;
+    i2c_dev
+        .write(REG_REMOTE_THERM_LIMIT, encode_temp(limits.remote_therm))
+        .await?;
+    i2c_dev
+        .write(REG_THERM_HYSTERESIS, limits.therm_hysteresis)
+        .await?;
+    Ok(())
+}
+
+/// Read the status register's ALERT/THERM latch bits. These clear on read, so this both reads
+/// back and clears them in one round-trip - there's no separate "clear" command on this family.
+async fn read_and_clear_alarms(i2c_dev: &mut Box<dyn i2c::Device>) -> Result<ThermalAlarms> {
+    let status = i2c_dev.read(REG_STATUS).await?;
+    Ok(ThermalAlarms {
+        local_high: (status & STATUS_LOCAL_HIGH) != 0,
+        local_low: (status & STATUS_LOCAL_LOW) != 0,
+        remote_high: (status & STATUS_REMOTE_HIGH) != 0,
+        remote_low: (status & STATUS_REMOTE_LOW) != 0,
+        remote_therm: (status & STATUS_REMOTE_THERM) != 0,
+    })
+}
+
 /// Read both local and remote temperatures.
 /// Check if external sensor is working properly.
 ///
@@ -93,29 +286,107 @@ async fn read_temperature_local(i2c_dev: &mut Box<dyn i2c::Device>) -> Result<Te
     })
 }
 
-async fn generic_init(i2c_dev: &mut Box<dyn i2c::Device>) -> Result<()> {
+async fn generic_init(
+    i2c_dev: &mut Box<dyn i2c::Device>,
+    offset: DiodeOffset,
+    mode: ConversionMode,
+) -> Result<()> {
     i2c_dev
-        .write_readback(REG_CONFIG_W, REG_CONFIG, CONFIG_RANGE)
+        .write_readback(REG_CONFIG_W, REG_CONFIG, CONFIG_RANGE | mode.config_bits())
         .await?;
-    i2c_dev.write(REG_OFFSET, 0).await?;
+    i2c_dev.write(REG_OFFSET, offset.whole as u8).await?;
+    i2c_dev.write(REG_OFFSET_FRAC, offset.fract).await?;
+    Ok(())
+}
+
+/// Program the periodic conversion rate.
+async fn set_conversion_rate(
+    i2c_dev: &mut Box<dyn i2c::Device>,
+    rate: ConversionRate,
+) -> Result<()> {
+    i2c_dev
+        .write_readback(REG_CONV_RATE_W, REG_CONV_RATE, rate.code())
+        .await
+}
+
+/// Put the chip in standby and trigger a single conversion, waiting for it to complete. Leaves
+/// the result ready to be read back by the caller with the appropriate `read_temperature*`.
+///
+/// NOTE: polls a bounded number of times rather than erroring out on a stuck BUSY bit, since
+/// this crate's `Error` enum lives in `lib.rs`, which isn't part of this checkout - there's no
+/// type here to safely construct a new timeout variant from.
+async fn trigger_one_shot(i2c_dev: &mut Box<dyn i2c::Device>) -> Result<()> {
+    i2c_dev
+        .write_readback(
+            REG_CONFIG_W,
+            REG_CONFIG,
+            CONFIG_RANGE | ConversionMode::Standby.config_bits(),
+        )
+        .await?;
+    i2c_dev.write(REG_ONE_SHOT, 0).await?;
+    for _ in 0..ONE_SHOT_MAX_POLLS {
+        if i2c_dev.read(REG_STATUS).await? & STATUS_BUSY == 0 {
+            break;
+        }
+    }
     Ok(())
 }
 
 /// TMP451 driver (most common type, has remote sensor)
 pub struct TMP451 {
     i2c_dev: Box<dyn i2c::Device>,
+    offset: DiodeOffset,
+    mode: ConversionMode,
 }
 
 impl TMP451 {
-    pub fn new(i2c_dev: Box<dyn i2c::Device>) -> Box<dyn Sensor> {
-        Box::new(Self { i2c_dev }) as Box<dyn Sensor>
+    pub fn new(
+        i2c_dev: Box<dyn i2c::Device>,
+        offset: DiodeOffset,
+        mode: ConversionMode,
+    ) -> Box<dyn Sensor> {
+        Box::new(Self {
+            i2c_dev,
+            offset,
+            mode,
+        }) as Box<dyn Sensor>
+    }
+
+    /// Program the ALERT/THERM thresholds.
+    ///
+    /// NOTE: not yet reachable as a `Sensor` method - `Sensor`/`Measurement` are defined in this
+    /// crate's `lib.rs`, which isn't part of this checkout. Once it is, this should become a
+    /// provided `Sensor::configure_limits()` and `ThermalAlarms` should fold into a new
+    /// `Measurement` variant so the fan-control layer can react to hardware thresholds instead
+    /// of polling raw temperatures.
+    pub async fn configure_limits(&mut self, limits: &ThermalLimits) -> Result<()> {
+        configure_limits(&mut self.i2c_dev, limits).await
+    }
+
+    /// Read back and clear the ALERT/THERM latch bits.
+    pub async fn read_and_clear_alarms(&mut self) -> Result<ThermalAlarms> {
+        read_and_clear_alarms(&mut self.i2c_dev).await
+    }
+
+    /// Program the periodic conversion rate. See `TMP451::configure_limits` for the caveat about
+    /// this not yet being reachable through the `Sensor` trait.
+    pub async fn set_conversion_rate(&mut self, rate: ConversionRate) -> Result<()> {
+        set_conversion_rate(&mut self.i2c_dev, rate).await
+    }
+
+    /// Put the chip in standby, trigger a single conversion and read back the result. See
+    /// `TMP451::configure_limits` for the caveat about this not yet being reachable through the
+    /// `Sensor` trait.
+    pub async fn one_shot(&mut self) -> Result<Temperature> {
+        trigger_one_shot(&mut self.i2c_dev).await?;
+        read_temperature(&mut self.i2c_dev, true).await
     }
 }
 
 #[async_trait]
 impl Sensor for TMP451 {
     async fn init(&mut self) -> Result<()> {
-        generic_init(&mut self.i2c_dev).await
+        generic_init(&mut self.i2c_dev, self.offset, self.mode).await
     }
 
     async fn read_temperature(&mut self) -> Result<Temperature> {
@@ -126,18 +397,64 @@ impl Sensor for TMP451 {
 /// ADT7461 driver (almost the same as TMP451)
 pub struct ADT7461 {
     i2c_dev: Box<dyn i2c::Device>,
+    offset: DiodeOffset,
+    mode: ConversionMode,
 }
 
 impl ADT7461 {
-    pub fn new(i2c_dev: Box<dyn i2c::Device>) -> Box<dyn Sensor> {
-        Box::new(Self { i2c_dev }) as Box<dyn Sensor>
+    pub fn new(
+        i2c_dev: Box<dyn i2c::Device>,
+        offset: DiodeOffset,
+        mode: ConversionMode,
+    ) -> Box<dyn Sensor> {
+        Box::new(Self {
+            i2c_dev,
+            offset,
+            mode,
+        }) as Box<dyn Sensor>
+    }
+
+    /// Program the ALERT/THERM thresholds. See `TMP451::configure_limits` for the caveat about
+    /// this not yet being reachable through the `Sensor` trait.
+    pub async fn configure_limits(&mut self, limits: &ThermalLimits) -> Result<()> {
+        configure_limits(&mut self.i2c_dev, limits).await
+    }
+
+    /// Read back and clear the ALERT/THERM latch bits.
+    pub async fn read_and_clear_alarms(&mut self) -> Result<ThermalAlarms> {
+        read_and_clear_alarms(&mut self.i2c_dev).await
+    }
+
+    /// Select the non-ideality (beta) compensation range for the remote diode. See
+    /// `TMP451::configure_limits` for the caveat about this not yet being reachable through the
+    /// `Sensor` trait.
+    pub async fn set_beta_compensation(&mut self, range: BetaCompensationRange) -> Result<()> {
+        let value = match range {
+            BetaCompensationRange::Standard => 0x00,
+            BetaCompensationRange::LowBeta => 0x08,
+        };
+        self.i2c_dev.write(REG_BETA_RANGE, value).await
+    }
+
+    /// Program the periodic conversion rate. See `TMP451::configure_limits` for the caveat about
+    /// this not yet being reachable through the `Sensor` trait.
+    pub async fn set_conversion_rate(&mut self, rate: ConversionRate) -> Result<()> {
+        set_conversion_rate(&mut self.i2c_dev, rate).await
+    }
+
+    /// Put the chip in standby, trigger a single conversion and read back the result. See
+    /// `TMP451::configure_limits` for the caveat about this not yet being reachable through the
+    /// `Sensor` trait.
+    pub async fn one_shot(&mut self) -> Result<Temperature> {
+        trigger_one_shot(&mut self.i2c_dev).await?;
+        read_temperature(&mut self.i2c_dev, false).await
     }
 }
 
 #[async_trait]
 impl Sensor for ADT7461 {
     async fn init(&mut self) -> Result<()> {
-        generic_init(&mut self.i2c_dev).await
+        generic_init(&mut self.i2c_dev, self.offset, self.mode).await
     }
 
     async fn read_temperature(&mut self) -> Result<Temperature> {
@@ -148,18 +465,42 @@ impl Sensor for ADT7461 {
 /// NCT218 driver (only local temperature)
 pub struct NCT218 {
     i2c_dev: Box<dyn i2c::Device>,
+    offset: DiodeOffset,
+    mode: ConversionMode,
 }
 
 impl NCT218 {
-    pub fn new(i2c_dev: Box<dyn i2c::Device>) -> Box<dyn Sensor> {
-        Box::new(Self { i2c_dev }) as Box<dyn Sensor>
+    pub fn new(
+        i2c_dev: Box<dyn i2c::Device>,
+        offset: DiodeOffset,
+        mode: ConversionMode,
+    ) -> Box<dyn Sensor> {
+        Box::new(Self {
+            i2c_dev,
+            offset,
+            mode,
+        }) as Box<dyn Sensor>
+    }
+
+    /// Program the periodic conversion rate. See `TMP451::configure_limits` for the caveat about
+    /// this not yet being reachable through the `Sensor` trait.
+    pub async fn set_conversion_rate(&mut self, rate: ConversionRate) -> Result<()> {
+        set_conversion_rate(&mut self.i2c_dev, rate).await
+    }
+
+    /// Put the chip in standby, trigger a single conversion and read back the result. See
+    /// `TMP451::configure_limits` for the caveat about this not yet being reachable through the
+    /// `Sensor` trait.
+    pub async fn one_shot(&mut self) -> Result<Temperature> {
+        trigger_one_shot(&mut self.i2c_dev).await?;
+        read_temperature_local(&mut self.i2c_dev).await
     }
 }
 
 #[async_trait]
 impl Sensor for NCT218 {
     async fn init(&mut self) -> Result<()> {
-        generic_init(&mut self.i2c_dev).await
+        generic_init(&mut self.i2c_dev, self.offset, self.mode).await
     }
 
     async fn read_temperature(&mut self) -> Result<Temperature> {
@@ -212,11 +553,12 @@ mod test {
             InitReg(REG_CONFIG_W, 0x00),
             // Config offset to 0
             InitReg(REG_OFFSET, 0x7f),
+            InitReg(REG_OFFSET_FRAC, 0x00),
         ];
 
         // Check "working conditions" on TMP451
         let mut dev = make_i2c_device(&ok_regs);
-        let mut sensor = TMP451::new(Box::new(dev.clone()));
+        let mut sensor = TMP451::new(Box::new(dev.clone()), DiodeOffset::default(), ConversionMode::Continuous);
         sensor.init().await.unwrap();
         check_config_ok(&mut dev).await;
         assert_eq!(
@@ -229,7 +571,7 @@ mod test {
 
         // Check "working conditions" on ADT7461
         let mut dev = make_i2c_device(&ok_regs);
-        let mut sensor = ADT7461::new(Box::new(dev.clone()));
+        let mut sensor = ADT7461::new(Box::new(dev.clone()), DiodeOffset::default(), ConversionMode::Continuous);
         sensor.init().await.unwrap();
         check_config_ok(&mut dev).await;
         assert_eq!(
@@ -242,7 +584,7 @@ mod test {
 
         // Check "working conditions" on NCT218
         let mut dev = make_i2c_device(&ok_regs);
-        let mut sensor = NCT218::new(Box::new(dev.clone()));
+        let mut sensor = NCT218::new(Box::new(dev.clone()), DiodeOffset::default(), ConversionMode::Continuous);
         sensor.init().await.unwrap();
         check_config_ok(&mut dev).await;
         assert_eq!(
@@ -274,11 +616,12 @@ mod test {
             InitReg(REG_CONFIG_W, 0x00),
             // Config offset to 0
             InitReg(REG_OFFSET, 0x7f),
+            InitReg(REG_OFFSET_FRAC, 0x00),
         ];
 
         // Test TMP451
         let mut dev = make_i2c_device(&ok_regs);
-        let mut sensor = TMP451::new(Box::new(dev.clone()));
+        let mut sensor = TMP451::new(Box::new(dev.clone()), DiodeOffset::default(), ConversionMode::Continuous);
         sensor.init().await.unwrap();
         check_config_ok(&mut dev).await;
         assert_eq!(
@@ -291,7 +634,7 @@ mod test {
 
         // Test ADT7461
         let mut dev = make_i2c_device(&ok_regs);
-        let mut sensor = ADT7461::new(Box::new(dev.clone()));
+        let mut sensor = ADT7461::new(Box::new(dev.clone()), DiodeOffset::default(), ConversionMode::Continuous);
         sensor.init().await.unwrap();
         check_config_ok(&mut dev).await;
         assert_eq!(
@@ -322,11 +665,12 @@ mod test {
             InitReg(REG_CONFIG_W, 0x00),
             // Config offset to 0
             InitReg(REG_OFFSET, 0x7f),
+            InitReg(REG_OFFSET_FRAC, 0x00),
         ];
 
         // Test TMP451
         let mut dev = make_i2c_device(&ok_regs);
-        let mut sensor = TMP451::new(Box::new(dev.clone()));
+        let mut sensor = TMP451::new(Box::new(dev.clone()), DiodeOffset::default(), ConversionMode::Continuous);
         sensor.init().await.unwrap();
         check_config_ok(&mut dev).await;
         assert_eq!(
@@ -339,7 +683,7 @@ mod test {
 
         // Test ADT7461
         let mut dev = make_i2c_device(&ok_regs);
-        let mut sensor = ADT7461::new(Box::new(dev.clone()));
+        let mut sensor = ADT7461::new(Box::new(dev.clone()), DiodeOffset::default(), ConversionMode::Continuous);
         sensor.init().await.unwrap();
         check_config_ok(&mut dev).await;
         assert_eq!(
@@ -350,4 +694,199 @@ mod test {
             }
         );
     }
+
+    #[tokio::test]
+    async fn test_configure_limits_writes_and_verifies_all_registers() {
+        let regs = [
+            // pre-set the read-back registers to the values written below so `write_readback`
+            // has something to verify against, same hack as `check_config_ok` above
+            InitReg(REG_LOCAL_HIGH_LIMIT, encode_temp(80.0)),
+            InitReg(REG_LOCAL_HIGH_LIMIT_W, 0),
+            InitReg(REG_LOCAL_LOW_LIMIT, encode_temp(0.0)),
+            InitReg(REG_LOCAL_LOW_LIMIT_W, 0),
+            InitReg(REG_REMOTE_HIGH_LIMIT, encode_temp(95.0)),
+            InitReg(REG_REMOTE_HIGH_LIMIT_W, 0),
+            InitReg(REG_REMOTE_LOW_LIMIT, encode_temp(5.0)),
+            InitReg(REG_REMOTE_LOW_LIMIT_W, 0),
+            InitReg(REG_REMOTE_THERM_LIMIT, 0),
+            InitReg(REG_THERM_HYSTERESIS, 0),
+        ];
+        let limits = ThermalLimits {
+            local_high: 80.0,
+            local_low: 0.0,
+            remote_high: 95.0,
+            remote_low: 5.0,
+            remote_therm: 105.0,
+            therm_hysteresis: 4,
+        };
+
+        let mut dev = make_i2c_device(&regs);
+        let mut sensor = TMP451 {
+            i2c_dev: Box::new(dev.clone()),
+            offset: DiodeOffset::default(),
+            mode: ConversionMode::Continuous,
+        };
+        sensor.configure_limits(&limits).await.unwrap();
+        assert_eq!(
+            dev.read(REG_LOCAL_HIGH_LIMIT_W).await.unwrap(),
+            encode_temp(80.0)
+        );
+        assert_eq!(
+            dev.read(REG_LOCAL_LOW_LIMIT_W).await.unwrap(),
+            encode_temp(0.0)
+        );
+        assert_eq!(
+            dev.read(REG_REMOTE_HIGH_LIMIT_W).await.unwrap(),
+            encode_temp(95.0)
+        );
+        assert_eq!(
+            dev.read(REG_REMOTE_LOW_LIMIT_W).await.unwrap(),
+            encode_temp(5.0)
+        );
+        assert_eq!(
+            dev.read(REG_REMOTE_THERM_LIMIT).await.unwrap(),
+            encode_temp(105.0)
+        );
+        assert_eq!(dev.read(REG_THERM_HYSTERESIS).await.unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_read_and_clear_alarms_decodes_tripped_channels() {
+        let regs = [InitReg(
+            REG_STATUS,
+            STATUS_REMOTE_HIGH | STATUS_REMOTE_THERM,
+        )];
+
+        let mut dev = make_i2c_device(&regs);
+        let mut sensor = ADT7461 {
+            i2c_dev: Box::new(dev.clone()),
+            offset: DiodeOffset::default(),
+            mode: ConversionMode::Continuous,
+        };
+        assert_eq!(
+            sensor.read_and_clear_alarms().await.unwrap(),
+            ThermalAlarms {
+                local_high: false,
+                local_low: false,
+                remote_high: true,
+                remote_low: false,
+                remote_therm: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_diode_offset_from_millidegrees() {
+        assert_eq!(
+            DiodeOffset::from_millidegrees(2_500),
+            DiodeOffset { whole: 2, fract: 128 }
+        );
+        assert_eq!(
+            DiodeOffset::from_millidegrees(-2_500),
+            DiodeOffset {
+                whole: -2,
+                fract: 128
+            }
+        );
+        assert_eq!(
+            DiodeOffset::from_millidegrees(0),
+            DiodeOffset { whole: 0, fract: 0 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_init_programs_configured_offset() {
+        let regs = [
+            InitReg(REG_CONFIG, 0x04),
+            InitReg(REG_CONFIG_W, 0x00),
+            InitReg(REG_OFFSET, 0x7f),
+            InitReg(REG_OFFSET_FRAC, 0x7f),
+        ];
+        let offset = DiodeOffset::from_millidegrees(-2_500);
+
+        let dev = make_i2c_device(&regs);
+        let mut sensor = TMP451::new(Box::new(dev.clone()), offset, ConversionMode::Continuous);
+        sensor.init().await.unwrap();
+
+        assert_eq!(dev.read(REG_OFFSET).await.unwrap(), offset.whole as u8);
+        assert_eq!(dev.read(REG_OFFSET_FRAC).await.unwrap(), offset.fract);
+    }
+
+    #[tokio::test]
+    async fn test_set_beta_compensation_writes_register() {
+        let regs = [InitReg(REG_BETA_RANGE, 0x00)];
+
+        let mut dev = make_i2c_device(&regs);
+        let mut sensor = ADT7461 {
+            i2c_dev: Box::new(dev.clone()),
+            offset: DiodeOffset::default(),
+            mode: ConversionMode::Continuous,
+        };
+        sensor
+            .set_beta_compensation(BetaCompensationRange::LowBeta)
+            .await
+            .unwrap();
+
+        assert_eq!(dev.read(REG_BETA_RANGE).await.unwrap(), 0x08);
+    }
+
+    #[tokio::test]
+    async fn test_set_conversion_rate_writes_register() {
+        let regs = [
+            // pre-set so `write_readback` has something to verify against, same hack as
+            // `check_config_ok` above
+            InitReg(REG_CONV_RATE, ConversionRate::Hz4.code()),
+            InitReg(REG_CONV_RATE_W, 0),
+        ];
+
+        let mut dev = make_i2c_device(&regs);
+        let mut sensor = TMP451 {
+            i2c_dev: Box::new(dev.clone()),
+            offset: DiodeOffset::default(),
+            mode: ConversionMode::Continuous,
+        };
+        sensor.set_conversion_rate(ConversionRate::Hz4).await.unwrap();
+
+        assert_eq!(
+            dev.read(REG_CONV_RATE_W).await.unwrap(),
+            ConversionRate::Hz4.code()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_one_shot_puts_chip_in_standby_and_reads_back_result() {
+        let regs = [
+            // pre-set so `write_readback` in `trigger_one_shot` succeeds, same hack as
+            // `check_config_ok` above
+            InitReg(REG_CONFIG, CONFIG_RANGE | CONFIG_STANDBY),
+            InitReg(REG_CONFIG_W, 0),
+            InitReg(REG_ONE_SHOT, 0),
+            // not busy, so `trigger_one_shot` returns after the first poll
+            InitReg(REG_STATUS, 0x00),
+            // 23 deg
+            InitReg(REG_LOCAL_TEMP, 0x57),
+            // 41 deg
+            InitReg(REG_REMOTE_TEMP, 0x69),
+            InitReg(REG_LOCAL_FRAC_TEMP, 0x00),
+            InitReg(REG_REMOTE_FRAC_TEMP, 0x00),
+        ];
+
+        let mut dev = make_i2c_device(&regs);
+        let mut sensor = TMP451 {
+            i2c_dev: Box::new(dev.clone()),
+            offset: DiodeOffset::default(),
+            mode: ConversionMode::Continuous,
+        };
+        assert_eq!(
+            sensor.one_shot().await.unwrap(),
+            Temperature {
+                local: Measurement::Ok(23.0),
+                remote: Measurement::Ok(41.0),
+            }
+        );
+        assert_eq!(
+            dev.read(REG_CONFIG_W).await.unwrap() & CONFIG_STANDBY,
+            CONFIG_STANDBY
+        );
+    }
 }
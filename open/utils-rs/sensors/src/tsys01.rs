@@ -0,0 +1,155 @@
+// Copyright (C) 2020  Braiins Systems s.r.o.
+//
+// This file is part of Braiins Open-Source Initiative (BOSI).
+//
+// BOSI is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Please, keep in mind that we may also license BOSI or any part thereof
+// under a proprietary license. For more information on the terms and conditions
+// of such proprietary license or if you have any other questions, please
+// contact us at opensource@braiins.com.
+
+//! Driver implementation of sensor driver for TSYS01, a Measurement-Specialties high-accuracy
+//! local temperature sensor. Unlike the TMP451 family (see `tmp451`), it has no addressable
+//! registers: every operation is a bare command byte, and the result is a raw ADC conversion that
+//! has to be run through on-chip calibration coefficients rather than a pre-scaled reading.
+
+use super::Result;
+use super::{Measurement, Sensor, Temperature};
+use ii_async_i2c as i2c;
+
+use async_trait::async_trait;
+use std::boxed::Box;
+use std::time::Duration;
+use tokio::time::delay_for;
+
+const CMD_RESET: u8 = 0x1e;
+const CMD_START_CONVERSION: u8 = 0x48;
+const CMD_READ_ADC: u8 = 0x00;
+const PROM_K4: u8 = 0xa2;
+const PROM_K3: u8 = 0xa4;
+const PROM_K2: u8 = 0xa6;
+const PROM_K1: u8 = 0xa8;
+const PROM_K0: u8 = 0xaa;
+
+/// Conversion takes up to 9.04 ms per the datasheet; pad it a little to avoid reading a stale
+/// (or still in-flight) result.
+const CONVERSION_DELAY: Duration = Duration::from_millis(10);
+
+/// Calibration coefficients read out of PROM once at `init()` time, used to linearize the raw
+/// ADC reading in `compute_temperature`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Coefficients {
+    k0: u16,
+    k1: u16,
+    k2: u16,
+    k3: u16,
+    k4: u16,
+}
+
+/// Read one 16-bit big-endian PROM word following `cmd`.
+async fn read_prom_word(i2c_dev: &mut Box<dyn i2c::Device>, cmd: u8) -> Result<u16> {
+    let mut buf = [0u8; 2];
+    i2c_dev.read_command(cmd, &mut buf).await?;
+    Ok(u16::from_be_bytes(buf))
+}
+
+/// Reset the chip, then read back the five calibration words.
+async fn read_coefficients(i2c_dev: &mut Box<dyn i2c::Device>) -> Result<Coefficients> {
+    i2c_dev.write_command(CMD_RESET).await?;
+    Ok(Coefficients {
+        k4: read_prom_word(i2c_dev, PROM_K4).await?,
+        k3: read_prom_word(i2c_dev, PROM_K3).await?,
+        k2: read_prom_word(i2c_dev, PROM_K2).await?,
+        k1: read_prom_word(i2c_dev, PROM_K1).await?,
+        k0: read_prom_word(i2c_dev, PROM_K0).await?,
+    })
+}
+
+/// Trigger a conversion, wait for it to complete, and read back the top 16 bits of the 24-bit
+/// ADC result.
+async fn read_adc16(i2c_dev: &mut Box<dyn i2c::Device>) -> Result<u16> {
+    i2c_dev.write_command(CMD_START_CONVERSION).await?;
+    delay_for(CONVERSION_DELAY).await;
+
+    let mut buf = [0u8; 3];
+    i2c_dev.read_command(CMD_READ_ADC, &mut buf).await?;
+    Ok(u16::from_be_bytes([buf[0], buf[1]]))
+}
+
+/// Apply the datasheet's fixed-point calibration polynomial to a raw ADC16 sample.
+fn compute_temperature(coeffs: &Coefficients, adc16: u16) -> f32 {
+    let adc16 = adc16 as f64;
+    let k0 = coeffs.k0 as f64;
+    let k1 = coeffs.k1 as f64;
+    let k2 = coeffs.k2 as f64;
+    let k3 = coeffs.k3 as f64;
+    let k4 = coeffs.k4 as f64;
+
+    let temp = -2.0 * k4 * 1e-21 * adc16.powi(4) + 4.0 * k3 * 1e-16 * adc16.powi(3)
+        - 2.0 * k2 * 1e-11 * adc16.powi(2)
+        + 1.0 * k1 * 1e-6 * adc16
+        - 1.5 * k0 * 1e-2;
+    temp as f32
+}
+
+/// TSYS01 driver
+pub struct TSYS01 {
+    i2c_dev: Box<dyn i2c::Device>,
+    coeffs: Coefficients,
+}
+
+impl TSYS01 {
+    pub fn new(i2c_dev: Box<dyn i2c::Device>) -> Box<dyn Sensor> {
+        Box::new(Self {
+            i2c_dev,
+            coeffs: Coefficients::default(),
+        }) as Box<dyn Sensor>
+    }
+}
+
+#[async_trait]
+impl Sensor for TSYS01 {
+    async fn init(&mut self) -> Result<()> {
+        self.coeffs = read_coefficients(&mut self.i2c_dev).await?;
+        Ok(())
+    }
+
+    async fn read_temperature(&mut self) -> Result<Temperature> {
+        let adc16 = read_adc16(&mut self.i2c_dev).await?;
+        Ok(Temperature {
+            local: Measurement::Ok(compute_temperature(&self.coeffs, adc16)),
+            remote: Measurement::NotPresent,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compute_temperature_applies_calibration_polynomial() {
+        let coeffs = Coefficients {
+            k0: 18000,
+            k1: 22000,
+            k2: 26000,
+            k3: 20000,
+            k4: 14000,
+        };
+
+        // hand-computed from the datasheet polynomial for these coefficients/ADC value
+        assert_eq!(compute_temperature(&coeffs, 24000), 59.782272f32);
+    }
+}